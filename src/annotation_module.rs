@@ -0,0 +1,367 @@
+//! annotation_module.rs - Notes on lines, kept beside the file (`note`)
+//!
+//! ## Project Context
+//! A code-review style note ("check this bound") belongs to a line, but
+//! must not be written into the file. Notes go in a sidecar file under
+//! the data directory, one per annotated file:
+//! ```text
+//! lines_data/annotations/3f9a1c0e52b7d416.txt
+//!
+//! # lines notes: /home/user/notes/todo.txt
+//! 12    9f3a1c20    check this loop
+//! 40    00c1d2e3    ask about the date format
+//! ```
+//! The sidecar is named by the SHA-256 of the file's path (first 16 hex
+//! digits); the first line names the file for a person looking in.
+//! Each note is `{line}\t{CRC32 of the line}\t{note}` (tab separated).
+//!
+//! ## Lines move
+//! Edits above a note shift its line. The line's CRC32 finds it again:
+//! the nearest line with the same content carries the note. A note whose
+//! line changed shows its old number and "(line changed)".
+//!
+//! ## Memory
+//! Line contents are hashed streaming, never held: any line length and
+//! any file size. Notes per file are capped at MAX_ANNOTATIONS_PER_FILE.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use super::checksum_module::{Crc32, SHA256_HEX_LENGTH, Sha256, sha256_to_hex};
+
+/// Subdirectory of the data directory holding the sidecar files
+pub const ANNOTATIONS_DIRECTORY_NAME: &str = "annotations";
+
+/// Most notes kept for one file
+pub const MAX_ANNOTATIONS_PER_FILE: usize = 1000;
+
+/// Longest note, in bytes (longer notes are cut at a character boundary)
+pub const MAX_NOTE_BYTES: usize = 200;
+
+/// Hex digits of the path hash used as the sidecar's name
+const SIDECAR_NAME_HEX_DIGITS: usize = 16;
+
+/// Bytes read per step when hashing lines
+const LINE_HASH_CHUNK_SIZE: usize = 4096;
+
+/// Most read steps for one line or one file pass (4 KiB each: 4 TiB)
+const MAX_LINE_HASH_CHUNKS: usize = 1 << 30;
+
+/// A note on one line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    /// Line number when the note was written, 1 = first line
+    pub line: usize,
+    /// CRC32 of the line's bytes, without its newline
+    pub line_hash: u32,
+    /// One line of text, no tabs
+    pub note: String,
+}
+
+/// A note as text for the sidecar: tabs, newlines and other control
+/// characters become spaces, then it is trimmed and cut to MAX_NOTE_BYTES
+pub fn clean_note(note: &str) -> String {
+    let spaced: String = note
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    let trimmed = spaced.trim();
+    let mut end = trimmed.len().min(MAX_NOTE_BYTES);
+    while !trimmed.is_char_boundary(end) {
+        end -= 1;
+    }
+    trimmed[..end].to_string()
+}
+
+/// Sidecar file name for `original`: "3f9a1c0e52b7d416.txt"
+pub fn sidecar_file_name(original: &Path) -> String {
+    let mut sha256 = Sha256::new();
+    sha256.update(original.as_os_str().as_encoded_bytes());
+    let digest = sha256.finalize();
+    let mut hex_buffer = [0u8; SHA256_HEX_LENGTH];
+    let hex = sha256_to_hex(&digest, &mut hex_buffer);
+    format!("{}.txt", &hex[..SIDECAR_NAME_HEX_DIGITS])
+}
+
+/// Sidecar text: a header naming `original`, then one note per line
+pub fn format_annotations(original: &Path, annotations: &[Annotation]) -> String {
+    let mut text = format!("# lines notes: {}\n", original.display());
+    for annotation in annotations {
+        text.push_str(&format!(
+            "{}\t{:08x}\t{}\n",
+            annotation.line, annotation.line_hash, annotation.note
+        ));
+    }
+    text
+}
+
+/// Notes from sidecar text; comment lines and malformed lines are skipped
+pub fn parse_annotations(text: &str) -> Vec<Annotation> {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let line_number = fields.next()?.parse::<usize>().ok()?;
+            let hash_field = fields.next()?;
+            let note = fields.next()?;
+            if line_number == 0 || hash_field.len() != 8 {
+                return None;
+            }
+            Some(Annotation {
+                line: line_number,
+                line_hash: u32::from_str_radix(hash_field, 16).ok()?,
+                note: clean_note(note),
+            })
+        })
+        .take(MAX_ANNOTATIONS_PER_FILE)
+        .collect()
+}
+
+/// The notes on `original` kept in `directory`; none if it has no sidecar
+pub fn load_annotations(directory: &Path, original: &Path) -> io::Result<Vec<Annotation>> {
+    match fs::read_to_string(directory.join(sidecar_file_name(original))) {
+        Ok(text) => Ok(parse_annotations(&text)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes the notes on `original` to its sidecar in `directory`; with
+/// no notes left the sidecar is removed
+pub fn save_annotations(
+    directory: &Path,
+    original: &Path,
+    annotations: &[Annotation],
+) -> io::Result<PathBuf> {
+    let sidecar = directory.join(sidecar_file_name(original));
+    if annotations.is_empty() {
+        match fs::remove_file(&sidecar) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => return Ok(sidecar),
+        }
+    }
+    fs::create_dir_all(directory)?;
+    // Written beside, then renamed: a crash leaves the old notes whole
+    let partial = sidecar.with_extension("partial");
+    fs::write(&partial, format_annotations(original, annotations))?;
+    fs::rename(&partial, &sidecar)?;
+    Ok(sidecar)
+}
+
+/// CRC32 of the line starting at `start_byte`, up to its newline or the
+/// end of the file
+pub fn hash_line_at(file: &mut File, start_byte: u64) -> io::Result<u32> {
+    file.seek(SeekFrom::Start(start_byte))?;
+    let mut crc32 = Crc32::new();
+    let mut buffer = [0u8; LINE_HASH_CHUNK_SIZE];
+    for _ in 0..MAX_LINE_HASH_CHUNKS {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        match buffer[..bytes_read].iter().position(|&byte| byte == b'\n') {
+            Some(newline) => {
+                crc32.update(&buffer[..newline]);
+                break;
+            }
+            None => crc32.update(&buffer[..bytes_read]),
+        }
+    }
+    Ok(crc32.finalize())
+}
+
+/// Where each note's line is now in `path`: the nearest line (by number)
+/// whose CRC32 matches, or None when no line matches any more
+///
+/// One streaming pass over the file, whatever the number of notes.
+pub fn resolve_annotation_lines(
+    path: &Path,
+    annotations: &[Annotation],
+) -> io::Result<Vec<Option<usize>>> {
+    let mut by_hash: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (index, annotation) in annotations.iter().enumerate() {
+        by_hash.entry(annotation.line_hash).or_default().push(index);
+    }
+    let mut resolved: Vec<Option<usize>> = vec![None; annotations.len()];
+    if annotations.is_empty() {
+        return Ok(resolved);
+    }
+
+    let mut reader = BufReader::with_capacity(LINE_HASH_CHUNK_SIZE, File::open(path)?);
+    let mut line_number = 1;
+    let mut crc32 = Crc32::new();
+    let mut line_has_bytes = false;
+    let mut settle_line = |line_number: usize, line_hash: u32| {
+        for &index in by_hash.get(&line_hash).into_iter().flatten() {
+            let wanted = annotations[index].line;
+            let closer = resolved[index]
+                .is_none_or(|best| line_number.abs_diff(wanted) < best.abs_diff(wanted));
+            if closer {
+                resolved[index] = Some(line_number);
+            }
+        }
+    };
+
+    for _ in 0..MAX_LINE_HASH_CHUNKS {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        let chunk_length = chunk.len();
+        let mut rest = chunk;
+        while let Some(newline) = rest.iter().position(|&byte| byte == b'\n') {
+            crc32.update(&rest[..newline]);
+            settle_line(line_number, crc32.finalize());
+            line_number += 1;
+            crc32 = Crc32::new();
+            line_has_bytes = false;
+            rest = &rest[newline + 1..];
+        }
+        crc32.update(rest);
+        line_has_bytes |= !rest.is_empty();
+        reader.consume(chunk_length);
+    }
+    // A last line with no final newline
+    if line_has_bytes {
+        settle_line(line_number, crc32.finalize());
+    }
+    Ok(resolved)
+}
+
+/// The notes in line order for the `notes` screen, each with the line it
+/// is on now (None: its line changed; those come last)
+pub fn notes_in_line_order(
+    annotations: &[Annotation],
+    resolved: &[Option<usize>],
+) -> Vec<(Option<usize>, Annotation)> {
+    let mut entries: Vec<(Option<usize>, Annotation)> = resolved
+        .iter()
+        .copied()
+        .zip(annotations.iter().cloned())
+        .collect();
+    entries.sort_by_key(|(line, annotation)| (line.is_none(), line.unwrap_or(annotation.line)));
+    entries
+}
+
+/// The `notes` screen: numbered notes, where they are now
+/// ("  2. line 40 (was 38)  ask about the date format")
+pub fn notes_screen_text(file_name: &str, entries: &[(Option<usize>, Annotation)]) -> String {
+    let mut text = format!("Notes on {}:\n", file_name);
+    if entries.is_empty() {
+        text.push_str("  (none yet: note TEXT adds one to the cursor's line)\n");
+    }
+    for (number, (line, annotation)) in entries.iter().enumerate() {
+        let place = match line {
+            Some(line) if *line == annotation.line => format!("line {}", line),
+            Some(line) => format!("line {} (was {})", line, annotation.line),
+            None => format!("line {} (line changed)", annotation.line),
+        };
+        text.push_str(&format!(
+            "  {}. {}  {}\n",
+            number + 1,
+            place,
+            annotation.note
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod annotation_tests {
+    use super::*;
+
+    fn crc32_of(bytes: &[u8]) -> u32 {
+        let mut crc32 = Crc32::new();
+        crc32.update(bytes);
+        crc32.finalize()
+    }
+
+    #[test]
+    fn test_sidecar_round_trip() {
+        let original = Path::new("/home/user/notes/todo.txt");
+        let annotations = vec![
+            Annotation {
+                line: 12,
+                line_hash: 0x9f3a_1c20,
+                note: "check this loop".to_string(),
+            },
+            Annotation {
+                line: 40,
+                line_hash: 0x00c1_d2e3,
+                note: "ask about the date format".to_string(),
+            },
+        ];
+        let text = format_annotations(original, &annotations);
+        assert!(text.starts_with("# lines notes: /home/user/notes/todo.txt\n"));
+        assert!(text.contains("40\t00c1d2e3\task about the date format\n"));
+        assert_eq!(parse_annotations(&text), annotations);
+        assert_eq!(parse_annotations("0\t00000000\tx\nx\t1\ty\n12\tzz\n"), []);
+
+        let name = sidecar_file_name(original);
+        assert_eq!(name.len(), SIDECAR_NAME_HEX_DIGITS + ".txt".len());
+        assert_ne!(name, sidecar_file_name(Path::new("/home/user/todo.txt")));
+    }
+
+    #[test]
+    fn test_clean_note() {
+        assert_eq!(clean_note("  a\tb\nc "), "a b c");
+        let long = "é".repeat(MAX_NOTE_BYTES);
+        let cleaned = clean_note(&long);
+        assert!(cleaned.len() <= MAX_NOTE_BYTES);
+        assert!(cleaned.chars().all(|c| c == 'é'));
+    }
+
+    #[test]
+    fn test_notes_follow_moved_lines() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_annotations_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("create test dir");
+        let path = test_dir.join("code.txt");
+        fs::write(&path, "same\nloop\nsame\nend").expect("write file");
+
+        let mut file = File::open(&path).expect("open");
+        assert_eq!(hash_line_at(&mut file, 5).expect("hash"), crc32_of(b"loop"));
+        assert_eq!(hash_line_at(&mut file, 15).expect("hash"), crc32_of(b"end"));
+
+        let note = |line: usize, text: &[u8]| Annotation {
+            line,
+            line_hash: crc32_of(text),
+            note: String::new(),
+        };
+        // Two lines were added above "loop"; "same" is on lines 1 and 3
+        fs::write(&path, "new\nnew\nsame\nloop\nsame\nend").expect("rewrite file");
+        let resolved = resolve_annotation_lines(
+            &path,
+            &[
+                note(2, b"loop"),
+                note(3, b"same"),
+                note(4, b"end"),
+                note(1, b"gone"),
+            ],
+        )
+        .expect("resolve");
+        assert_eq!(resolved, [Some(4), Some(3), Some(6), None]);
+
+        let annotations = [note(2, b"loop"), note(1, b"gone"), note(3, b"same")];
+        let entries = notes_in_line_order(&annotations, &[Some(4), None, Some(3)]);
+        assert_eq!(
+            notes_screen_text("code.txt", &entries),
+            "Notes on code.txt:\n  1. line 3  \n  2. line 4 (was 2)  \n  3. line 1 (line changed)  \n"
+        );
+
+        // Saving no notes removes the sidecar
+        let original = Path::new("/tmp/code.txt");
+        let sidecar = save_annotations(&test_dir, original, &[note(4, b"loop")]).expect("save");
+        assert!(sidecar.exists());
+        assert_eq!(
+            load_annotations(&test_dir, original).expect("load").len(),
+            1
+        );
+        save_annotations(&test_dir, original, &[]).expect("save none");
+        assert!(!sidecar.exists());
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+}
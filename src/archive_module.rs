@@ -0,0 +1,329 @@
+//! archive_module.rs - The backups each save leaves in archive/
+//!
+//! ## Project Context
+//! Every save first copies the file, as it still is on disk, into an
+//! `archive/` directory beside it, named `{timestamp}_{file name}`:
+//! ```text
+//! notes/todo.txt
+//! notes/archive/2026_26_10_14_09_12_33_104233_todo.txt
+//! ```
+//! The timestamp is createarchive_timestamp_with_precision's
+//! ("YYYY_YY_MM_DD_HH_MM_SS", then "_UUUUUU" microseconds). Its fields
+//! have fixed widths. With LINES_TIMESTAMP_FORMAT=iso-basic it is ISO
+//! 8601 basic instead ("20261016T141233.104233Z"). Both forms sort by
+//! their digits, so one directory may hold both.
+//!
+//! Compatibility: archives made before stack_format_it zero-padded {:02}
+//! have space-padded two-digit fields ("2026_26_10_16_11_ 2_48_..."). They
+//! are still listed, a space counting as a leading zero.
+//!
+//! ## What this module does
+//! Finds a file's archives, newest first, and names them for the info
+//! bar. `arc` in the editor shows one read-only (ArchivePreview holds
+//! what to go back to).
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory beside a file that holds its backups
+pub const ARCHIVE_DIRECTORY_NAME: &str = "archive";
+
+/// Widths of the timestamp fields: year, 2-digit year, month, day, hour,
+/// minute, second, then microseconds (may be absent)
+const TIMESTAMP_FIELD_WIDTHS: [usize; 8] = [4, 2, 2, 2, 2, 2, 2, 6];
+
+/// ISO 8601 basic timestamp: "YYYYMMDDTHHMMSS.UUUUUUZ"
+const ISO_BASIC_TIMESTAMP_LENGTH: usize = 23;
+
+/// Most directory entries looked at when listing archives
+const MAX_ARCHIVE_DIRECTORY_ENTRIES: usize = 1_000_000;
+
+/// `archive/` beside `original`, or None for a path with no parent
+pub fn archive_directory(original: &Path) -> Option<PathBuf> {
+    original
+        .parent()
+        .map(|parent| parent.join(ARCHIVE_DIRECTORY_NAME))
+}
+
+/// The timestamp of an archive of `file_name`, or None if `archive_name`
+/// is not one: ("2026_26_10_14_09_12_33_104233_todo.txt", "todo.txt")
+/// gives "2026_26_10_14_09_12_33_104233"
+pub fn archive_timestamp<'a>(archive_name: &'a str, file_name: &str) -> Option<&'a str> {
+    let timestamp = archive_name.strip_suffix(file_name)?.strip_suffix('_')?;
+    if is_iso_basic_timestamp(timestamp) {
+        return Some(timestamp);
+    }
+    let field_count = timestamp.split('_').count();
+    let well_formed = (field_count == 7 || field_count == 8)
+        && timestamp
+            .split('_')
+            .zip(TIMESTAMP_FIELD_WIDTHS)
+            .all(|(field, width)| {
+                // trim: old space-padded names (see the module docs)
+                field.len() == width
+                    && field
+                        .trim_start_matches(' ')
+                        .bytes()
+                        .all(|b| b.is_ascii_digit())
+                    && !field.ends_with(' ')
+            });
+    well_formed.then_some(timestamp)
+}
+
+/// Whether `timestamp` is "YYYYMMDDTHHMMSS.UUUUUUZ"
+fn is_iso_basic_timestamp(timestamp: &str) -> bool {
+    timestamp.len() == ISO_BASIC_TIMESTAMP_LENGTH
+        && timestamp
+            .bytes()
+            .enumerate()
+            .all(|(index, byte)| match index {
+                8 => byte == b'T',
+                15 => byte == b'.',
+                22 => byte == b'Z',
+                _ => byte.is_ascii_digit(),
+            })
+}
+
+/// "YYYYMMDDHHMMSSUUUUUU" from either timestamp form (no microseconds:
+/// zeros), so archives named in both sort together by time
+fn timestamp_digits(timestamp: &str) -> String {
+    if is_iso_basic_timestamp(timestamp) {
+        return timestamp.chars().filter(char::is_ascii_digit).collect();
+    }
+    let mut digits: String = timestamp
+        .split('_')
+        .enumerate()
+        // The second field repeats the year's last two digits
+        .filter(|&(index, _)| index != 1)
+        // Old space-padded names (see the module docs)
+        .map(|(_, field)| field.replace(' ', "0"))
+        .collect();
+    if digits.len() == 14 {
+        digits.push_str("000000");
+    }
+    digits
+}
+
+/// The archives of `original`, newest first; none if there is no
+/// archive directory
+pub fn list_archives(original: &Path) -> io::Result<Vec<PathBuf>> {
+    let (Some(directory), Some(file_name)) = (
+        archive_directory(original),
+        original.file_name().and_then(|name| name.to_str()),
+    ) else {
+        return Ok(Vec::new());
+    };
+    let entries = match fs::read_dir(&directory) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut archives: Vec<(String, PathBuf)> = Vec::new();
+    for entry in entries.take(MAX_ARCHIVE_DIRECTORY_ENTRIES) {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(timestamp) = name
+            .to_str()
+            .and_then(|name| archive_timestamp(name, file_name))
+        else {
+            continue;
+        };
+        if entry.file_type()?.is_file() {
+            archives.push((timestamp_digits(timestamp), entry.path()));
+        }
+    }
+    archives.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(archives.into_iter().map(|(_, path)| path).collect())
+}
+
+/// "26-10-14 09:12:33" for an archive of `file_name`, sized for the
+/// info bar, in local time `utc_offset_minutes` from the UTC of the name
+/// (LINES_UTC_OFFSET); None if `archive` is not one
+pub fn archive_label(archive: &Path, file_name: &str, utc_offset_minutes: i32) -> Option<String> {
+    let name = archive.file_name()?.to_str()?;
+    let digits = timestamp_digits(archive_timestamp(name, file_name)?);
+    let field = |range: std::ops::Range<usize>| digits[range].parse::<i64>().ok();
+
+    let days = days_from_civil(field(0..4)?, field(4..6)?, field(6..8)?);
+    let local_seconds = days * SECONDS_PER_DAY
+        + field(8..10)? * 3600
+        + field(10..12)? * 60
+        + field(12..14)?
+        + i64::from(utc_offset_minutes) * 60;
+    let (year, month, day) = civil_from_days(local_seconds.div_euclid(SECONDS_PER_DAY));
+    let second_of_day = local_seconds.rem_euclid(SECONDS_PER_DAY);
+    Some(format!(
+        "{:02}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year.rem_euclid(100),
+        month,
+        day,
+        second_of_day / 3600,
+        second_of_day % 3600 / 60,
+        second_of_day % 60
+    ))
+}
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Days from 1970-01-01 to a Gregorian date (negative before it)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Years start in March, so a leap day ends its year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The Gregorian (year, month, day) `days` after 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    (year_of_era + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// An archive shown read-only in place of the file (`arc`), and the
+/// file's view to go back to
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchivePreview {
+    /// The archive shown; 1 is the newest
+    pub archive_path: PathBuf,
+    pub number: usize,
+    /// The file's read-copy and view-without-copy flag
+    pub file_read_copy_path: Option<PathBuf>,
+    pub file_read_copy_deferred: bool,
+    /// The file's window and cursor
+    pub file_top_line: usize,
+    pub file_top_byte: u64,
+    pub file_horizontal_offset: usize,
+    pub file_cursor_row: usize,
+    pub file_cursor_col: usize,
+}
+
+#[cfg(test)]
+mod archive_tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_timestamp() {
+        assert_eq!(
+            archive_timestamp("2026_26_10_14_09_12_33_104233_todo.txt", "todo.txt"),
+            Some("2026_26_10_14_09_12_33_104233")
+        );
+        assert_eq!(
+            archive_timestamp("2026_26_10_14_09_12_33_todo.txt", "todo.txt"),
+            Some("2026_26_10_14_09_12_33")
+        );
+        // Old space-padded names are still archives
+        assert_eq!(
+            archive_timestamp("2026_26_10_16_11_ 2_48_896015_todo.txt", "todo.txt"),
+            Some("2026_26_10_16_11_ 2_48_896015")
+        );
+        assert_eq!(
+            archive_timestamp("2026_26_10_16_11_2 _48_896015_todo.txt", "todo.txt"),
+            None
+        );
+        // Another file whose name ends the same way
+        assert_eq!(
+            archive_timestamp("2026_26_10_14_09_12_33_104233_old_todo.txt", "todo.txt"),
+            None
+        );
+        assert_eq!(
+            archive_timestamp("2026_26_10_14_09_12_33_12_todo.txt", "todo.txt"),
+            None
+        );
+        assert_eq!(archive_timestamp("todo.txt", "todo.txt"), None);
+
+        // LINES_TIMESTAMP_FORMAT=iso-basic
+        assert_eq!(
+            archive_timestamp("20261014T091233.104233Z_todo.txt", "todo.txt"),
+            Some("20261014T091233.104233Z")
+        );
+        assert_eq!(
+            archive_timestamp("20261014T091233Z_todo.txt", "todo.txt"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_list_archives_newest_first() {
+        let test_dir = std::env::temp_dir().join(format!("lines_archives_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&test_dir);
+        let archive_dir = test_dir.join(ARCHIVE_DIRECTORY_NAME);
+        fs::create_dir_all(&archive_dir).expect("create archive dir");
+        let original = test_dir.join("todo.txt");
+        assert_eq!(
+            list_archives(&test_dir.join("none.txt")).expect("list"),
+            Vec::<PathBuf>::new()
+        );
+
+        for name in [
+            "2026_26_10_14_09_12_33_104233_todo.txt",
+            "2026_26_10_15_08_00_00_000001_todo.txt",
+            "2026_26_10_15_ 9_ 0_ 0_000001_todo.txt",
+            "2025_25_12_31_23_59_59_todo.txt",
+            "20261014T120000.000000Z_todo.txt",
+            "2026_26_10_16_08_00_00_000001_other.txt",
+            "notes_todo.txt",
+        ] {
+            fs::write(archive_dir.join(name), name).expect("write archive");
+        }
+
+        let archives = list_archives(&original).expect("list");
+        let names: Vec<_> = archives
+            .iter()
+            .map(|path| {
+                path.file_name()
+                    .expect("file name")
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "2026_26_10_15_ 9_ 0_ 0_000001_todo.txt",
+                "2026_26_10_15_08_00_00_000001_todo.txt",
+                "20261014T120000.000000Z_todo.txt",
+                "2026_26_10_14_09_12_33_104233_todo.txt",
+                "2025_25_12_31_23_59_59_todo.txt",
+            ]
+        );
+        assert_eq!(
+            archive_label(&archives[0], "todo.txt", 0).as_deref(),
+            Some("26-10-15 09:00:00")
+        );
+        assert_eq!(
+            archive_label(&archives[2], "todo.txt", 0).as_deref(),
+            Some("26-10-14 12:00:00")
+        );
+        assert_eq!(
+            archive_label(&archives[3], "todo.txt", 0).as_deref(),
+            Some("26-10-14 09:12:33")
+        );
+        // LINES_UTC_OFFSET: the label moves, across a day and a year too
+        assert_eq!(
+            archive_label(&archives[3], "todo.txt", -(9 * 60 + 30)).as_deref(),
+            Some("26-10-13 23:42:33")
+        );
+        assert_eq!(
+            archive_label(&archives[4], "todo.txt", 60).as_deref(),
+            Some("26-01-01 00:59:59")
+        );
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+}
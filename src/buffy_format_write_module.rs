@@ -6,6 +6,8 @@
 //! This module performs string formatting and terminal output with ZERO heap
 //! allocation. All operations use stack-allocated buffers. No String, no Vec,
 //! no .to_string(), no dynamic memory allocation.
+//! (One exception by design: BuffyFrame holds a single buffer, allocated
+//! once and reused, so a whole TUI frame goes out in one write.)
 //!
 //! ## Design Philosophy
 //! - User provides output buffers (for string building)
@@ -15,15 +17,19 @@
 //!
 //! ## Memory Model
 //! - Number conversions: Stack buffers (max 20 bytes per number)
-//! - ANSI styling: Stack buffers (max 64 bytes)
+//! - ANSI styling: Stack buffers (max 64 bytes); named Style helpers write
+//!   into the caller's buffer
 //! - Template processing: Read-only, no allocation
 //! - Terminal output: Direct write, no intermediate storage
+//! - Sink output: stderr or a borrowed writer (e.g. the error log)
+//! - Frame output: BuffyFrame buffer, filled per frame, flushed in one write
 //! - String building: User-provided buffer, zero allocation
 //!
 //! ## Limitations (By Design)
 //! - Max 8 format arguments per call (prevents stack overflow)
 //! - User must provide adequate output buffer (we validate and return None if too small)
 //! - Max 64 characters width for alignment (prevents runaway padding)
+//! - Max 12 digits of float precision; floats beyond u64 range are an error
 
 use std::io::{self, Write};
 use std::path::Path;
@@ -140,6 +146,7 @@ pub struct BuffyStyles {
 /// - Str: Existing string slices
 /// - U8, U16, U32, U64, Usize: Unsigned integers (stack-converted)
 /// - I8, I16, I32, I64, Isize: Signed integers (stack-converted)
+/// - F64: Floats, fixed-point with `{:.N}` precision (stack-converted)
 /// - U8Hex, U16Hex, U32Hex: Hex formatting (stack-converted)
 /// - Bool: true/false
 /// - Char: Single character
@@ -152,16 +159,20 @@ pub enum BuffyFormatArg<'a> {
     // U8(u8),
     // U16(u16),
     // U32(u32),
-    // U64(u64),
+    U64(u64),
     Usize(usize),
 
     // Signed integers
     // I8(i8),
     // I16(i16),
     // I32(i32),
-    // I64(i64),
+    #[allow(dead_code)] // API for callers; lines itself prints no signed values yet
+    I64(i64),
     // Isize(isize),
 
+    // Floats
+    F64(f64),
+
     // // Hex formatting
     // U8Hex(u8),
     // U16Hex(u16),
@@ -229,54 +240,153 @@ fn format_u64_to_buffer<'a>(value: u64, buf: &'a mut [u8]) -> Option<&'a str> {
     std::str::from_utf8(&buf[..pos]).ok()
 }
 
-// /// Converts i64 to decimal string with sign in provided stack buffer
-// ///
-// /// Memory: should be all stack, no heap
-// fn format_i64_to_buffer<'a>(value: i64, buf: &'a mut [u8]) -> Option<&'a str> {
-//     if buf.is_empty() {
-//         return None;
-//     }
+/// Converts i64 to decimal string with sign in provided stack buffer
+///
+/// Memory: should be all stack, no heap
+fn format_i64_to_buffer(value: i64, buf: &mut [u8]) -> Option<&str> {
+    if buf.is_empty() {
+        return None;
+    }
 
-//     if value == 0 {
-//         buf[0] = b'0';
-//         return std::str::from_utf8(&buf[..1]).ok();
-//     }
+    if value == 0 {
+        buf[0] = b'0';
+        return std::str::from_utf8(&buf[..1]).ok();
+    }
 
-//     let (is_negative, abs_value) = if value < 0 {
-//         (true, value.wrapping_abs() as u64)
-//     } else {
-//         (false, value as u64)
-//     };
+    let (is_negative, abs_value) = if value < 0 {
+        (true, value.wrapping_abs() as u64)
+    } else {
+        (false, value as u64)
+    };
 
-//     let mut temp = [0u8; 20];
-//     let mut pos = 0;
-//     let mut num = abs_value;
+    let mut temp = [0u8; 20];
+    let mut pos = 0;
+    let mut num = abs_value;
 
-//     while num > 0 {
-//         temp[pos] = b'0' + (num % 10) as u8;
-//         num /= 10;
-//         pos += 1;
-//     }
+    while num > 0 {
+        temp[pos] = b'0' + (num % 10) as u8;
+        num /= 10;
+        pos += 1;
+    }
 
-//     let total_len = if is_negative { pos + 1 } else { pos };
+    let total_len = if is_negative { pos + 1 } else { pos };
 
-//     if total_len > buf.len() {
-//         return None;
-//     }
+    if total_len > buf.len() {
+        return None;
+    }
 
-//     let mut buf_pos = 0;
+    let mut buf_pos = 0;
 
-//     if is_negative {
-//         buf[buf_pos] = b'-';
-//         buf_pos += 1;
-//     }
+    if is_negative {
+        buf[buf_pos] = b'-';
+        buf_pos += 1;
+    }
 
-//     for i in 0..pos {
-//         buf[buf_pos + i] = temp[pos - 1 - i];
-//     }
+    for i in 0..pos {
+        buf[buf_pos + i] = temp[pos - 1 - i];
+    }
 
-//     std::str::from_utf8(&buf[..total_len]).ok()
-// }
+    std::str::from_utf8(&buf[..total_len]).ok()
+}
+
+/// Largest float precision accepted by `{:.N}`
+const MAX_FLOAT_PRECISION: usize = 12;
+
+/// Default digits when no precision is given (trailing zeros trimmed)
+const DEFAULT_FLOAT_PRECISION: usize = 6;
+
+/// Converts f64 to fixed-point decimal string in provided stack buffer
+///
+/// Memory: should be all stack, no heap
+///
+/// ## Parameters
+/// - value: Number to convert
+/// - precision: Digits after the point (`{:.N}`); `None` means up to 6
+///   digits with trailing zeros (and a bare point) trimmed, so 1.5 -> "1.5"
+///   and 2.0 -> "2"
+/// - buf: Stack buffer to write into (min 34 bytes for any accepted value)
+///
+/// ## Returns
+/// - Some(&str): Formatted number borrowing from buf
+/// - None: Buffer too small, precision over 12, or magnitude beyond u64
+fn format_f64_to_buffer(value: f64, precision: Option<usize>, buf: &mut [u8]) -> Option<&str> {
+    if value.is_nan() {
+        return copy_str_to_buffer("NaN", buf);
+    }
+    if value.is_infinite() {
+        return copy_str_to_buffer(if value < 0.0 { "-inf" } else { "inf" }, buf);
+    }
+
+    let digits = precision.unwrap_or(DEFAULT_FLOAT_PRECISION);
+    if digits > MAX_FLOAT_PRECISION {
+        return None;
+    }
+
+    let is_negative = value.is_sign_negative() && value != 0.0;
+    let abs_value = value.abs();
+    if abs_value >= u64::MAX as f64 {
+        return None;
+    }
+
+    // Round once on the scaled fraction; carry into the integer part
+    let scale = 10u64.pow(digits as u32);
+    let mut integer_part = abs_value.trunc() as u64;
+    let mut fraction_part = ((abs_value - abs_value.trunc()) * scale as f64).round() as u64;
+    if fraction_part >= scale {
+        integer_part = integer_part.checked_add(1)?;
+        fraction_part -= scale;
+    }
+
+    let mut pos = 0;
+    if is_negative {
+        *buf.get_mut(pos)? = b'-';
+        pos += 1;
+    }
+
+    let integer_len = format_u64_to_buffer(integer_part, buf.get_mut(pos..)?)?.len();
+    pos += integer_len;
+
+    if digits > 0 {
+        if pos + 1 + digits > buf.len() {
+            return None;
+        }
+        buf[pos] = b'.';
+        // Fraction digits, most significant first, zero-filled
+        let mut remaining = fraction_part;
+        for i in (0..digits).rev() {
+            buf[pos + 1 + i] = b'0' + (remaining % 10) as u8;
+            remaining /= 10;
+        }
+        pos += 1 + digits;
+
+        if precision.is_none() {
+            while buf[pos - 1] == b'0' {
+                pos -= 1;
+            }
+            if buf[pos - 1] == b'.' {
+                pos -= 1;
+            }
+        }
+    }
+
+    // "-0" after rounding a tiny negative: drop the sign
+    if is_negative && buf[1..pos].iter().all(|&byte| byte == b'0' || byte == b'.') {
+        buf.copy_within(1..pos, 0);
+        pos -= 1;
+    }
+
+    std::str::from_utf8(&buf[..pos]).ok()
+}
+
+/// Copies a static str into the buffer (for NaN/inf)
+fn copy_str_to_buffer<'a>(text: &str, buf: &'a mut [u8]) -> Option<&'a str> {
+    let bytes = text.as_bytes();
+    if bytes.len() > buf.len() {
+        return None;
+    }
+    buf[..bytes.len()].copy_from_slice(bytes);
+    std::str::from_utf8(&buf[..bytes.len()]).ok()
+}
 
 // /// Converts u8 to 2-digit uppercase hex in provided stack buffer
 // ///
@@ -391,12 +501,139 @@ pub fn style_to_ansi<'a>(style: BuffyStyles, buf: &'a mut [u8]) -> Option<&'a st
     std::str::from_utf8(&buf[..pos]).ok()
 }
 
+// =============================================================================
+// STYLING HELPERS - Named styles composed into caller buffers
+// =============================================================================
+
+/// Resets all ANSI attributes (colour, bold, background)
+pub const ANSI_RESET: &str = "\x1b[0m";
+
+/// Named colour/weight combinations used across the TUI
+///
+/// ## Project Context
+/// Menus, legends, and info bars use a handful of fixed combinations
+/// (red hotkeys, yellow labels, magenta section numbers). Naming them
+/// keeps callers from concatenating RED/YELLOW/RESET constants by hand.
+///
+/// Memory: should be all stack, no heap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// No styling: text is copied as-is
+    Plain,
+    Bold,
+    Red,
+    Green,
+    Yellow,
+    Magenta,
+    Cyan,
+    BrightWhiteBold,
+}
+
+impl Style {
+    /// The BuffyStyles this named style stands for
+    pub const fn to_buffy_styles(self) -> BuffyStyles {
+        let (fg_color, bold) = match self {
+            Style::Plain => (None, false),
+            Style::Bold => (None, true),
+            Style::Red => (Some("\x1b[31m"), false),
+            Style::Green => (Some("\x1b[32m"), false),
+            Style::Yellow => (Some("\x1b[33m"), false),
+            Style::Magenta => (Some("\x1b[35m"), false),
+            Style::Cyan => (Some("\x1b[36m"), false),
+            Style::BrightWhiteBold => (Some("\x1b[97m"), true),
+        };
+        BuffyStyles {
+            fg_color,
+            bg_color: None,
+            bold,
+            underline: false,
+            italic: false,
+            dim: false,
+        }
+    }
+}
+
+/// Appends `bytes` at `pos` in `buf`, returning the new position
+///
+/// Memory: should be all stack, no heap
+/// Returns None (buffer untouched past `pos`) if `bytes` does not fit.
+fn append_to_buffer(buf: &mut [u8], pos: usize, bytes: &[u8]) -> Option<usize> {
+    let end = pos.checked_add(bytes.len())?;
+    if end > buf.len() {
+        return None;
+    }
+    buf[pos..end].copy_from_slice(bytes);
+    Some(end)
+}
+
+/// Wraps text in a named style and a trailing reset, in the caller's buffer
+///
+/// ## Project Context
+/// Replaces hand-built `RED + text + RESET` strings. The result is always
+/// self-contained: whatever follows it starts unstyled.
+///
+/// Memory: should be all stack, no heap
+///
+/// ## Returns
+/// - `Some(&str)` - Styled text, borrowing `buf`
+/// - `None` - `buf` too small for codes + text + reset
+///
+/// ## Example
+/// ```rust
+/// let mut buf = [0u8; 64];
+/// let warning = buffy_styled("read-only", Style::Red, &mut buf);
+/// ```
+pub fn buffy_styled<'a>(text: &str, style: Style, buf: &'a mut [u8]) -> Option<&'a str> {
+    buffy_styled_segments(&[(text, style)], buf)
+}
+
+/// Joins several styled pieces into the caller's buffer
+///
+/// ## Project Context
+/// Info bars and legends alternate colours within one line
+/// (YELLOW label, RED number, YELLOW label, ...). Each segment carries its
+/// own style; a styled segment is followed by a reset so styles never leak
+/// into the next segment. Plain segments are copied as-is.
+///
+/// Memory: should be all stack, no heap
+///
+/// ## Returns
+/// - `Some(&str)` - Joined text, borrowing `buf`
+/// - `None` - `buf` too small for the whole line
+///
+/// ## Example
+/// ```rust
+/// let mut buf = [0u8; 128];
+/// let line = buffy_styled_segments(
+///     &[("line ", Style::Yellow), ("42", Style::Red), (" > ", Style::Plain)],
+///     &mut buf,
+/// );
+/// ```
+pub fn buffy_styled_segments<'a>(segments: &[(&str, Style)], buf: &'a mut [u8]) -> Option<&'a str> {
+    let mut pos = 0;
+
+    for &(text, style) in segments {
+        if style == Style::Plain {
+            pos = append_to_buffer(buf, pos, text.as_bytes())?;
+            continue;
+        }
+
+        let mut ansi_buf = [0u8; 64];
+        let ansi = style_to_ansi(style.to_buffy_styles(), &mut ansi_buf)?;
+        pos = append_to_buffer(buf, pos, ansi.as_bytes())?;
+        pos = append_to_buffer(buf, pos, text.as_bytes())?;
+        pos = append_to_buffer(buf, pos, ANSI_RESET.as_bytes())?;
+    }
+
+    std::str::from_utf8(&buf[..pos]).ok()
+}
+
 // =============================================================================
 // ALIGNMENT SUPPORT
 // =============================================================================
 
-#[derive(Debug, Clone, Copy)]
-enum Alignment {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
     Left,
     Right,
     Center,
@@ -406,16 +643,25 @@ enum Alignment {
 struct FormatSpec {
     alignment: Alignment,
     width: Option<usize>,
+    /// `{:08}`: pad numbers with zeros after the sign (ignored for text)
+    zero_pad: bool,
+    /// `{:.2}`: float digits after the point; max characters for text
+    precision: Option<usize>,
 }
 
 /// Parse format specifier from placeholder text
-/// Examples: "" -> no alignment, "<5" -> left 5, ">10" -> right 10
+/// Examples: "" -> no alignment, "<5" -> left 5, ">10" -> right 10,
+/// "08" -> zero-pad to 8, ".2" -> precision 2, ">9.3" -> right 9 precision 3
 fn parse_format_spec(placeholder: &str) -> Option<FormatSpec> {
+    let mut format_spec = FormatSpec {
+        alignment: Alignment::Left,
+        width: None,
+        zero_pad: false,
+        precision: None,
+    };
+
     if placeholder.is_empty() {
-        return Some(FormatSpec {
-            alignment: Alignment::Left,
-            width: None,
-        });
+        return Some(format_spec);
     }
 
     if !placeholder.starts_with(':') {
@@ -425,34 +671,55 @@ fn parse_format_spec(placeholder: &str) -> Option<FormatSpec> {
     let spec = &placeholder[1..];
 
     if spec.is_empty() {
-        return Some(FormatSpec {
-            alignment: Alignment::Left,
-            width: None,
-        });
-    }
-
-    let (alignment, width_str) = if spec.starts_with('<') {
-        (Alignment::Left, &spec[1..])
-    } else if spec.starts_with('>') {
-        (Alignment::Right, &spec[1..])
-    } else if spec.starts_with('^') {
-        (Alignment::Center, &spec[1..])
-    } else if spec.chars().next()?.is_ascii_digit() {
-        (Alignment::Right, spec)
+        return Some(format_spec);
+    }
+
+    // Split off ".precision"
+    let (spec, precision_str) = match spec.find('.') {
+        Some(dot_pos) => (&spec[..dot_pos], Some(&spec[dot_pos + 1..])),
+        None => (spec, None),
+    };
+
+    if let Some(precision_str) = precision_str {
+        match precision_str.parse::<usize>() {
+            Ok(p) if p <= 64 => format_spec.precision = Some(p),
+            _ => return None,
+        }
+    }
+
+    let width_str = if let Some(rest) = spec.strip_prefix('<') {
+        rest
+    } else if let Some(rest) = spec.strip_prefix('>') {
+        format_spec.alignment = Alignment::Right;
+        rest
+    } else if let Some(rest) = spec.strip_prefix('^') {
+        format_spec.alignment = Alignment::Center;
+        rest
+    } else if spec.is_empty() || spec.chars().next()?.is_ascii_digit() {
+        // Bare width right-aligns (as before); "{:.2}" has no width at all
+        format_spec.alignment = Alignment::Right;
+        spec
     } else {
         return None;
     };
 
-    let width = if width_str.is_empty() {
-        None
-    } else {
+    // Leading '0' before more digits is the zero-pad flag
+    let width_str = match width_str.strip_prefix('0') {
+        Some(rest) if !rest.is_empty() => {
+            format_spec.zero_pad = true;
+            rest
+        }
+        _ => width_str,
+    };
+
+    if !width_str.is_empty() {
         match width_str.parse::<usize>() {
-            Ok(w) if w <= 64 => Some(w),
+            Ok(w) if w <= 64 => format_spec.width = Some(w),
             _ => return None,
         }
-    };
+    }
 
-    Some(FormatSpec { alignment, width })
+    Some(format_spec)
 }
 
 /// Apply alignment to a value, writing result to buffer
@@ -488,6 +755,15 @@ fn apply_alignment<'a>(value: &str, spec: FormatSpec, buf: &'a mut [u8]) -> Opti
 
     let padding = width - value_len;
 
+    if spec.zero_pad {
+        // Sign stays in front: "-42" at width 5 -> "-0042"
+        let sign_len = if value.starts_with('-') { 1 } else { 0 };
+        buf[..sign_len].copy_from_slice(&value.as_bytes()[..sign_len]);
+        buf[sign_len..sign_len + padding].fill(b'0');
+        buf[sign_len + padding..width].copy_from_slice(&value.as_bytes()[sign_len..]);
+        return std::str::from_utf8(&buf[..width]).ok();
+    }
+
     match spec.alignment {
         Alignment::Left => {
             // Value then spaces
@@ -520,20 +796,160 @@ fn apply_alignment<'a>(value: &str, spec: FormatSpec, buf: &'a mut [u8]) -> Opti
     std::str::from_utf8(&buf[..width]).ok()
 }
 
+/// Shortens text to at most `max_chars` characters (on a char boundary)
+fn truncate_to_chars(value: &str, max_chars: usize) -> &str {
+    match value.char_indices().nth(max_chars) {
+        Some((byte_index, _)) => &value[..byte_index],
+        None => value,
+    }
+}
+
+// =============================================================================
+// FIXED-WIDTH TABLES
+// =============================================================================
+
+/// Widest table column, in characters (same cap as `{:<N}` alignment)
+pub const MAX_TABLE_COLUMN_WIDTH: usize = 64;
+
+/// Marks a cell whose text was cut to fit its column
+pub const TABLE_ELLIPSIS: char = '…';
+
+/// Stack buffer for one cell: MAX_TABLE_COLUMN_WIDTH chars of up to 4 bytes
+const TABLE_CELL_BUFFER_SIZE: usize = MAX_TABLE_COLUMN_WIDTH * 4;
+
+/// One column of a fixed-width table
+///
+/// ## Project Context
+/// List screens (e.g. Pasty's clipboard list) show one item per line with
+/// columns that must line up whatever the item text is. Width is counted
+/// in characters, not bytes, so non-ASCII names stay aligned.
+///
+/// Memory: should be all stack, no heap
+#[derive(Debug, Clone, Copy)]
+pub struct BuffyTableColumn {
+    /// Column width in characters (1..=MAX_TABLE_COLUMN_WIDTH)
+    pub width: usize,
+    pub alignment: Alignment,
+    pub style: Style,
+}
+
+/// Fits text into exactly `width` characters in the caller's buffer
+///
+/// ## Behavior
+/// - Longer text is cut to `width - 1` characters plus TABLE_ELLIPSIS
+/// - Shorter text is padded with spaces per `alignment`
+///
+/// Memory: should be all stack, no heap
+///
+/// ## Returns
+/// - `Some(&str)` - Cell text, borrowing `buf`
+/// - `None` - Width is 0 or `buf` is too small
+pub fn buffy_table_cell<'a>(
+    value: &str,
+    width: usize,
+    alignment: Alignment,
+    buf: &'a mut [u8],
+) -> Option<&'a str> {
+    if width == 0 {
+        return None;
+    }
+
+    let value_chars = value.chars().count();
+    let mut ellipsis_bytes = [0u8; 4];
+    let (text, text_chars, ellipsis) = if value_chars > width {
+        let ellipsis = TABLE_ELLIPSIS.encode_utf8(&mut ellipsis_bytes);
+        (truncate_to_chars(value, width - 1), width - 1, &*ellipsis)
+    } else {
+        (value, value_chars, "")
+    };
+
+    let padding = width - text_chars - ellipsis.chars().count();
+    let left_pad = match alignment {
+        Alignment::Left => 0,
+        Alignment::Right => padding,
+        Alignment::Center => padding / 2,
+    };
+
+    let total_len = padding + text.len() + ellipsis.len();
+    if total_len > buf.len() {
+        return None;
+    }
+
+    let mut pos = 0;
+    buf[..left_pad].fill(b' ');
+    pos += left_pad;
+    buf[pos..pos + text.len()].copy_from_slice(text.as_bytes());
+    pos += text.len();
+    buf[pos..pos + ellipsis.len()].copy_from_slice(ellipsis.as_bytes());
+    pos += ellipsis.len();
+    buf[pos..total_len].fill(b' ');
+
+    std::str::from_utf8(&buf[..total_len]).ok()
+}
+
+/// Writes one table row: each cell fitted to its column, then a newline
+///
+/// ## Project Context
+/// Each cell is written in its column's Style; `separator` goes between
+/// cells unstyled. Rows written with the same columns line up exactly.
+///
+/// Memory: should be all stack, no heap
+///
+/// ## Errors
+/// - InvalidInput if `cells` and `columns` differ in length, or a column
+///   width is 0 or above MAX_TABLE_COLUMN_WIDTH
+pub fn buffy_write_table_row<W: Write + ?Sized>(
+    writer: &mut W,
+    columns: &[BuffyTableColumn],
+    cells: &[&str],
+    separator: &str,
+) -> io::Result<()> {
+    if cells.len() != columns.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Table row cell count does not match column count",
+        ));
+    }
+
+    for (index, (column, cell)) in columns.iter().zip(cells.iter()).enumerate() {
+        if column.width == 0 || column.width > MAX_TABLE_COLUMN_WIDTH {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Table column width out of range",
+            ));
+        }
+
+        if index > 0 {
+            writer.write_all(separator.as_bytes())?;
+        }
+
+        let mut cell_buf = [0u8; TABLE_CELL_BUFFER_SIZE];
+        let fitted = buffy_table_cell(cell, column.width, column.alignment, &mut cell_buf)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Table cell too large"))?;
+
+        let mut styled_buf = [0u8; TABLE_CELL_BUFFER_SIZE + 64];
+        let styled = buffy_styled(fitted, column.style, &mut styled_buf)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Table cell too large"))?;
+        writer.write_all(styled.as_bytes())?;
+    }
+
+    writer.write_all(b"\n")
+}
+
 // =============================================================================
 // DIRECT TERMINAL OUTPUT - TRUE ZERO HEAP
 // =============================================================================
 
 // Q: could 'args' be an 'option'?
-/// Writes formatted output directly to stdout without any intermediate allocation.
+/// Writes formatted output to any writer without any intermediate allocation.
 ///
 /// ## Project Context
-/// Primary output function for TUI. Processes format template and writes
-/// results directly to terminal as it goes. No String building, no Vec,
-/// no intermediate storage.
+/// Formatting engine behind buffy_print (stdout) and BuffyFrame (a whole
+/// TUI frame). Processes format template and writes results directly to
+/// the writer as it goes. No String building, no Vec, no intermediate storage.
 ///
 /// Memory: should be all stack, no heap
-/// All conversions use stack buffers. Output written directly to stdout.
+/// All conversions use stack buffers. Output written directly to the writer.
 ///
 /// ## Operation
 /// 1. Parse template piece by piece
@@ -548,11 +964,13 @@ fn apply_alignment<'a>(value: &str, spec: FormatSpec, buf: &'a mut [u8]) -> Opti
 /// - Non-critical: Caller can continue on error
 ///
 /// ## Parameters
-/// - template: Format string with {} or {:<N}/{:>N}/{:^N} placeholders
+/// - template: Format string with {} or {:<N}/{:>N}/{:^N} placeholders,
+///   plus {:0N} zero-pad (numbers) and {:.P} precision (float digits, or
+///   max characters of text), combinable as {:>9.2} or {:08.3}
 /// - args: Slice of BuffyFormatArg values (max 8)
 ///
 /// ## Returns
-/// - Ok(()): Successfully written to stdout
+/// - Ok(()): Successfully written to the writer
 /// - Err(io::Error): Write failed or format error
 ///
 /// ## Examples
@@ -568,8 +986,16 @@ fn apply_alignment<'a>(value: &str, spec: FormatSpec, buf: &'a mut [u8]) -> Opti
 ///
 /// // With alignment
 /// buffy_print("{:<10} {:>5}", &[BuffyFormatArg::Str("Name"), BuffyFormatArg::U32(123)])?;
+///
+/// // Zero-pad and precision
+/// buffy_print("{:08} {:.2}", &[BuffyFormatArg::I64(-42), BuffyFormatArg::F64(0.125)])?;
+/// // -> "-0000042 0.13"
 /// ```
-pub fn buffy_print(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
+pub fn buffy_write<W: Write + ?Sized>(
+    writer: &mut W,
+    template: &str,
+    args: &[BuffyFormatArg],
+) -> io::Result<()> {
     const MAX_ARGS: usize = 8;
 
     if args.len() > MAX_ARGS {
@@ -579,12 +1005,12 @@ pub fn buffy_print(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
         ));
     }
 
-    let mut stdout = io::stdout();
     let mut arg_index = 0;
     let mut pos = 0;
 
     // Stack buffers for conversions
     let mut num_buf = [0u8; 20];
+    let mut float_buf = [0u8; 40];
     let mut style_buf = [0u8; 64];
     let mut align_buf = [0u8; 128];
 
@@ -595,7 +1021,7 @@ pub fn buffy_print(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
 
             // Write literal text before placeholder
             if brace_pos > 0 {
-                stdout.write_all(template[pos..absolute_brace].as_bytes())?;
+                writer.write_all(&template.as_bytes()[pos..absolute_brace])?;
             }
 
             // Find closing brace
@@ -643,12 +1069,11 @@ pub fn buffy_print(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
                     //     })?;
                     //     (s, false, BuffyStyles::default())
                     // }
-                    // BuffyFormatArg::U64(n) => {
-                    //     let s = format_u64_to_buffer(*n, &mut num_buf).ok_or_else(|| {
-                    //         io::Error::new(io::ErrorKind::Other, "Number conversion failed")
-                    //     })?;
-                    //     (s, false, BuffyStyles::default())
-                    // }
+                    BuffyFormatArg::U64(n) => {
+                        let s = format_u64_to_buffer(*n, &mut num_buf)
+                            .ok_or_else(|| io::Error::other("Number conversion failed"))?;
+                        (s, false, BuffyStyles::default())
+                    }
                     BuffyFormatArg::Usize(n) => {
                         let s = format_u64_to_buffer(*n as u64, &mut num_buf).ok_or_else(|| {
                             io::Error::new(io::ErrorKind::Other, "Number conversion failed")
@@ -673,12 +1098,16 @@ pub fn buffy_print(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
                     //     })?;
                     //     (s, false, BuffyStyles::default())
                     // }
-                    // BuffyFormatArg::I64(n) => {
-                    //     let s = format_i64_to_buffer(*n, &mut num_buf).ok_or_else(|| {
-                    //         io::Error::new(io::ErrorKind::Other, "Number conversion failed")
-                    //     })?;
-                    //     (s, false, BuffyStyles::default())
-                    // }
+                    BuffyFormatArg::I64(n) => {
+                        let s = format_i64_to_buffer(*n, &mut num_buf)
+                            .ok_or_else(|| io::Error::other("Number conversion failed"))?;
+                        (s, false, BuffyStyles::default())
+                    }
+                    BuffyFormatArg::F64(n) => {
+                        let s = format_f64_to_buffer(*n, spec.precision, &mut float_buf)
+                            .ok_or_else(|| io::Error::other("Number conversion failed"))?;
+                        (s, false, BuffyStyles::default())
+                    }
                     // BuffyFormatArg::Isize(n) => {
                     //     let s = format_i64_to_buffer(*n as i64, &mut num_buf).ok_or_else(|| {
                     //         io::Error::new(io::ErrorKind::Other, "Number conversion failed")
@@ -763,22 +1192,39 @@ pub fn buffy_print(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
                     // }
                 };
 
+                // Zero-pad applies to numbers only; precision on text truncates
+                let is_numeric = matches!(
+                    &args[arg_index],
+                    BuffyFormatArg::U64(_)
+                        | BuffyFormatArg::Usize(_)
+                        | BuffyFormatArg::I64(_)
+                        | BuffyFormatArg::F64(_)
+                );
+                let spec = FormatSpec {
+                    zero_pad: spec.zero_pad && is_numeric,
+                    ..spec
+                };
+                let value_str = match spec.precision {
+                    Some(max_chars) if !is_numeric => truncate_to_chars(value_str, max_chars),
+                    _ => value_str,
+                };
+
                 // Apply style if needed
                 if has_style {
                     let ansi = style_to_ansi(style, &mut style_buf).ok_or_else(|| {
                         io::Error::new(io::ErrorKind::Other, "BuffyStyles conversion failed")
                     })?;
-                    stdout.write_all(ansi.as_bytes())?;
+                    writer.write_all(ansi.as_bytes())?;
                 }
 
                 // Apply alignment and write
                 let aligned = apply_alignment(value_str, spec, &mut align_buf)
                     .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Alignment failed"))?;
-                stdout.write_all(aligned.as_bytes())?;
+                writer.write_all(aligned.as_bytes())?;
 
                 // Reset style if needed
                 if has_style {
-                    stdout.write_all(b"\x1b[0m")?;
+                    writer.write_all(b"\x1b[0m")?;
                 }
 
                 arg_index += 1;
@@ -791,7 +1237,7 @@ pub fn buffy_print(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
             }
         } else {
             // No more placeholders, write remaining literal text
-            stdout.write_all(template[pos..].as_bytes())?;
+            writer.write_all(&template.as_bytes()[pos..])?;
             break;
         }
     }
@@ -799,6 +1245,13 @@ pub fn buffy_print(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
     Ok(())
 }
 
+/// Writes formatted output directly to stdout (see buffy_write)
+///
+/// Memory: should be all stack, no heap
+pub fn buffy_print(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
+    buffy_write(&mut io::stdout(), template, args)
+}
+
 /// Writes formatted output to stdout with newline and flush.
 ///
 /// Memory: should be all stack, no heap
@@ -810,6 +1263,193 @@ pub fn buffy_println(template: &str, args: &[BuffyFormatArg]) -> io::Result<()>
     stdout.flush()
 }
 
+// =============================================================================
+// STDERR AND SINK OUTPUT
+// =============================================================================
+
+/// Writes formatted output directly to stderr (see buffy_write)
+///
+/// Memory: should be all stack, no heap
+/// stderr is unbuffered, so no flush is needed.
+pub fn buffy_eprint(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
+    buffy_write(&mut io::stderr(), template, args)
+}
+
+/// Writes formatted output to stderr with newline.
+///
+/// Memory: should be all stack, no heap
+pub fn buffy_eprintln(template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
+    let mut stderr = io::stderr();
+    buffy_write(&mut stderr, template, args)?;
+    stderr.write_all(b"\n")
+}
+
+/// Destination for a formatted message
+///
+/// ## Project Context
+/// Error reporting writes the same line either to the error-log file or,
+/// when the log is unavailable, to stderr. A sink lets that line be
+/// formatted once, by one template, whichever way it ends up going.
+///
+/// Memory: should be all stack, no heap
+/// `Writer` borrows an already-open destination (e.g. the log File).
+pub enum BuffySink<'a> {
+    Stderr,
+    Writer(&'a mut dyn Write),
+}
+
+impl BuffySink<'_> {
+    /// Writes formatted output to the sink (see buffy_write)
+    pub fn print(&mut self, template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
+        match self {
+            BuffySink::Stderr => buffy_eprint(template, args),
+            BuffySink::Writer(writer) => buffy_write(*writer, template, args),
+        }
+    }
+
+    /// Writes formatted output plus newline to the sink, then flushes it
+    pub fn println(&mut self, template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
+        self.print(template, args)?;
+        match self {
+            BuffySink::Stderr => io::stderr().write_all(b"\n"),
+            BuffySink::Writer(writer) => {
+                writer.write_all(b"\n")?;
+                writer.flush()
+            }
+        }
+    }
+}
+
+// =============================================================================
+// FRAME-BUFFERED OUTPUT - ONE WRITE PER TUI FRAME
+// =============================================================================
+
+/// Default BuffyFrame capacity: a full-size TUI frame with ANSI styling
+///
+/// 45 rows x 182 bytes with per-character colour codes fits comfortably;
+/// a larger frame still works (the buffer grows once and keeps the size).
+pub const BUFFY_FRAME_DEFAULT_CAPACITY: usize = 128 * 1024;
+
+/// Accumulates a whole TUI frame, then writes it to stdout in one call
+///
+/// ## Project Context
+/// Rendering a screen as many small print!/println! calls lets the terminal
+/// paint partial frames: stdout is line-buffered, so every row is its own
+/// write, and over a slow SSH link the screen visibly tears. Building the
+/// frame here and flushing once makes each refresh a single write.
+///
+/// ## Memory
+/// One heap buffer, allocated once at creation and reused for every frame
+/// (`flush_to_stdout` clears the content but keeps the capacity). Keep one
+/// BuffyFrame for the life of the render loop. Formatting into it uses the
+/// same stack-only path as buffy_print.
+///
+/// ## Example
+/// ```rust
+/// let mut frame = BuffyFrame::with_capacity(BUFFY_FRAME_DEFAULT_CAPACITY);
+/// frame.write_all(b"\x1b[2J\x1b[H")?;
+/// frame.println("Rows: {}", &[BuffyFormatArg::Usize(24)])?;
+/// frame.flush_to_stdout()?; // one write
+/// ```
+#[derive(Debug)]
+pub struct BuffyFrame {
+    buffer: Vec<u8>,
+}
+
+impl BuffyFrame {
+    /// Creates an empty frame with `capacity` bytes pre-allocated
+    pub fn with_capacity(capacity: usize) -> Self {
+        BuffyFrame {
+            buffer: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Appends formatted output (same template rules as buffy_print)
+    pub fn print(&mut self, template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
+        buffy_write(self, template, args)
+    }
+
+    /// Appends formatted output and a newline (no flush: see flush_to_stdout)
+    pub fn println(&mut self, template: &str, args: &[BuffyFormatArg]) -> io::Result<()> {
+        buffy_write(self, template, args)?;
+        self.buffer.push(b'\n');
+        Ok(())
+    }
+
+    /// The frame built so far (for saving a screenshot)
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Discards the frame content, keeping the allocation
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Overwrites the whole allocation with zeros, then clears
+    ///
+    /// For security mode: `clear` only resets the length, so the last
+    /// frame's text would stay in memory until overwritten. Only the
+    /// current allocation is wiped; one left behind by a reallocation
+    /// (a frame larger than the capacity) is not reachable from here.
+    pub fn wipe(&mut self) {
+        let capacity = self.buffer.capacity();
+        self.buffer.clear();
+        // Within capacity: no reallocation, every byte is written
+        self.buffer.resize(capacity, 0);
+        std::hint::black_box(&mut self.buffer);
+        self.buffer.clear();
+    }
+
+    /// Writes the whole frame to stdout in one write, flushes, and clears
+    ///
+    /// The frame is cleared even if the write fails, so a broken frame is
+    /// never re-sent appended to the next one.
+    pub fn flush_to_stdout(&mut self) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        let result = stdout.write_all(&self.buffer).and_then(|_| stdout.flush());
+        self.buffer.clear();
+        result
+    }
+}
+
+/// Copies `bytes` without ANSI escape sequences (colours, cursor moves)
+///
+/// CSI sequences (ESC '[' parameters final-byte) and two-byte ESC
+/// sequences are dropped; everything else is kept as-is.
+pub fn buffy_strip_ansi(bytes: &[u8]) -> Vec<u8> {
+    let mut plain = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] != 0x1B {
+            plain.push(bytes[index]);
+            index += 1;
+        } else if bytes.get(index + 1) == Some(&b'[') {
+            // Parameters and intermediates, then one final byte @..~
+            index += 2;
+            while index < bytes.len() && !(0x40..=0x7E).contains(&bytes[index]) {
+                index += 1;
+            }
+            index += 1;
+        } else {
+            index += 2;
+        }
+    }
+    plain
+}
+
+impl Write for BuffyFrame {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    /// No-op: output leaves the frame only via flush_to_stdout
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 // /// Writes formatted output to any writer.
 // ///
 // /// Memory: should be all stack, no heap
@@ -1264,12 +1904,72 @@ mod buffy_format_tests {
         assert_eq!(result, Some("42"));
     }
 
-    // #[test]
-    // fn test_format_i64_negative() {
-    //     let mut buf = [0u8; 20];
-    //     let result = format_i64_to_buffer(-42, &mut buf);
-    //     assert_eq!(result, Some("-42"));
-    // }
+    #[test]
+    fn test_format_i64_negative() {
+        let mut buf = [0u8; 20];
+        let result = format_i64_to_buffer(-42, &mut buf);
+        assert_eq!(result, Some("-42"));
+        let result = format_i64_to_buffer(i64::MIN, &mut buf);
+        assert_eq!(result, Some("-9223372036854775808"));
+    }
+
+    #[test]
+    fn test_format_f64() {
+        let mut buf = [0u8; 40];
+        assert_eq!(format_f64_to_buffer(0.125, Some(2), &mut buf), Some("0.13"));
+        assert_eq!(
+            format_f64_to_buffer(-1.5, Some(3), &mut buf),
+            Some("-1.500")
+        );
+        assert_eq!(
+            format_f64_to_buffer(9.999, Some(2), &mut buf),
+            Some("10.00")
+        );
+        assert_eq!(format_f64_to_buffer(2.7, Some(0), &mut buf), Some("3"));
+        assert_eq!(format_f64_to_buffer(1.5, None, &mut buf), Some("1.5"));
+        assert_eq!(format_f64_to_buffer(2.0, None, &mut buf), Some("2"));
+        assert_eq!(
+            format_f64_to_buffer(-0.0001, Some(2), &mut buf),
+            Some("0.00")
+        );
+        assert_eq!(format_f64_to_buffer(f64::NAN, None, &mut buf), Some("NaN"));
+        assert_eq!(format_f64_to_buffer(1e30, None, &mut buf), None);
+        assert_eq!(format_f64_to_buffer(1.0, Some(13), &mut buf), None);
+    }
+
+    #[test]
+    fn test_parse_format_spec_zero_pad_and_precision() {
+        let spec = parse_format_spec(":08").unwrap();
+        assert!(spec.zero_pad);
+        assert_eq!(spec.width, Some(8));
+        assert_eq!(spec.precision, None);
+
+        let spec = parse_format_spec(":>9.3").unwrap();
+        assert!(matches!(spec.alignment, Alignment::Right));
+        assert!(!spec.zero_pad);
+        assert_eq!(spec.width, Some(9));
+        assert_eq!(spec.precision, Some(3));
+
+        let spec = parse_format_spec(":.2").unwrap();
+        assert_eq!(spec.width, None);
+        assert_eq!(spec.precision, Some(2));
+
+        // A lone 0 is a width, not the flag
+        let spec = parse_format_spec(":0").unwrap();
+        assert!(!spec.zero_pad);
+        assert_eq!(spec.width, Some(0));
+
+        assert!(parse_format_spec(":.x").is_none());
+    }
+
+    #[test]
+    fn test_alignment_zero_pad_keeps_sign_first() {
+        let mut buf = [0u8; 10];
+        let spec = parse_format_spec(":06").unwrap();
+        assert_eq!(apply_alignment("-42", spec, &mut buf), Some("-00042"));
+        assert_eq!(apply_alignment("3.5", spec, &mut buf), Some("0003.5"));
+        assert_eq!(truncate_to_chars("héllo", 2), "hé");
+    }
 
     // #[test]
     // fn test_format_hex() {
@@ -1278,12 +1978,142 @@ mod buffy_format_tests {
     //     assert_eq!(result, Some("FF"));
     // }
 
+    #[test]
+    fn test_styled_wraps_text_and_resets() {
+        let mut buf = [0u8; 32];
+        assert_eq!(
+            buffy_styled("q", Style::BrightWhiteBold, &mut buf),
+            Some("\x1b[1m\x1b[97mq\x1b[0m")
+        );
+        assert_eq!(buffy_styled("q", Style::Plain, &mut buf), Some("q"));
+
+        let mut small = [0u8; 6];
+        assert_eq!(buffy_styled("q", Style::Red, &mut small), None);
+    }
+
+    #[test]
+    fn test_styled_segments_join_in_order() {
+        let mut buf = [0u8; 64];
+        let line = buffy_styled_segments(
+            &[
+                ("line ", Style::Yellow),
+                ("42", Style::Red),
+                (" > ", Style::Plain),
+            ],
+            &mut buf,
+        );
+        assert_eq!(line, Some("\x1b[33mline \x1b[0m\x1b[31m42\x1b[0m > "));
+    }
+
+    #[test]
+    fn test_writer_sink_formats_like_print() {
+        let mut captured: Vec<u8> = Vec::new();
+        let mut sink = BuffySink::Writer(&mut captured);
+        sink.println(
+            "[{}] {}",
+            &[BuffyFormatArg::Str("ctx"), BuffyFormatArg::Usize(42)],
+        )
+        .expect("println into writer sink");
+        sink.print("{:>3}", &[BuffyFormatArg::Usize(7)])
+            .expect("print into writer sink");
+        assert_eq!(captured, b"[ctx] 42\n  7");
+    }
+
+    #[test]
+    fn test_table_cell_pads_and_truncates_by_chars() {
+        let mut buf = [0u8; 64];
+        assert_eq!(
+            buffy_table_cell("ab", 5, Alignment::Right, &mut buf),
+            Some("   ab")
+        );
+        assert_eq!(
+            buffy_table_cell("ab", 5, Alignment::Center, &mut buf),
+            Some(" ab  ")
+        );
+        assert_eq!(
+            buffy_table_cell("éé", 4, Alignment::Left, &mut buf),
+            Some("éé  ")
+        );
+        assert_eq!(
+            buffy_table_cell("clipboard_item", 6, Alignment::Left, &mut buf),
+            Some("clipb…")
+        );
+        assert_eq!(buffy_table_cell("x", 0, Alignment::Left, &mut buf), None);
+    }
+
+    #[test]
+    fn test_table_rows_line_up() {
+        let columns = [
+            BuffyTableColumn {
+                width: 3,
+                alignment: Alignment::Right,
+                style: Style::Plain,
+            },
+            BuffyTableColumn {
+                width: 5,
+                alignment: Alignment::Left,
+                style: Style::Plain,
+            },
+        ];
+        let mut captured: Vec<u8> = Vec::new();
+        buffy_write_table_row(&mut captured, &columns, &["1", "short"], " ").expect("first row");
+        buffy_write_table_row(&mut captured, &columns, &["10", "longer name"], " ")
+            .expect("second row");
+        assert_eq!(captured, "  1 short\n 10 long…\n".as_bytes());
+
+        assert!(buffy_write_table_row(&mut captured, &columns, &["1"], " ").is_err());
+    }
+
+    #[test]
+    fn test_frame_accumulates_until_flush() {
+        let mut frame = BuffyFrame::with_capacity(64);
+        frame
+            .print("{:>4}|", &[BuffyFormatArg::Usize(7)])
+            .expect("print into frame");
+        frame
+            .println("{}", &[BuffyFormatArg::Str("row")])
+            .expect("println into frame");
+        frame.write_all(b"{raw}").expect("raw write into frame");
+        assert_eq!(frame.buffer, b"   7|row\n{raw}");
+
+        frame.clear();
+        assert!(frame.buffer.is_empty());
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(
+            buffy_strip_ansi(b"\x1B[2J\x1B[H\x1B[1;31mq\x1B[0muit caf\xC3\xA9\n"),
+            "quit café\n".as_bytes()
+        );
+        assert_eq!(buffy_strip_ansi(b"a\x1B7b\x1B[3"), b"ab");
+    }
+
+    #[test]
+    fn test_frame_wipe_keeps_allocation() {
+        let mut frame = BuffyFrame::with_capacity(64);
+        frame
+            .write_all(b"secret text")
+            .expect("raw write into frame");
+        let capacity = frame.buffer.capacity();
+
+        frame.wipe();
+        assert!(frame.buffer.is_empty());
+        assert_eq!(
+            frame.buffer.capacity(),
+            capacity,
+            "wipe must not reallocate"
+        );
+    }
+
     #[test]
     fn test_alignment_left() {
         let mut buf = [0u8; 10];
         let spec = FormatSpec {
             alignment: Alignment::Left,
             width: Some(5),
+            zero_pad: false,
+            precision: None,
         };
         let result = apply_alignment("AB", spec, &mut buf);
         assert_eq!(result, Some("AB   "));
@@ -1295,6 +2125,8 @@ mod buffy_format_tests {
         let spec = FormatSpec {
             alignment: Alignment::Right,
             width: Some(5),
+            zero_pad: false,
+            precision: None,
         };
         let result = apply_alignment("AB", spec, &mut buf);
         assert_eq!(result, Some("   AB"));
@@ -12,6 +12,8 @@ use std::{
     thread,
     time::Duration,
 };
+
+use crate::session_encryption_module::{encrypt_if_session_encrypted, read_session_file};
 /*
 Rules & Policies
 
@@ -3432,6 +3434,10 @@ pub enum EditType {
 // Constants
 const MAX_UTF8_BYTES: usize = 4;
 
+/// Maximum log files in one letter-suffixed log set (bare number + a..z)
+/// A UTF-8 character uses at most 4, a hex byte-sequence insert up to 27
+const MAX_LOG_SET_ENTRIES: usize = LOG_LETTER_SEQUENCE.len() + 1;
+
 // ==========================================================
 // ERROR SECTION: BUTTON UNDO CHANGELOG ERROR HANDLING SYSTEM
 // ==========================================================
@@ -4279,10 +4285,13 @@ pub const REDO_LOG_DIR_PREFIX: &str = "changelog_redo_";
 /// # Purpose
 /// For multi-byte UTF-8 characters, we need to create a sequence of log files
 /// with letter suffixes to maintain LIFO ordering.
+/// Hex-mode byte-sequence inserts reuse the same scheme for longer sets
+/// (up to MAX_LOG_SET_ENTRIES: bare number + 26 letters).
 ///
 /// # Arguments
 /// * `byte_index` - Index of byte in character (0 = first, 3 = last)
-/// * `total_bytes` - Total number of bytes in character (1-4)
+/// * `total_bytes` - Total number of bytes in character (1-4),
+///   or in a hex byte-sequence set (1-27)
 ///
 /// # Returns
 /// * `Option<char>` - Letter suffix, or None for the last byte (no extension)
@@ -4309,17 +4318,17 @@ pub fn get_log_file_letter_suffix(byte_index: usize, total_bytes: usize) -> Opti
     // =================================================
 
     debug_assert!(
-        total_bytes >= 1 && total_bytes <= MAX_UTF8_BYTES,
-        "total_bytes must be 1-4"
+        total_bytes >= 1 && total_bytes <= MAX_LOG_SET_ENTRIES,
+        "total_bytes must be 1-27"
     );
 
     #[cfg(test)]
     assert!(
-        total_bytes >= 1 && total_bytes <= MAX_UTF8_BYTES,
-        "total_bytes must be 1-4"
+        total_bytes >= 1 && total_bytes <= MAX_LOG_SET_ENTRIES,
+        "total_bytes must be 1-27"
     );
 
-    if total_bytes < 1 || total_bytes > MAX_UTF8_BYTES {
+    if total_bytes < 1 || total_bytes > MAX_LOG_SET_ENTRIES {
         // Production: return None as safe fallback
         return None;
     }
@@ -4514,7 +4523,7 @@ mod log_entry_tests {
 /// let next = get_next_log_number(&log_dir)?;
 /// assert_eq!(next, 4);
 /// ```
-fn get_next_log_number(log_dir: &Path) -> ButtonResult<u128> {
+pub fn get_next_log_number(log_dir: &Path) -> ButtonResult<u128> {
     // If directory doesn't exist, start at 0
     if !log_dir.exists() {
         return Ok(0);
@@ -4602,6 +4611,16 @@ fn get_next_log_number(log_dir: &Path) -> ButtonResult<u128> {
     }
 }
 
+/// Writes one changelog file; with --secure-encrypt it is then encrypted
+///
+/// Changelog files are small, so they are encrypted in place right after
+/// the write (see session_encryption_module). Without an active session
+/// key this is a plain `fs::write`.
+fn write_changelog_file(log_file_path: &Path, log_content: &str) -> io::Result<()> {
+    fs::write(log_file_path, log_content)?;
+    encrypt_if_session_encrypted(log_file_path)
+}
+
 /// Creates a single-byte log file in the specified directory
 ///
 /// # Purpose
@@ -4683,7 +4702,7 @@ fn write_log_entry_to_file(
     let log_content = log_entry.to_file_format();
 
     // Write to file
-    fs::write(&log_file_path, log_content).map_err(|e| {
+    write_changelog_file(&log_file_path, &log_content).map_err(|e| {
         // Log error before returning
         log_button_error(
             target_file,
@@ -5069,20 +5088,24 @@ fn read_log_file(log_file_path: &Path) -> ButtonResult<LogEntry> {
         });
     }
 
-    // Read file content
-    let content = fs::read_to_string(log_file_path).map_err(|_e| {
-        #[cfg(debug_assertions)]
-        eprintln!(
-            "Failed to read log file {}: {}",
-            log_file_path.display(),
-            _e
-        );
+    // Read file content (decrypted if --secure-encrypt stored it encrypted)
+    let content = read_session_file(log_file_path)
+        .and_then(|bytes| {
+            String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .map_err(|_e| {
+            #[cfg(debug_assertions)]
+            eprintln!(
+                "Failed to read log file {}: {}",
+                log_file_path.display(),
+                _e
+            );
 
-        ButtonError::MalformedLog {
-            logpath: log_file_path.to_path_buf(),
-            reason: "Cannot read log file",
-        }
-    })?;
+            ButtonError::MalformedLog {
+                logpath: log_file_path.to_path_buf(),
+                reason: "Cannot read log file",
+            }
+        })?;
 
     // Parse into LogEntry
     let log_entry = LogEntry::from_file_format(&content).map_err(|reason| {
@@ -5941,7 +5964,7 @@ pub fn button_remove_multibyte_make_log_files(
 
         // Serialize and write
         let log_content = log_entry.to_file_format();
-        fs::write(&log_file_path, log_content).map_err(|e| {
+        write_changelog_file(&log_file_path, &log_content).map_err(|e| {
             log_button_error(
                 target_file,
                 &format!("Failed to write multi-byte log file {}: {}", filename, e),
@@ -5957,6 +5980,264 @@ pub fn button_remove_multibyte_make_log_files(
     Ok(())
 }
 
+/// Creates one log set for a hex-mode byte-sequence insert (user ADDED bytes)
+///
+/// # Purpose
+/// When the user inserts a run of raw bytes in hex mode (e.g. `DE AD BE EF`),
+/// create a single letter-suffixed log set of "remove" entries so that one
+/// undo removes the whole run. Same "cheap trick" button stack as
+/// `button_remove_multibyte_make_log_files`, but for raw bytes (no UTF-8
+/// validation) and for up to MAX_LOG_SET_ENTRIES bytes.
+///
+/// # Inverse Changelog Logic
+/// - User action: ADD 4 bytes (DE AD BE EF) at position 20
+/// - Log entries: RMV at position 20 (four times, each recording its byte)
+/// - Log files created: "10.c", "10.b", "10.a", "10"
+///
+/// # Arguments
+/// * `target_file` - File being edited (absolute path)
+/// * `edit_file_position` - Position of the first inserted byte (0-indexed)
+/// * `inserted_bytes` - The bytes that were inserted (1-27)
+/// * `log_directory_path` - Directory to write log files (absolute path)
+///
+/// # Returns
+/// * `ButtonResult<()>` - Success or error
+pub fn button_remove_byte_sequence_make_log_files(
+    target_file: &Path,
+    edit_file_position: u128,
+    inserted_bytes: &[u8],
+    log_directory_path: &Path,
+) -> ButtonResult<()> {
+    let byte_count = inserted_bytes.len();
+
+    // =================================================
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    // =================================================
+
+    debug_assert!(
+        (1..=MAX_LOG_SET_ENTRIES).contains(&byte_count),
+        "Byte sequence count must be 1-27"
+    );
+
+    #[cfg(test)]
+    assert!(
+        (1..=MAX_LOG_SET_ENTRIES).contains(&byte_count),
+        "Byte sequence count must be 1-27"
+    );
+
+    if !(1..=MAX_LOG_SET_ENTRIES).contains(&byte_count) {
+        return Err(ButtonError::AssertionViolation {
+            check: "Byte sequence count must be 1-27",
+        });
+    }
+
+    // Create log directory if needed
+    if !log_directory_path.exists() {
+        fs::create_dir_all(log_directory_path).map_err(ButtonError::Io)?;
+    }
+
+    // One base number for the whole set
+    let base_log_number = get_next_log_number(log_directory_path)?;
+
+    // Bounded loop: max 27 iterations (MAX_LOG_SET_ENTRIES)
+    for (byte_index, &byte_value) in inserted_bytes.iter().enumerate() {
+        // Create log entry: Rmv byte at position (records the byte removed)
+        let log_entry = LogEntry::new(EditType::RmvByte, edit_file_position, Some(byte_value))
+            .map_err(|e| ButtonError::AssertionViolation { check: e })?;
+
+        let filename = match get_log_file_letter_suffix(byte_index, byte_count) {
+            Some(letter) => format!("{}.{}", base_log_number, letter),
+            None => base_log_number.to_string(),
+        };
+
+        let log_file_path = log_directory_path.join(&filename);
+
+        write_changelog_file(&log_file_path, &log_entry.to_file_format()).map_err(|e| {
+            log_button_error(
+                target_file,
+                &format!("Failed to write byte-sequence log file {}: {}", filename, e),
+                Some("button_remove_byte_sequence_make_log_files"),
+            );
+            ButtonError::Io(e)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Creates one log set for a hex-mode byte-range removal (user REMOVED bytes)
+///
+/// # Purpose
+/// Inverse of `button_remove_byte_sequence_make_log_files`: when the user
+/// deletes a run of raw bytes in hex mode, create a single letter-suffixed
+/// log set of "add" entries so that one undo puts the whole run back.
+///
+/// # Inverse Changelog Logic
+/// - User action: REMOVE 3 bytes (DE AD BE) at position 20
+/// - Log entries: ADD at position 20, each carrying its byte
+/// - Log files created: "10.b" (add BE), "10.a" (add AD), "10" (add DE)
+/// - Undo runs highest letter first, so the run is rebuilt in order
+///
+/// # Arguments
+/// * `target_file` - File being edited (absolute path)
+/// * `edit_file_position` - Position of the first removed byte (0-indexed)
+/// * `removed_bytes` - The bytes that were removed (1-27)
+/// * `log_directory_path` - Directory to write log files (absolute path)
+///
+/// # Returns
+/// * `ButtonResult<()>` - Success or error
+pub fn button_add_byte_sequence_make_log_files(
+    target_file: &Path,
+    edit_file_position: u128,
+    removed_bytes: &[u8],
+    log_directory_path: &Path,
+) -> ButtonResult<()> {
+    let byte_count = removed_bytes.len();
+
+    // =================================================
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    // =================================================
+
+    debug_assert!(
+        (1..=MAX_LOG_SET_ENTRIES).contains(&byte_count),
+        "Byte sequence count must be 1-27"
+    );
+
+    #[cfg(test)]
+    assert!(
+        (1..=MAX_LOG_SET_ENTRIES).contains(&byte_count),
+        "Byte sequence count must be 1-27"
+    );
+
+    if !(1..=MAX_LOG_SET_ENTRIES).contains(&byte_count) {
+        return Err(ButtonError::AssertionViolation {
+            check: "Byte sequence count must be 1-27",
+        });
+    }
+
+    // Create log directory if needed
+    if !log_directory_path.exists() {
+        fs::create_dir_all(log_directory_path).map_err(ButtonError::Io)?;
+    }
+
+    // One base number for the whole set
+    let base_log_number = get_next_log_number(log_directory_path)?;
+
+    // Bounded loop: max 27 iterations (MAX_LOG_SET_ENTRIES)
+    for (byte_index, &byte_value) in removed_bytes.iter().enumerate() {
+        // Create log entry: Add byte back at position
+        let log_entry = LogEntry::new(EditType::AddByte, edit_file_position, Some(byte_value))
+            .map_err(|e| ButtonError::AssertionViolation { check: e })?;
+
+        let filename = match get_log_file_letter_suffix(byte_index, byte_count) {
+            Some(letter) => format!("{}.{}", base_log_number, letter),
+            None => base_log_number.to_string(),
+        };
+
+        let log_file_path = log_directory_path.join(&filename);
+
+        write_changelog_file(&log_file_path, &log_entry.to_file_format()).map_err(|e| {
+            log_button_error(
+                target_file,
+                &format!("Failed to write byte-sequence log file {}: {}", filename, e),
+                Some("button_add_byte_sequence_make_log_files"),
+            );
+            ButtonError::Io(e)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Creates one log set for a run of hex-mode in-place edits (user OVERWROTE bytes)
+///
+/// # Purpose
+/// A hex fill overwrites a run of bytes without changing the file length.
+/// Instead of one log per byte (`button_hexeditinplace_byte_make_log_file`),
+/// the run gets one letter-suffixed set of "edit" entries, each at its own
+/// position, so a large fill undoes one set at a time.
+///
+/// # Inverse Changelog Logic
+/// - User action: OVERWRITE 3 bytes (11 22 33) at position 20 with 00
+/// - Log entries: EDT 11 at 20, EDT 22 at 21, EDT 33 at 22
+/// - Log files created: "10.b", "10.a", "10"
+/// - In-place edits move nothing, so the order they run in does not matter
+///
+/// # Arguments
+/// * `target_file` - File being edited (absolute path)
+/// * `edit_file_position` - Position of the first overwritten byte (0-indexed)
+/// * `original_bytes` - The bytes as they were before the edit (1-27)
+/// * `log_directory_path` - Directory to write log files (absolute path)
+///
+/// # Returns
+/// * `ButtonResult<()>` - Success or error
+pub fn button_hexeditinplace_byte_run_make_log_files(
+    target_file: &Path,
+    edit_file_position: u128,
+    original_bytes: &[u8],
+    log_directory_path: &Path,
+) -> ButtonResult<()> {
+    let byte_count = original_bytes.len();
+
+    // =================================================
+    // Debug-Assert, Test-Assert, Production-Catch-Handle
+    // =================================================
+
+    debug_assert!(
+        (1..=MAX_LOG_SET_ENTRIES).contains(&byte_count),
+        "Byte run count must be 1-27"
+    );
+
+    #[cfg(test)]
+    assert!(
+        (1..=MAX_LOG_SET_ENTRIES).contains(&byte_count),
+        "Byte run count must be 1-27"
+    );
+
+    if !(1..=MAX_LOG_SET_ENTRIES).contains(&byte_count) {
+        return Err(ButtonError::AssertionViolation {
+            check: "Byte run count must be 1-27",
+        });
+    }
+
+    // Create log directory if needed
+    if !log_directory_path.exists() {
+        fs::create_dir_all(log_directory_path).map_err(ButtonError::Io)?;
+    }
+
+    // One base number for the whole set
+    let base_log_number = get_next_log_number(log_directory_path)?;
+
+    // Bounded loop: max 27 iterations (MAX_LOG_SET_ENTRIES)
+    for (byte_index, &byte_value) in original_bytes.iter().enumerate() {
+        // Create log entry: Edit the byte at its own position back
+        let log_entry = LogEntry::new(
+            EditType::EdtByteInplace,
+            edit_file_position + byte_index as u128,
+            Some(byte_value),
+        )
+        .map_err(|e| ButtonError::AssertionViolation { check: e })?;
+
+        let filename = match get_log_file_letter_suffix(byte_index, byte_count) {
+            Some(letter) => format!("{}.{}", base_log_number, letter),
+            None => base_log_number.to_string(),
+        };
+
+        let log_file_path = log_directory_path.join(&filename);
+
+        write_changelog_file(&log_file_path, &log_entry.to_file_format()).map_err(|e| {
+            log_button_error(
+                target_file,
+                &format!("Failed to write byte-run log file {}: {}", filename, e),
+                Some("button_hexeditinplace_byte_run_make_log_files"),
+            );
+            ButtonError::Io(e)
+        })?;
+    }
+
+    Ok(())
+}
+
 /// Creates multiple log files for a multi-byte character addition (user REMOVED)
 ///
 /// # Purpose
@@ -6096,7 +6377,7 @@ pub fn button_add_multibyte_make_log_files(
 
         // Serialize and write
         let log_content = log_entry.to_file_format();
-        fs::write(&log_file_path, log_content).map_err(|e| {
+        write_changelog_file(&log_file_path, &log_content).map_err(|e| {
             log_button_error(
                 target_file,
                 &format!("Failed to write multi-byte log file {}: {}", filename, e),
@@ -6137,6 +6418,7 @@ pub fn button_add_multibyte_make_log_files(
 /// - 2-byte: "10.a", "10"
 /// - 3-byte: "10.b", "10.a", "10"
 /// - 4-byte: "10.c", "10.b", "10.a", "10"
+/// - hex byte-sequence: up to "10.z" ... "10.a", "10"
 ///
 /// # LIFO Order
 /// Returns highest letter first: [10.c, 10.b, 10.a, 10]
@@ -6147,7 +6429,7 @@ pub fn button_add_multibyte_make_log_files(
 /// - No orphaned letters (e.g., having 'b' without 'a')
 /// - Returns error if incomplete set detected
 fn find_multibyte_log_set(log_dir: &Path, base_number: u128) -> ButtonResult<Vec<PathBuf>> {
-    let mut log_files = Vec::with_capacity(MAX_UTF8_BYTES);
+    let mut log_files = Vec::with_capacity(MAX_LOG_SET_ENTRIES);
 
     // Check for bare number (required)
     let bare_path = log_dir.join(base_number.to_string());
@@ -6160,7 +6442,7 @@ fn find_multibyte_log_set(log_dir: &Path, base_number: u128) -> ButtonResult<Vec
 
     // FIXED: Scan ALL possible letter files first (don't break early)
     let mut found_letters = Vec::new();
-    for i in 0..(MAX_UTF8_BYTES - 1) {
+    for i in 0..(MAX_LOG_SET_ENTRIES - 1) {
         let letter = LOG_LETTER_SEQUENCE[i];
         let letter_path = log_dir.join(format!("{}.{}", base_number, letter));
 
@@ -6927,8 +7209,8 @@ pub fn button_undo_redo_next_inverse_changelog_pop_lifo(
     // Check for letter-suffix files to determine if multi-byte
     let mut has_letter_files = false;
 
-    // Bounded loop: check for letters a, b, c (max 3)
-    for i in 0..(MAX_UTF8_BYTES - 1) {
+    // Bounded loop: check for letters a..z (max 26)
+    for i in 0..(MAX_LOG_SET_ENTRIES - 1) {
         let letter = LOG_LETTER_SEQUENCE[i];
         let letter_path = log_dir_abs.join(format!("{}.{}", base_number, letter));
 
@@ -7232,24 +7514,24 @@ fn button_undo_multibyte_with_redo_support(
             base_position
         );
 
-        // Bounded loop: max 4 iterations (MAX_UTF8_BYTES)
+        // Bounded loop: max 27 iterations (MAX_LOG_SET_ENTRIES)
         for byte_index in 0..byte_count {
             // =================================================
             // Debug-Assert, Test-Assert, Production-Catch-Handle
             // =================================================
 
             debug_assert!(
-                byte_index < MAX_UTF8_BYTES,
+                byte_index < MAX_LOG_SET_ENTRIES,
                 "Byte index exceeded max UTF-8 bytes"
             );
 
             #[cfg(test)]
             assert!(
-                byte_index < MAX_UTF8_BYTES,
+                byte_index < MAX_LOG_SET_ENTRIES,
                 "Byte index exceeded max UTF-8 bytes"
             );
 
-            if byte_index >= MAX_UTF8_BYTES {
+            if byte_index >= MAX_LOG_SET_ENTRIES {
                 return Err(ButtonError::AssertionViolation {
                     check: "Too many log entries in set",
                 });
@@ -7290,13 +7572,16 @@ fn button_undo_multibyte_with_redo_support(
                     }
                 }
                 EditType::EdtByteInplace => {
-                    // About to EDIT byte - capture current value from ACTUAL position
-                    match read_single_byte_from_file(target_file, actual_file_position) {
+                    // About to EDIT byte - capture its current value. In-place
+                    // entries each carry their own position (a hex fill run),
+                    // which is where the byte is
+                    match read_single_byte_from_file(target_file, log_entry.position()) {
                         Ok(byte) => {
                             #[cfg(debug_assertions)]
                             println!(
-                                "    Captured byte 0x{:02X} from ACTUAL position {} for hex-edit redo",
-                                byte, actual_file_position
+                                "    Captured byte 0x{:02X} from position {} for hex-edit redo",
+                                byte,
+                                log_entry.position()
                             );
                             Some(byte)
                         }
@@ -7304,7 +7589,8 @@ fn button_undo_multibyte_with_redo_support(
                             #[cfg(debug_assertions)]
                             eprintln!(
                                 "    Warning: Could not capture byte at position {}: {}",
-                                actual_file_position, _e
+                                log_entry.position(),
+                                _e
                             );
                             None
                         }
@@ -7338,24 +7624,24 @@ fn button_undo_multibyte_with_redo_support(
     // =========================================
     // Operations use log positions (cheap trick - all at position 0)
 
-    // Bounded loop: max 4 iterations (MAX_UTF8_BYTES)
+    // Bounded loop: max 27 iterations (MAX_LOG_SET_ENTRIES)
     for (i, log_entry) in log_entries.iter().enumerate() {
         // =================================================
         // Debug-Assert, Test-Assert, Production-Catch-Handle
         // =================================================
 
         debug_assert!(
-            i < MAX_UTF8_BYTES,
+            i < MAX_LOG_SET_ENTRIES,
             "Log entry index exceeded max UTF-8 bytes"
         );
 
         #[cfg(test)]
         assert!(
-            i < MAX_UTF8_BYTES,
+            i < MAX_LOG_SET_ENTRIES,
             "Log entry index exceeded max UTF-8 bytes"
         );
 
-        if i >= MAX_UTF8_BYTES {
+        if i >= MAX_LOG_SET_ENTRIES {
             return Err(ButtonError::AssertionViolation {
                 check: "Too many log entries in set",
             });
@@ -7524,12 +7810,21 @@ fn create_inverse_redo_log(
         }
 
         EditType::AddByte => {
-            // Undo log said "add X" - we added a byte
-            // Redo log should say "rmv"
+            // Undo log said "add_byte X" - we added byte X
+            // Redo log should say "rmv_byte X" (rmv_byte logs record their byte,
+            // same as hex byte-sequence logs, and are rejected on read without one)
+            let byte = undo_log_entry
+                .byte_value()
+                .ok_or(ButtonError::InvalidUtf8 {
+                    position,
+                    byte_count: 1,
+                    reason: "Cannot create redo log: add_byte log has no byte",
+                })?;
+
             #[cfg(debug_assertions)]
-            println!("  Inverse: add byte -> rmv at {}", position);
+            println!("  Inverse: add byte -> rmv 0x{:02X} at {}", byte, position);
 
-            LogEntry::new(EditType::RmvByte, position, None)
+            LogEntry::new(EditType::RmvByte, position, Some(byte))
                 .map_err(|e| ButtonError::AssertionViolation { check: e })?
         }
 
@@ -7650,24 +7945,24 @@ fn create_inverse_redo_logs_multibyte(
 
     let byte_count = undo_log_entries.len();
 
-    // Bounded loop: max 4 iterations
+    // Bounded loop: max 27 iterations (MAX_LOG_SET_ENTRIES)
     for (byte_index, undo_log_entry) in undo_log_entries.iter().enumerate() {
         // =================================================
         // Debug-Assert, Test-Assert, Production-Catch-Handle
         // =================================================
 
         debug_assert!(
-            byte_index < MAX_UTF8_BYTES,
+            byte_index < MAX_LOG_SET_ENTRIES,
             "Byte index exceeded max UTF-8 bytes"
         );
 
         #[cfg(test)]
         assert!(
-            byte_index < MAX_UTF8_BYTES,
+            byte_index < MAX_LOG_SET_ENTRIES,
             "Byte index exceeded max UTF-8 bytes"
         );
 
-        if byte_index >= MAX_UTF8_BYTES {
+        if byte_index >= MAX_LOG_SET_ENTRIES {
             log_button_error(
                 target_file,
                 "Too many log entries in redo set",
@@ -7764,7 +8059,7 @@ fn create_inverse_redo_logs_multibyte(
         // Serialize and write
         let log_content = inverse_log_entry.to_file_format();
 
-        if let Err(e) = fs::write(&log_file_path, log_content) {
+        if let Err(e) = write_changelog_file(&log_file_path, &log_content) {
             // Debug: verbose error
             #[cfg(debug_assertions)]
             eprintln!("Failed to write redo log file {}: {}", filename, e);
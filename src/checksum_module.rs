@@ -0,0 +1,396 @@
+//! checksum_module.rs - CRC32 and SHA-256 in vanilla Rust, streamed over files
+//!
+//! ## HEAP ALLOCATION: NONE
+//!
+//! Both digests are computed incrementally over a fixed-size stack buffer,
+//! so any file (or any byte range of a file) can be hashed without loading
+//! it into memory.
+//!
+//! ## Algorithms
+//! - CRC32: IEEE 802.3 polynomial (reflected 0xEDB88320), as used by
+//!   zip, gzip, and png. Table is built at compile time.
+//! - SHA-256: FIPS 180-4.
+//!
+//! ## Memory Model
+//! - Read chunks: one stack buffer of `CHECKSUM_CHUNK_SIZE` bytes
+//! - SHA-256 block: 64-byte stack buffer inside `Sha256`
+//! - Hex output: caller-provided fixed arrays
+//!
+//! ## Limitations (By Design)
+//! - Read loop is bounded by (range length / chunk size) + 2 iterations
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Bytes read from disk per iteration
+const CHECKSUM_CHUNK_SIZE: usize = 4096;
+
+/// Length of a CRC32 rendered as lowercase hex
+pub const CRC32_HEX_LENGTH: usize = 8;
+
+/// Length of a SHA-256 digest rendered as lowercase hex
+pub const SHA256_HEX_LENGTH: usize = 64;
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+// ============================================================================
+// CRC32
+// ============================================================================
+
+/// CRC32 lookup table, computed at compile time
+const CRC32_TABLE: [u32; 256] = build_crc32_table();
+
+const fn build_crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = index as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            if crc & 1 == 1 {
+                crc = (crc >> 1) ^ 0xEDB8_8320;
+            } else {
+                crc >>= 1;
+            }
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+}
+
+/// Streaming CRC32 (IEEE) state
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    pub fn new() -> Self {
+        Crc32 { crc: 0xFFFF_FFFF }
+    }
+
+    /// Feeds more bytes into the running checksum
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            let table_index = ((self.crc ^ byte as u32) & 0xFF) as usize;
+            self.crc = (self.crc >> 8) ^ CRC32_TABLE[table_index];
+        }
+    }
+
+    /// Returns the final checksum (state is not consumed)
+    pub fn finalize(&self) -> u32 {
+        self.crc ^ 0xFFFF_FFFF
+    }
+}
+
+// ============================================================================
+// SHA-256
+// ============================================================================
+
+/// SHA-256 round constants (first 32 bits of the fractional parts of the
+/// cube roots of the first 64 primes)
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 initial hash values
+const SHA256_H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Streaming SHA-256 state
+pub struct Sha256 {
+    state: [u32; 8],
+    block: [u8; 64],
+    block_length: usize,
+    total_length: u64,
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Sha256 {
+            state: SHA256_H0,
+            block: [0u8; 64],
+            block_length: 0,
+            total_length: 0,
+        }
+    }
+
+    /// Feeds more bytes into the running digest
+    pub fn update(&mut self, data: &[u8]) {
+        self.total_length = self.total_length.wrapping_add(data.len() as u64);
+
+        let mut remaining = data;
+        while !remaining.is_empty() {
+            let take = (64 - self.block_length).min(remaining.len());
+            self.block[self.block_length..self.block_length + take]
+                .copy_from_slice(&remaining[..take]);
+            self.block_length += take;
+            remaining = &remaining[take..];
+
+            if self.block_length == 64 {
+                let block = self.block;
+                self.compress(&block);
+                self.block_length = 0;
+            }
+        }
+    }
+
+    /// Pads, processes the last block(s), and returns the 32-byte digest
+    pub fn finalize(mut self) -> [u8; 32] {
+        let bit_length = self.total_length.wrapping_mul(8);
+
+        // Padding: 0x80, zeros, then 64-bit big-endian length
+        let mut padding = [0u8; 72];
+        padding[0] = 0x80;
+        let padding_length = if self.block_length < 56 {
+            56 - self.block_length
+        } else {
+            120 - self.block_length
+        };
+        padding[padding_length..padding_length + 8].copy_from_slice(&bit_length.to_be_bytes());
+
+        // update() would change total_length; feed blocks directly
+        let total_before = self.total_length;
+        self.update(&padding[..padding_length + 8]);
+        self.total_length = total_before;
+
+        let mut digest = [0u8; 32];
+        for (word_index, word) in self.state.iter().enumerate() {
+            digest[word_index * 4..word_index * 4 + 4].copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut w = [0u32; 64];
+        for (i, chunk) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA256_K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(maj);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+
+        let working = [a, b, c, d, e, f, g, h];
+        for (state_word, working_word) in self.state.iter_mut().zip(working.iter()) {
+            *state_word = state_word.wrapping_add(*working_word);
+        }
+    }
+}
+
+// ============================================================================
+// File Streaming
+// ============================================================================
+
+/// Computes CRC32 and SHA-256 over an inclusive byte range of a file
+///
+/// # Arguments
+/// * `file_path` - File to read
+/// * `start` - First byte (0-indexed, inclusive)
+/// * `end` - Last byte (0-indexed, inclusive); `None` means end of file
+///
+/// # Returns
+/// * `Ok((crc32, sha256, byte_count))`
+/// * `Err(io::Error)` - Open/seek/read failure, or range past end of file
+///
+/// # Memory Safety
+/// - One stack read buffer of `CHECKSUM_CHUNK_SIZE` bytes
+/// - Bounded iteration (range length / chunk size, plus margin)
+pub fn checksum_file_range(
+    file_path: &Path,
+    start: u64,
+    end: Option<u64>,
+) -> io::Result<(u32, [u8; 32], u64)> {
+    let mut file = File::open(file_path)?;
+    let file_size = file.metadata()?.len();
+
+    let range_end_exclusive = match end {
+        Some(last_byte) => {
+            if last_byte >= file_size || start > last_byte {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "checksum range outside file",
+                ));
+            }
+            last_byte + 1
+        }
+        None => file_size,
+    };
+
+    let mut crc32 = Crc32::new();
+    let mut sha256 = Sha256::new();
+    let mut remaining = range_end_exclusive.saturating_sub(start);
+    let byte_count = remaining;
+
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut buffer = [0u8; CHECKSUM_CHUNK_SIZE];
+    let max_iterations = (byte_count / CHECKSUM_CHUNK_SIZE as u64) + 2;
+
+    for _ in 0..max_iterations {
+        if remaining == 0 {
+            break;
+        }
+        let to_read = remaining.min(CHECKSUM_CHUNK_SIZE as u64) as usize;
+        let bytes_read = file.read(&mut buffer[..to_read])?;
+        if bytes_read == 0 {
+            // File shrank under us
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "file ended during checksum",
+            ));
+        }
+        crc32.update(&buffer[..bytes_read]);
+        sha256.update(&buffer[..bytes_read]);
+        remaining -= bytes_read as u64;
+    }
+
+    if remaining != 0 {
+        return Err(io::Error::other("checksum iteration limit reached"));
+    }
+
+    Ok((crc32.finalize(), sha256.finalize(), byte_count))
+}
+
+/// Renders a CRC32 as 8 lowercase hex digits into a caller buffer
+pub fn crc32_to_hex(crc32: u32, hex_buffer: &mut [u8; CRC32_HEX_LENGTH]) -> &str {
+    for (i, byte) in crc32.to_be_bytes().iter().enumerate() {
+        hex_buffer[i * 2] = HEX_DIGITS[(byte >> 4) as usize];
+        hex_buffer[i * 2 + 1] = HEX_DIGITS[(byte & 0x0F) as usize];
+    }
+    // Only ASCII hex digits were written
+    std::str::from_utf8(hex_buffer).unwrap_or("")
+}
+
+/// Renders a SHA-256 digest as 64 lowercase hex digits into a caller buffer
+pub fn sha256_to_hex<'a>(
+    digest: &[u8; 32],
+    hex_buffer: &'a mut [u8; SHA256_HEX_LENGTH],
+) -> &'a str {
+    for (i, byte) in digest.iter().enumerate() {
+        hex_buffer[i * 2] = HEX_DIGITS[(byte >> 4) as usize];
+        hex_buffer[i * 2 + 1] = HEX_DIGITS[(byte & 0x0F) as usize];
+    }
+    // Only ASCII hex digits were written
+    std::str::from_utf8(hex_buffer).unwrap_or("")
+}
+
+// ============================================================================
+// TESTS - Known Answer Vectors
+// ============================================================================
+
+#[cfg(test)]
+mod checksum_tests {
+    use super::*;
+    use std::fs;
+
+    fn sha256_hex_of(data: &[u8]) -> [u8; SHA256_HEX_LENGTH] {
+        let mut sha256 = Sha256::new();
+        sha256.update(data);
+        let digest = sha256.finalize();
+        let mut hex_buffer = [0u8; SHA256_HEX_LENGTH];
+        let _ = sha256_to_hex(&digest, &mut hex_buffer);
+        hex_buffer
+    }
+
+    #[test]
+    fn test_crc32_known_values() {
+        let mut crc32 = Crc32::new();
+        crc32.update(b"123456789");
+        assert_eq!(crc32.finalize(), 0xCBF4_3926);
+
+        assert_eq!(Crc32::new().finalize(), 0);
+
+        let mut hex_buffer = [0u8; CRC32_HEX_LENGTH];
+        assert_eq!(crc32_to_hex(0xCBF4_3926, &mut hex_buffer), "cbf43926");
+    }
+
+    #[test]
+    fn test_sha256_known_values() {
+        assert_eq!(
+            &sha256_hex_of(b""),
+            b"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            &sha256_hex_of(b"abc"),
+            b"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // Two-block message (56 bytes forces a second padding block)
+        assert_eq!(
+            &sha256_hex_of(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            b"248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_checksum_file_range_streams_across_chunks() {
+        let mut test_path = std::env::temp_dir();
+        test_path.push("lines_checksum_test_range.bin");
+
+        // Larger than one chunk, with a recognisable slice in the middle
+        let mut content = vec![b'x'; CHECKSUM_CHUNK_SIZE * 2 + 100];
+        content[5000..5003].copy_from_slice(b"abc");
+        fs::write(&test_path, &content).expect("Should write test file");
+
+        let (whole_crc, whole_sha, whole_count) =
+            checksum_file_range(&test_path, 0, None).expect("Whole-file checksum");
+        assert_eq!(whole_count, content.len() as u64);
+        let mut expected_crc = Crc32::new();
+        expected_crc.update(&content);
+        assert_eq!(whole_crc, expected_crc.finalize());
+        let mut expected_sha = Sha256::new();
+        expected_sha.update(&content);
+        assert_eq!(whole_sha, expected_sha.finalize());
+
+        let (_, range_sha, range_count) =
+            checksum_file_range(&test_path, 5000, Some(5002)).expect("Range checksum");
+        assert_eq!(range_count, 3);
+        let mut hex_buffer = [0u8; SHA256_HEX_LENGTH];
+        assert_eq!(
+            sha256_to_hex(&range_sha, &mut hex_buffer),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        assert!(checksum_file_range(&test_path, 0, Some(content.len() as u64)).is_err());
+
+        let _ = fs::remove_file(&test_path);
+    }
+}
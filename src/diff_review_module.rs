@@ -0,0 +1,136 @@
+//! diff_review_module.rs - Reading .patch / .diff files (`]h`, `[h`)
+//!
+//! ## Project Context
+//! A unified diff is read line by line, and each line's first bytes say
+//! what it is:
+//! ```text
+//! diff --git a/src/main.rs b/src/main.rs     file header
+//! --- a/src/main.rs                          file header
+//! +++ b/src/main.rs                          file header
+//! @@ -12,7 +12,8 @@ fn main() {             hunk header
+//!      let x = 1;                            context
+//! -    let y = 2;                            removed
+//! +    let y = 3;                            added
+//! ```
+//! A file named *.patch or *.diff is shown in diff review: lines are
+//! coloured by kind instead of by syntax, and `]h` / `[h` move between
+//! hunk headers, naming the hunk in the info bar.
+//!
+//! ## What this module does
+//! Decides which files get diff review, classifies a line from its first
+//! bytes, and cuts a hunk header down to its "@@ ... @@" part. Finding
+//! hunks in the file is the editor's chunked byte search.
+
+use std::path::Path;
+
+/// File extensions shown in diff review (case-sensitive, as for syntax
+/// highlighting)
+const DIFF_EXTENSIONS: [&[u8]; 2] = [b"patch", b"diff"];
+
+/// Bytes of a line diff_line_kind looks at ("index " is the longest
+/// prefix it knows)
+pub const DIFF_LINE_PREFIX_BYTES: usize = 6;
+
+/// Start of a hunk header line, after the previous line's newline
+pub const HUNK_HEADER_AFTER_NEWLINE: &[u8] = b"\n@@ ";
+
+/// What a line of a unified diff is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiffLineKind {
+    /// Unchanged line (or anything not recognized)
+    #[default]
+    Context,
+    /// `+` line
+    Added,
+    /// `-` line
+    Removed,
+    /// `@@ -12,7 +12,8 @@` line
+    HunkHeader,
+    /// `diff`, `index`, `---`, `+++` line
+    FileHeader,
+}
+
+/// Whether `path` names a .patch or .diff file
+pub fn is_diff_path(path: Option<&Path>) -> bool {
+    path.and_then(Path::extension)
+        .is_some_and(|extension| DIFF_EXTENSIONS.contains(&extension.as_encoded_bytes()))
+}
+
+/// The kind of a diff line from its first bytes (at most
+/// DIFF_LINE_PREFIX_BYTES are needed; a newline ends the line)
+pub fn diff_line_kind(line_start: &[u8]) -> DiffLineKind {
+    const FILE_HEADER_PREFIXES: [&[u8]; 4] = [b"diff ", b"index ", b"--- ", b"+++ "];
+    if FILE_HEADER_PREFIXES
+        .iter()
+        .any(|prefix| line_start.starts_with(prefix))
+    {
+        return DiffLineKind::FileHeader;
+    }
+    match line_start {
+        [b'@', b'@', ..] => DiffLineKind::HunkHeader,
+        [b'+', ..] => DiffLineKind::Added,
+        [b'-', ..] => DiffLineKind::Removed,
+        _ => DiffLineKind::Context,
+    }
+}
+
+/// "@@ -12,7 +12,8 @@" from the start of a hunk header line: through
+/// the closing "@@", else up to the newline (or all of `line`)
+pub fn hunk_header_label(line: &[u8]) -> &[u8] {
+    let line = match line.iter().position(|&byte| byte == b'\n') {
+        Some(newline) => &line[..newline],
+        None => line,
+    };
+    match line
+        .get(2..)
+        .and_then(|rest| rest.windows(2).position(|pair| pair == b"@@"))
+    {
+        Some(closing) => &line[..closing + 4],
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod diff_review_tests {
+    use super::*;
+
+    #[test]
+    fn test_is_diff_path() {
+        assert!(is_diff_path(Some(Path::new("fix.patch"))));
+        assert!(is_diff_path(Some(Path::new("/tmp/review/changes.diff"))));
+        assert!(!is_diff_path(Some(Path::new("patch.txt"))));
+        assert!(!is_diff_path(Some(Path::new("diff"))));
+        assert!(!is_diff_path(None));
+    }
+
+    #[test]
+    fn test_diff_line_kind() {
+        assert_eq!(
+            diff_line_kind(b"diff --git a/x b/x"),
+            DiffLineKind::FileHeader
+        );
+        assert_eq!(diff_line_kind(b"index 1a2b"), DiffLineKind::FileHeader);
+        assert_eq!(diff_line_kind(b"--- a/x"), DiffLineKind::FileHeader);
+        assert_eq!(diff_line_kind(b"+++ b/x"), DiffLineKind::FileHeader);
+        assert_eq!(diff_line_kind(b"@@ -1,2 "), DiffLineKind::HunkHeader);
+        assert_eq!(diff_line_kind(b"+    le"), DiffLineKind::Added);
+        assert_eq!(diff_line_kind(b"+\n"), DiffLineKind::Added);
+        assert_eq!(diff_line_kind(b"-    le"), DiffLineKind::Removed);
+        // A removed line that starts "-- " is still a removed line
+        assert_eq!(diff_line_kind(b"-- c"), DiffLineKind::Removed);
+        assert_eq!(diff_line_kind(b"     l"), DiffLineKind::Context);
+        assert_eq!(diff_line_kind(b"\n"), DiffLineKind::Context);
+        assert_eq!(diff_line_kind(b""), DiffLineKind::Context);
+    }
+
+    #[test]
+    fn test_hunk_header_label() {
+        assert_eq!(
+            hunk_header_label(b"@@ -12,7 +12,8 @@ fn main() {\n+x"),
+            b"@@ -12,7 +12,8 @@"
+        );
+        assert_eq!(hunk_header_label(b"@@ -1 +1 @@"), b"@@ -1 +1 @@");
+        assert_eq!(hunk_header_label(b"@@ -1,40 +1,4"), b"@@ -1,40 +1,4");
+        assert_eq!(hunk_header_label(b"@@ broken\nnext"), b"@@ broken");
+    }
+}
@@ -0,0 +1,322 @@
+//! escape_sequence_module.rs - Decode arrow / Home / End / Page key sequences
+//!
+//! ## Project Context
+//! Lines reads whole input lines (the "+Enter" system). Keys such as the
+//! arrows arrive inside that line as escape sequences: Up then Enter is
+//! "\x1b[A\n", Up pressed three times then Enter is "\x1b[A\x1b[A\x1b[A\n".
+//! Terminals do not agree on the bytes:
+//! ```text
+//! Up       \x1b[A   \x1bOA (application cursor mode)   \x1b[1;5A (Ctrl+Up)
+//! Home     \x1b[H   \x1bOH   \x1b[1~   \x1b[7~
+//! End      \x1b[F   \x1bOF   \x1b[4~   \x1b[8~
+//! PageUp   \x1b[5~          PageDown \x1b[6~          Delete \x1b[3~
+//! ```
+//! This module turns such a line into one key and a press count. Each
+//! mode's command reader maps the key to its own command.
+//!
+//! ## Scope
+//! - A line must be made only of sequences for the same key; anything else
+//!   is reported as unrecognized, so it is never typed in as text
+//! - Modifiers (Shift, Ctrl, Alt) are ignored: Ctrl+Up is Up
+//! - A lone ESC is not a key here (every mode already reads it as "leave")
+//!
+//! ## Bracketed paste
+//! Pasted lines arrive like typed ones, one read per line, so Insert mode
+//! cannot tell a paste's newlines from Enter presses. While Insert mode
+//! is open the terminal is asked to mark pastes:
+//! ```text
+//! \x1b[?2004h  on    \x1b[200~ pasted text \x1b[201~    \x1b[?2004l  off
+//! ```
+//! Terminals without the mode ignore the request and paste unmarked.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Start marker of a bracketed paste
+pub const PASTE_START: &[u8] = b"\x1b[200~";
+
+/// End marker of a bracketed paste
+pub const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Asks the terminal to mark pastes (DEC private mode 2004)
+const BRACKETED_PASTE_ON: &[u8] = b"\x1b[?2004h";
+
+/// Back to unmarked pastes
+pub const BRACKETED_PASTE_OFF: &[u8] = b"\x1b[?2004l";
+
+/// Marking is on right now (read by the signal handler, which has to
+/// switch it off before exiting)
+static BRACKETED_PASTE_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Keys that reach Lines as escape sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialKey {
+    Up,
+    Down,
+    Right,
+    Left,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Delete,
+}
+
+/// What an input line is, as far as special keys go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyLine {
+    /// Does not start with an escape sequence: ordinary text or a command
+    NotKeys,
+    /// One key, pressed this many times
+    Keys(SpecialKey, usize),
+    /// Unknown sequences, or sequences for different keys
+    Unrecognized,
+}
+
+/// Longest CSI sequence decoded, e.g. "\x1b[1;5A" is 6 bytes
+const MAX_SEQUENCE_BYTES: usize = 8;
+
+/// Most sequences counted in one input line
+const MAX_SEQUENCES_PER_LINE: usize = 1024;
+
+/// Decodes the escape sequence at the start of `bytes`
+///
+/// # Returns
+/// The key and the sequence's length in bytes, or None if `bytes` does
+/// not start with a known sequence.
+pub fn decode_escape_sequence(bytes: &[u8]) -> Option<(SpecialKey, usize)> {
+    if bytes.len() < 3 || bytes[0] != 0x1B {
+        return None;
+    }
+
+    match bytes[1] {
+        // SS3: ESC O letter
+        b'O' => letter_key(bytes[2]).map(|key| (key, 3)),
+
+        // CSI: ESC [ parameters final-byte
+        b'[' => {
+            let mut end = 2;
+            while end < bytes.len()
+                && end < MAX_SEQUENCE_BYTES
+                && (bytes[end].is_ascii_digit() || bytes[end] == b';')
+            {
+                end += 1;
+            }
+            let final_byte = *bytes.get(end)?;
+            let first_parameter = bytes[2..end]
+                .split(|&byte| byte == b';')
+                .next()
+                .unwrap_or(&[]);
+
+            let key = match final_byte {
+                b'~' => tilde_key(first_parameter)?,
+                letter => letter_key(letter)?,
+            };
+            Some((key, end + 1))
+        }
+
+        _ => None,
+    }
+}
+
+/// Decodes an input line made of key sequences ("\x1b[A\x1b[A" = Up twice)
+///
+/// Surrounding whitespace (the Enter) is ignored.
+pub fn decode_key_line(input: &str) -> KeyLine {
+    let bytes = input.trim().as_bytes();
+    if bytes.len() < 3 || bytes[0] != 0x1B {
+        return KeyLine::NotKeys;
+    }
+
+    let mut position = 0;
+    let mut line_key: Option<SpecialKey> = None;
+    let mut presses: usize = 0;
+
+    while position < bytes.len() {
+        if presses >= MAX_SEQUENCES_PER_LINE {
+            return KeyLine::Unrecognized;
+        }
+        match decode_escape_sequence(&bytes[position..]) {
+            Some((key, length)) if line_key.is_none_or(|first| first == key) => {
+                line_key = Some(key);
+                presses += 1;
+                position += length;
+            }
+            _ => return KeyLine::Unrecognized,
+        }
+    }
+
+    match line_key {
+        Some(key) => KeyLine::Keys(key, presses),
+        None => KeyLine::Unrecognized,
+    }
+}
+
+/// Position of the first paste end marker in `bytes`
+pub fn find_paste_end(bytes: &[u8]) -> Option<usize> {
+    bytes
+        .windows(PASTE_END.len())
+        .position(|window| window == PASTE_END)
+}
+
+/// How many bytes at the end of `bytes` could be the first part of a
+/// paste end marker cut in two by a full read buffer
+pub fn paste_end_prefix_len(bytes: &[u8]) -> usize {
+    (1..PASTE_END.len())
+        .rev()
+        .find(|&length| bytes.ends_with(&PASTE_END[..length]))
+        .unwrap_or(0)
+}
+
+/// The terminal's bracketed paste mode; switched off again on drop
+pub struct BracketedPaste {
+    /// False when stdin / stdout are not a terminal: nothing is written
+    enabled: bool,
+    active: bool,
+}
+
+impl BracketedPaste {
+    pub fn new(enabled: bool) -> Self {
+        BracketedPaste {
+            enabled,
+            active: false,
+        }
+    }
+
+    /// Switches marking on or off; writes only when that changes it
+    pub fn set(&mut self, on: bool) {
+        if !self.enabled || self.active == on {
+            return;
+        }
+        let request = if on {
+            BRACKETED_PASTE_ON
+        } else {
+            BRACKETED_PASTE_OFF
+        };
+        let mut stdout = io::stdout();
+        // Unmarked pastes still work: a failed write is not an error
+        let _ = stdout.write_all(request);
+        let _ = stdout.flush();
+        self.active = on;
+        BRACKETED_PASTE_ACTIVE.store(on, Ordering::SeqCst);
+    }
+}
+
+/// The terminal was asked to mark pastes and not yet asked to stop
+///
+/// One atomic load: safe in a signal handler.
+pub fn bracketed_paste_active() -> bool {
+    BRACKETED_PASTE_ACTIVE.load(Ordering::SeqCst)
+}
+
+impl Drop for BracketedPaste {
+    fn drop(&mut self) {
+        self.set(false);
+    }
+}
+
+/// Final letter of a CSI or SS3 sequence
+fn letter_key(letter: u8) -> Option<SpecialKey> {
+    match letter {
+        b'A' => Some(SpecialKey::Up),
+        b'B' => Some(SpecialKey::Down),
+        b'C' => Some(SpecialKey::Right),
+        b'D' => Some(SpecialKey::Left),
+        b'H' => Some(SpecialKey::Home),
+        b'F' => Some(SpecialKey::End),
+        _ => None,
+    }
+}
+
+/// Number before the '~' of a "\x1b[N~" sequence (VT220 / rxvt keys)
+fn tilde_key(number: &[u8]) -> Option<SpecialKey> {
+    match number {
+        b"1" | b"7" => Some(SpecialKey::Home),
+        b"3" => Some(SpecialKey::Delete),
+        b"4" | b"8" => Some(SpecialKey::End),
+        b"5" => Some(SpecialKey::PageUp),
+        b"6" => Some(SpecialKey::PageDown),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod escape_sequence_tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_terminal_variants() {
+        assert_eq!(decode_escape_sequence(b"\x1b[A"), Some((SpecialKey::Up, 3)));
+        assert_eq!(
+            decode_escape_sequence(b"\x1bOB"),
+            Some((SpecialKey::Down, 3))
+        );
+        assert_eq!(
+            decode_escape_sequence(b"\x1b[1;5C"),
+            Some((SpecialKey::Right, 6))
+        );
+        assert_eq!(
+            decode_escape_sequence(b"\x1b[H"),
+            Some((SpecialKey::Home, 3))
+        );
+        assert_eq!(
+            decode_escape_sequence(b"\x1b[7~"),
+            Some((SpecialKey::Home, 4))
+        );
+        assert_eq!(
+            decode_escape_sequence(b"\x1bOF"),
+            Some((SpecialKey::End, 3))
+        );
+        assert_eq!(
+            decode_escape_sequence(b"\x1b[4~"),
+            Some((SpecialKey::End, 4))
+        );
+        assert_eq!(
+            decode_escape_sequence(b"\x1b[5~"),
+            Some((SpecialKey::PageUp, 4))
+        );
+        assert_eq!(
+            decode_escape_sequence(b"\x1b[6;2~"),
+            Some((SpecialKey::PageDown, 6))
+        );
+        assert_eq!(
+            decode_escape_sequence(b"\x1b[3~"),
+            Some((SpecialKey::Delete, 4))
+        );
+
+        assert_eq!(decode_escape_sequence(b"\x1b[15~"), None); // F5
+        assert_eq!(decode_escape_sequence(b"\x1b["), None);
+        assert_eq!(decode_escape_sequence(b"abc"), None);
+    }
+
+    #[test]
+    fn test_decode_key_line() {
+        assert_eq!(
+            decode_key_line("\x1b[A\n"),
+            KeyLine::Keys(SpecialKey::Up, 1)
+        );
+        assert_eq!(
+            decode_key_line("\x1b[D\x1bOD\x1b[1;2D\n"),
+            KeyLine::Keys(SpecialKey::Left, 3)
+        );
+        assert_eq!(decode_key_line("\x1b[A\x1b[B"), KeyLine::Unrecognized);
+        assert_eq!(decode_key_line("\x1b[Ax"), KeyLine::Unrecognized);
+        assert_eq!(decode_key_line("\x1b[24~"), KeyLine::Unrecognized);
+
+        assert_eq!(decode_key_line("\x1b\n"), KeyLine::NotKeys);
+        assert_eq!(decode_key_line("hello"), KeyLine::NotKeys);
+        assert_eq!(decode_key_line("5j"), KeyLine::NotKeys);
+    }
+
+    #[test]
+    fn test_paste_end_marker() {
+        assert_eq!(find_paste_end(b"one\ntwo\x1b[201~\n"), Some(7));
+        assert_eq!(find_paste_end(b"\x1b[201~"), Some(0));
+        assert_eq!(find_paste_end(b"one\x1b[200~"), None);
+
+        assert_eq!(paste_end_prefix_len(b"text\x1b[20"), 4);
+        assert_eq!(paste_end_prefix_len(b"text\x1b"), 1);
+        assert_eq!(paste_end_prefix_len(b"text\x1b[200"), 0);
+        assert_eq!(paste_end_prefix_len(b"text"), 0);
+    }
+}
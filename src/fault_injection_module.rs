@@ -0,0 +1,302 @@
+//! fault_injection_module.rs - Make chosen I/O operations fail on purpose
+//!
+//! ## Project Context
+//! Lines' error policy is "handle and move on": transient errors are retried
+//! (`retry_operation`), permanent ones are logged and reported, and nothing
+//! should panic or leave a half-written file behind. Those paths are hard to
+//! reach with a real filesystem. This module lets tests (and debug builds)
+//! make the Nth read, write, or rename fail with a chosen `io::ErrorKind`.
+//!
+//! ## Usage
+//! I/O call sites that matter call `fault_point(FaultOperation::X)?` just
+//! before the real operation. Tests arm a fault with `inject_fault`; debug
+//! builds can also arm faults from the environment:
+//! ```text
+//! LINES_FAULT_INJECT=rename:1:permission_denied
+//! LINES_FAULT_INJECT=read:2:interrupted:3,write:1:timed_out
+//! ```
+//! Fields: operation, nth call (1-based), error kind, optional repeat count
+//! (consecutive calls that fail, default 1).
+//!
+//! ## Scope
+//! - Only compiled in under `cfg(test)` or `debug_assertions`; in release
+//!   builds `fault_point` is an empty inline function
+//! - State is per thread, so parallel tests do not see each other's faults
+//! - Operation counts start at zero when a fault plan is armed or cleared
+
+/// Kinds of I/O operation a fault can be injected into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultOperation {
+    Read,
+    Write,
+    Rename,
+}
+
+/// Returns Err if a fault is armed for this call of `operation`
+///
+/// Release builds: always Ok, compiled away.
+#[cfg(not(any(test, debug_assertions)))]
+#[inline(always)]
+pub fn fault_point(_operation: FaultOperation) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(any(test, debug_assertions))]
+pub use injection::*;
+
+#[cfg(any(test, debug_assertions))]
+mod injection {
+    use super::FaultOperation;
+    use std::cell::RefCell;
+    use std::env;
+    use std::io::{self, ErrorKind};
+
+    /// Environment variable read (debug builds) for initial fault plans
+    pub const FAULT_INJECT_ENV_VAR: &str = "LINES_FAULT_INJECT";
+
+    /// Number of FaultOperation variants (size of per-operation tables)
+    const FAULT_OPERATION_COUNT: usize = 3;
+
+    /// Upper bound on consecutive failures per plan
+    const MAX_FAULT_REPEAT: usize = 1000;
+
+    impl FaultOperation {
+        fn index(self) -> usize {
+            match self {
+                FaultOperation::Read => 0,
+                FaultOperation::Write => 1,
+                FaultOperation::Rename => 2,
+            }
+        }
+
+        fn from_name(name: &str) -> Option<Self> {
+            match name {
+                "read" => Some(FaultOperation::Read),
+                "write" => Some(FaultOperation::Write),
+                "rename" => Some(FaultOperation::Rename),
+                _ => None,
+            }
+        }
+    }
+
+    /// One armed fault: calls nth..nth+repeat-1 of `operation` fail with `kind`
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct FaultPlan {
+        pub operation: FaultOperation,
+        /// First failing call, 1-based
+        pub nth: usize,
+        /// Consecutive failing calls, at least 1
+        pub repeat: usize,
+        pub kind: ErrorKind,
+    }
+
+    /// Per-thread plans and call counters
+    struct FaultState {
+        plans: [Option<FaultPlan>; FAULT_OPERATION_COUNT],
+        calls_seen: [usize; FAULT_OPERATION_COUNT],
+    }
+
+    impl FaultState {
+        fn empty() -> Self {
+            FaultState {
+                plans: [None; FAULT_OPERATION_COUNT],
+                calls_seen: [0; FAULT_OPERATION_COUNT],
+            }
+        }
+
+        /// Initial state: plans from LINES_FAULT_INJECT outside tests
+        fn from_environment() -> Self {
+            let mut state = FaultState::empty();
+
+            // Tests arm faults explicitly; a stray variable must not leak in
+            if cfg!(test) {
+                return state;
+            }
+
+            if let Ok(spec) = env::var(FAULT_INJECT_ENV_VAR) {
+                match parse_fault_plans(&spec) {
+                    Ok(plans) => {
+                        for plan in plans.into_iter().flatten() {
+                            state.plans[plan.operation.index()] = Some(plan);
+                        }
+                    }
+                    Err(message) => {
+                        eprintln!("Ignoring {}: {}", FAULT_INJECT_ENV_VAR, message);
+                    }
+                }
+            }
+            state
+        }
+    }
+
+    thread_local! {
+        static FAULT_STATE: RefCell<FaultState> = RefCell::new(FaultState::from_environment());
+    }
+
+    /// Arms a fault for this thread, replacing any plan for that operation
+    ///
+    /// The operation's call counter restarts, so `nth` counts from now.
+    #[cfg(test)]
+    pub fn inject_fault(plan: FaultPlan) {
+        let plan = FaultPlan {
+            repeat: plan.repeat.clamp(1, MAX_FAULT_REPEAT),
+            ..plan
+        };
+        FAULT_STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let index = plan.operation.index();
+            state.plans[index] = Some(plan);
+            state.calls_seen[index] = 0;
+        });
+    }
+
+    /// Disarms every fault on this thread and resets the counters
+    #[cfg(test)]
+    pub fn clear_faults() {
+        FAULT_STATE.with(|state| *state.borrow_mut() = FaultState::empty());
+    }
+
+    /// Number of `operation` calls seen since its plan was armed or cleared
+    #[cfg(test)]
+    pub fn fault_calls_seen(operation: FaultOperation) -> usize {
+        FAULT_STATE.with(|state| state.borrow().calls_seen[operation.index()])
+    }
+
+    /// Returns Err if a fault is armed for this call of `operation`
+    ///
+    /// Call immediately before the real I/O operation, with `?`.
+    pub fn fault_point(operation: FaultOperation) -> io::Result<()> {
+        FAULT_STATE.with(|state| {
+            let mut state = state.borrow_mut();
+            let index = operation.index();
+            state.calls_seen[index] = state.calls_seen[index].saturating_add(1);
+            let call = state.calls_seen[index];
+
+            match state.plans[index] {
+                Some(plan) if call >= plan.nth && call - plan.nth < plan.repeat => {
+                    Err(io::Error::new(plan.kind, "injected fault"))
+                }
+                _ => Ok(()),
+            }
+        })
+    }
+
+    /// Parses "op:nth:kind[:repeat]" entries separated by commas
+    ///
+    /// # Returns
+    /// One slot per operation (a later entry for the same operation wins),
+    /// or a short message naming the first bad entry.
+    pub fn parse_fault_plans(
+        spec: &str,
+    ) -> Result<[Option<FaultPlan>; FAULT_OPERATION_COUNT], String> {
+        let mut plans = [None; FAULT_OPERATION_COUNT];
+
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let bad_entry = || format!("bad entry '{}'", entry);
+            let mut fields = entry.split(':');
+
+            let operation = fields
+                .next()
+                .and_then(FaultOperation::from_name)
+                .ok_or_else(bad_entry)?;
+            let nth = fields
+                .next()
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|&n| n > 0)
+                .ok_or_else(bad_entry)?;
+            let kind = fields
+                .next()
+                .and_then(error_kind_from_name)
+                .ok_or_else(bad_entry)?;
+            let repeat = match fields.next() {
+                Some(count) => count
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| (1..=MAX_FAULT_REPEAT).contains(&n))
+                    .ok_or_else(bad_entry)?,
+                None => 1,
+            };
+            if fields.next().is_some() {
+                return Err(bad_entry());
+            }
+
+            plans[operation.index()] = Some(FaultPlan {
+                operation,
+                nth,
+                repeat,
+                kind,
+            });
+        }
+        Ok(plans)
+    }
+
+    /// Error kinds that can be injected, by snake_case name
+    fn error_kind_from_name(name: &str) -> Option<ErrorKind> {
+        match name {
+            "interrupted" => Some(ErrorKind::Interrupted),
+            "would_block" => Some(ErrorKind::WouldBlock),
+            "timed_out" => Some(ErrorKind::TimedOut),
+            "permission_denied" => Some(ErrorKind::PermissionDenied),
+            "not_found" => Some(ErrorKind::NotFound),
+            "storage_full" => Some(ErrorKind::StorageFull),
+            "other" => Some(ErrorKind::Other),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod fault_injection_tests {
+    use super::*;
+    use std::io::ErrorKind;
+
+    #[test]
+    fn test_unarmed_fault_point_is_ok() {
+        clear_faults();
+        assert!(fault_point(FaultOperation::Read).is_ok());
+        assert_eq!(fault_calls_seen(FaultOperation::Read), 1);
+    }
+
+    #[test]
+    fn test_nth_call_fails_with_repeat() {
+        clear_faults();
+        inject_fault(FaultPlan {
+            operation: FaultOperation::Write,
+            nth: 2,
+            repeat: 2,
+            kind: ErrorKind::TimedOut,
+        });
+
+        assert!(fault_point(FaultOperation::Write).is_ok());
+        let error = fault_point(FaultOperation::Write).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+        assert!(fault_point(FaultOperation::Write).is_err());
+        assert!(fault_point(FaultOperation::Write).is_ok());
+
+        // Other operations are unaffected
+        assert!(fault_point(FaultOperation::Rename).is_ok());
+        clear_faults();
+    }
+
+    #[test]
+    fn test_parse_fault_plans() {
+        let plans = parse_fault_plans("read:2:interrupted:3, rename:1:permission_denied").unwrap();
+        assert_eq!(
+            plans[0],
+            Some(FaultPlan {
+                operation: FaultOperation::Read,
+                nth: 2,
+                repeat: 3,
+                kind: ErrorKind::Interrupted,
+            })
+        );
+        assert_eq!(plans[1], None);
+        assert_eq!(plans[2].map(|p| p.kind), Some(ErrorKind::PermissionDenied));
+
+        assert!(parse_fault_plans("read:0:interrupted").is_err());
+        assert!(parse_fault_plans("seek:1:interrupted").is_err());
+        assert!(parse_fault_plans("write:1:on_fire").is_err());
+        assert!(parse_fault_plans("write:1:other:2:extra").is_err());
+        assert!(parse_fault_plans("").unwrap().iter().all(Option::is_none));
+    }
+}
@@ -0,0 +1,387 @@
+//! input_record_replay_module.rs - Record and replay editor input, byte-exact
+//!
+//! ## Project Context
+//! Rendering and corruption bugs are hard to describe and easy to reproduce
+//! once the exact input is known. `lines --record FILE` appends every chunk
+//! of input the editor reads to FILE; `lines --replay FILE` feeds those
+//! chunks back, in order, before handing over to the keyboard.
+//!
+//! ## Recording Format (v1)
+//! ```text
+//! # lines input recording v1
+//! +<milliseconds since recording start> <byte count>
+//! <exactly byte count raw bytes>
+//! ```
+//! One record per read. The raw bytes are written untouched (usually a
+//! command line ending in '\n'), then a '\n' separator, so the file stays
+//! readable in a text editor and exact for binary input.
+//!
+//! ## Determinism
+//! Replay ignores the timestamps: chunks are delivered as fast as the
+//! editor asks for them, at the same chunk boundaries they were recorded
+//! with (as long as the reading buffer is at least as large).
+//!
+//! ## Limitations (By Design)
+//! - Keystroke-input mode ('ki') reads the terminal directly and is not
+//!   recorded or replayed
+//! - A damaged record ends the replay early (with a message on stderr);
+//!   input then comes from the keyboard
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::time::Instant;
+
+/// First line of every recording
+pub const RECORDING_HEADER: &str = "# lines input recording v1\n";
+
+/// Largest single record accepted on replay (a sanity bound, not a limit
+/// on recording: editor reads are at most a few hundred bytes)
+const MAX_RECORD_BYTES: usize = 64 * 1024;
+
+/// Bytes buffered for BufRead callers (read_line in the help menu)
+const LINE_BUFFER_SIZE: usize = 256;
+
+/// Longest record header line ("+<ms> <count>\n")
+const MAX_RECORD_HEADER_LENGTH: usize = 48;
+
+// ============================================================================
+// RECORDING
+// ============================================================================
+
+/// Appends input chunks to a recording file
+pub struct InputRecorder {
+    file: File,
+    started: Instant,
+}
+
+impl InputRecorder {
+    /// Opens (or creates) a recording file for appending
+    ///
+    /// A new or empty file gets the RECORDING_HEADER first. An existing
+    /// recording is appended to, so a crashed session's input is kept.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() == 0 {
+            file.write_all(RECORDING_HEADER.as_bytes())?;
+            file.flush()?;
+        }
+        Ok(InputRecorder {
+            file,
+            started: Instant::now(),
+        })
+    }
+
+    /// Appends one record; flushed at once so a crash cannot lose it
+    pub fn record(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let elapsed_ms = self.started.elapsed().as_millis();
+        let header = format!("+{} {}\n", elapsed_ms, bytes.len());
+        self.file.write_all(header.as_bytes())?;
+        self.file.write_all(bytes)?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()
+    }
+}
+
+// ============================================================================
+// REPLAY
+// ============================================================================
+
+/// Reads input chunks back from a recording file
+pub struct InputReplay {
+    reader: BufReader<File>,
+}
+
+impl InputReplay {
+    /// Opens a recording and checks its header
+    ///
+    /// # Errors
+    /// - InvalidData if the file does not start with RECORDING_HEADER
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = String::new();
+        reader
+            .by_ref()
+            .take(RECORDING_HEADER.len() as u64)
+            .read_line(&mut header)?;
+        if header != RECORDING_HEADER {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not a lines input recording (missing header)",
+            ));
+        }
+        Ok(InputReplay { reader })
+    }
+
+    /// Reads the next record into `chunk`
+    ///
+    /// # Returns
+    /// * `Ok(true)` - `chunk` holds the next record's bytes
+    /// * `Ok(false)` - Recording fully replayed
+    /// * `Err(InvalidData)` - Damaged record
+    pub fn next_record(&mut self, chunk: &mut Vec<u8>) -> io::Result<bool> {
+        let mut header = String::new();
+        let header_length = self
+            .reader
+            .by_ref()
+            .take(MAX_RECORD_HEADER_LENGTH as u64)
+            .read_line(&mut header)?;
+        if header_length == 0 {
+            return Ok(false);
+        }
+
+        let byte_count = parse_record_header(&header)?;
+
+        chunk.clear();
+        chunk.resize(byte_count, 0);
+        self.reader.read_exact(chunk)?;
+
+        let mut separator = [0u8; 1];
+        self.reader.read_exact(&mut separator)?;
+        if separator[0] != b'\n' {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Recording record not followed by newline",
+            ));
+        }
+        Ok(true)
+    }
+}
+
+/// Parses "+<ms> <count>\n", returning the byte count
+fn parse_record_header(header: &str) -> io::Result<usize> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Bad recording record header");
+
+    let fields = header.strip_prefix('+').ok_or_else(invalid)?.trim_end();
+    let (elapsed_ms, byte_count) = fields.split_once(' ').ok_or_else(invalid)?;
+    elapsed_ms.parse::<u128>().map_err(|_| invalid())?;
+    let byte_count = byte_count.parse::<usize>().map_err(|_| invalid())?;
+    if byte_count > MAX_RECORD_BYTES {
+        return Err(invalid());
+    }
+    Ok(byte_count)
+}
+
+// ============================================================================
+// EDITOR INPUT: LIVE SOURCE + OPTIONAL RECORDER + OPTIONAL REPLAY
+// ============================================================================
+
+/// Editor input that can record what it reads and replay a recording first
+///
+/// ## Project Context
+/// Wraps the stdin lock the editor modes read from. With neither recorder
+/// nor replay it is a pass-through. Reads are served from the replay until
+/// it runs out, then from `live`; every chunk handed to the editor (replayed
+/// or live) goes to the recorder.
+pub struct RecordReplayInput<R: Read> {
+    live: R,
+    recorder: Option<InputRecorder>,
+    replay: Option<InputReplay>,
+    /// Current replay record and how much of it was handed out
+    replay_chunk: Vec<u8>,
+    replay_chunk_position: usize,
+    /// BufRead buffer (only used by read_line callers)
+    line_buffer: [u8; LINE_BUFFER_SIZE],
+    line_buffer_start: usize,
+    line_buffer_end: usize,
+}
+
+impl<R: Read> RecordReplayInput<R> {
+    /// Plain pass-through over `live`
+    pub fn new(live: R) -> Self {
+        RecordReplayInput {
+            live,
+            recorder: None,
+            replay: None,
+            replay_chunk: Vec::new(),
+            replay_chunk_position: 0,
+            line_buffer: [0u8; LINE_BUFFER_SIZE],
+            line_buffer_start: 0,
+            line_buffer_end: 0,
+        }
+    }
+
+    /// Records every chunk read from here on
+    pub fn with_recorder(mut self, recorder: InputRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Serves the recording's chunks before any live input
+    pub fn with_replay(mut self, replay: InputReplay) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+
+    /// Whether replayed input is still being served
+    #[cfg(test)]
+    fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Copies pending replay bytes into `buf`, loading records as needed
+    ///
+    /// Returns 0 when the replay is finished (or was stopped by a damaged
+    /// record), after which reads come from `live`.
+    fn read_from_replay(&mut self, buf: &mut [u8]) -> usize {
+        while self.replay_chunk_position >= self.replay_chunk.len() {
+            let replay = match self.replay.as_mut() {
+                Some(replay) => replay,
+                None => return 0,
+            };
+            match replay.next_record(&mut self.replay_chunk) {
+                Ok(true) => self.replay_chunk_position = 0,
+                Ok(false) => {
+                    self.replay = None;
+                    return 0;
+                }
+                Err(e) => {
+                    eprintln!("Replay stopped: {}", e);
+                    self.replay = None;
+                    self.replay_chunk.clear();
+                    self.replay_chunk_position = 0;
+                    return 0;
+                }
+            }
+        }
+
+        let pending = &self.replay_chunk[self.replay_chunk_position..];
+        let count = pending.len().min(buf.len());
+        buf[..count].copy_from_slice(&pending[..count]);
+        self.replay_chunk_position += count;
+        count
+    }
+
+    /// One read from replay (if active) or live input, then recorded
+    fn read_from_source(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut count = self.read_from_replay(buf);
+        if count == 0 {
+            count = self.live.read(buf)?;
+        }
+
+        if count > 0 {
+            if let Some(recorder) = self.recorder.as_mut() {
+                if let Err(e) = recorder.record(&buf[..count]) {
+                    // Keep editing; only the recording is lost from here on
+                    eprintln!("Recording stopped: {}", e);
+                    self.recorder = None;
+                }
+            }
+        }
+        Ok(count)
+    }
+}
+
+impl<R: Read> Read for RecordReplayInput<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Bytes already buffered for a read_line caller go first
+        if self.line_buffer_start < self.line_buffer_end {
+            let buffered = &self.line_buffer[self.line_buffer_start..self.line_buffer_end];
+            let count = buffered.len().min(buf.len());
+            buf[..count].copy_from_slice(&buffered[..count]);
+            self.line_buffer_start += count;
+            return Ok(count);
+        }
+        self.read_from_source(buf)
+    }
+}
+
+impl<R: Read> BufRead for RecordReplayInput<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.line_buffer_start >= self.line_buffer_end {
+            let mut chunk = [0u8; LINE_BUFFER_SIZE];
+            let count = self.read_from_source(&mut chunk)?;
+            self.line_buffer[..count].copy_from_slice(&chunk[..count]);
+            self.line_buffer_start = 0;
+            self.line_buffer_end = count;
+        }
+        Ok(&self.line_buffer[self.line_buffer_start..self.line_buffer_end])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.line_buffer_start = (self.line_buffer_start + amount).min(self.line_buffer_end);
+    }
+}
+
+#[cfg(test)]
+mod input_record_replay_tests {
+    use super::*;
+    use std::fs;
+    use std::io::Cursor;
+
+    fn temp_recording_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "lines_recording_{}_{}.txt",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_record_then_replay_round_trip() {
+        let path = temp_recording_path("round_trip");
+
+        // Record two reads of live input (one per command line)
+        {
+            let recorder = InputRecorder::open(&path).expect("open recorder");
+            let mut input = RecordReplayInput::new(Cursor::new(b"j\nhello\xFF\n".to_vec()))
+                .with_recorder(recorder);
+            let mut buf = [0u8; 2];
+            assert_eq!(input.read(&mut buf).expect("first read"), 2);
+            let mut buf = [0u8; 16];
+            assert_eq!(input.read(&mut buf).expect("second read"), 7);
+        }
+
+        let recording = fs::read(&path).expect("read recording");
+        assert!(recording.starts_with(RECORDING_HEADER.as_bytes()));
+
+        // Replay: same chunks, then the live source takes over
+        let replay = InputReplay::open(&path).expect("open replay");
+        let mut input = RecordReplayInput::new(Cursor::new(b"q\n".to_vec())).with_replay(replay);
+        let mut buf = [0u8; 64];
+        assert_eq!(input.read(&mut buf).expect("replay 1"), 2);
+        assert_eq!(&buf[..2], b"j\n");
+        assert_eq!(input.read(&mut buf).expect("replay 2"), 7);
+        assert_eq!(&buf[..7], b"hello\xFF\n");
+        assert_eq!(input.read(&mut buf).expect("live"), 2);
+        assert_eq!(&buf[..2], b"q\n");
+        assert!(!input.is_replaying());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_read_line_through_buffer() {
+        let mut input = RecordReplayInput::new(Cursor::new(b"3\nrest".to_vec()));
+        let mut line = String::new();
+        input.read_line(&mut line).expect("read_line");
+        assert_eq!(line, "3\n");
+
+        // Bytes buffered past the line are not lost to plain reads
+        let mut buf = [0u8; 8];
+        assert_eq!(input.read(&mut buf).expect("read after line"), 4);
+        assert_eq!(&buf[..4], b"rest");
+    }
+
+    #[test]
+    fn test_replay_rejects_non_recording() {
+        let path = temp_recording_path("not_a_recording");
+        fs::write(&path, b"just some notes\n").expect("write file");
+        assert!(InputReplay::open(&path).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_parse_record_header() {
+        assert_eq!(parse_record_header("+120 5\n").expect("valid"), 5);
+        assert!(parse_record_header("120 5\n").is_err());
+        assert!(parse_record_header("+x 5\n").is_err());
+        assert!(parse_record_header("+1 99999999\n").is_err());
+    }
+}
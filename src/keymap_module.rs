@@ -0,0 +1,142 @@
+//! keymap_module.rs - Multi-key command sequences (gg, ge, dd, yy, ...)
+//!
+//! ## Project Context
+//! Some commands are several keys: `gg`, `ge`, `dd`, `yw`. A Keymap is a
+//! fixed table of key sequences and what they do, looked up as a small
+//! state machine: after each key the keys so far are either a bound
+//! command, the start of a longer one, both, or nothing.
+//! ```text
+//! table: d dd gg ge         "g" -> Prefix          "gg" -> Bound
+//!                           "d" -> BoundOrLonger   "gx" -> Unbound
+//! ```
+//! In the "+Enter" system the whole line is known, so only Bound and
+//! BoundOrLonger run. Reading single keypresses (--raw-keys), a Prefix
+//! waits for the next key, and BoundOrLonger waits up to
+//! KEY_SEQUENCE_TIMEOUT_DECISECONDS before running the shorter command.
+//!
+//! ## Adding a sequence
+//! Add one `(keys, action)` row to the mode's table; both input systems
+//! pick it up. Counts ("3dd") are handled by the caller, not the table.
+
+/// How long a key that is a command and also starts a longer one ("d" of
+/// "dd") waits for the next key, in tenths of a second (stty `time` units)
+pub const KEY_SEQUENCE_TIMEOUT_DECISECONDS: u8 = 8;
+
+/// Result of looking up the keys typed so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyLookup<A> {
+    /// A command, and no longer sequence starts with these keys
+    Bound(A),
+    /// A command, but a longer sequence also starts with these keys
+    BoundOrLonger(A),
+    /// Not a command yet, but the start of one
+    Prefix,
+    /// Neither a command nor the start of one
+    Unbound,
+}
+
+/// What a keypress reader should do next with the keys it has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyWait {
+    /// Run the keys now
+    Ready,
+    /// Wait for another key
+    NeedMore,
+    /// Wait for another key, for at most KEY_SEQUENCE_TIMEOUT_DECISECONDS
+    ReadyAfterTimeout,
+    /// The keys match nothing: run them (the parser reports them), or,
+    /// after ReadyAfterTimeout, run all but the last key
+    NoMatch,
+}
+
+impl<A> KeyLookup<A> {
+    /// How a keypress reader treats keys with this lookup result
+    pub fn wait(&self) -> KeyWait {
+        match self {
+            KeyLookup::Bound(_) => KeyWait::Ready,
+            KeyLookup::Unbound => KeyWait::NoMatch,
+            KeyLookup::BoundOrLonger(_) => KeyWait::ReadyAfterTimeout,
+            KeyLookup::Prefix => KeyWait::NeedMore,
+        }
+    }
+}
+
+/// A mode's table of key sequences
+pub struct Keymap<A: 'static> {
+    bindings: &'static [(&'static str, A)],
+}
+
+impl<A: Copy> Keymap<A> {
+    /// Keymap over a fixed table; sequences must be unique
+    pub const fn new(bindings: &'static [(&'static str, A)]) -> Self {
+        Keymap { bindings }
+    }
+
+    /// Looks up the keys typed so far
+    pub fn lookup(&self, keys: &str) -> KeyLookup<A> {
+        if keys.is_empty() {
+            return KeyLookup::Prefix;
+        }
+
+        let mut bound = None;
+        let mut longer = false;
+        for &(sequence, action) in self.bindings {
+            if sequence == keys {
+                bound = Some(action);
+            } else if sequence.starts_with(keys) {
+                longer = true;
+            }
+        }
+
+        match (bound, longer) {
+            (Some(action), false) => KeyLookup::Bound(action),
+            (Some(action), true) => KeyLookup::BoundOrLonger(action),
+            (None, true) => KeyLookup::Prefix,
+            (None, false) => KeyLookup::Unbound,
+        }
+    }
+}
+
+#[cfg(test)]
+mod keymap_tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum TestAction {
+        Delete,
+        Top,
+        Bottom,
+    }
+
+    const TEST_KEYMAP: Keymap<TestAction> = Keymap::new(&[
+        ("d", TestAction::Delete),
+        ("dd", TestAction::Delete),
+        ("gg", TestAction::Top),
+        ("ge", TestAction::Bottom),
+    ]);
+
+    #[test]
+    fn test_lookup_states() {
+        assert_eq!(TEST_KEYMAP.lookup("gg"), KeyLookup::Bound(TestAction::Top));
+        assert_eq!(
+            TEST_KEYMAP.lookup("dd"),
+            KeyLookup::Bound(TestAction::Delete)
+        );
+        assert_eq!(
+            TEST_KEYMAP.lookup("d"),
+            KeyLookup::BoundOrLonger(TestAction::Delete)
+        );
+        assert_eq!(TEST_KEYMAP.lookup("g"), KeyLookup::Prefix);
+        assert_eq!(TEST_KEYMAP.lookup(""), KeyLookup::Prefix);
+        assert_eq!(TEST_KEYMAP.lookup("gx"), KeyLookup::Unbound);
+        assert_eq!(TEST_KEYMAP.lookup("ggg"), KeyLookup::Unbound);
+    }
+
+    #[test]
+    fn test_lookup_wait() {
+        assert_eq!(TEST_KEYMAP.lookup("g").wait(), KeyWait::NeedMore);
+        assert_eq!(TEST_KEYMAP.lookup("d").wait(), KeyWait::ReadyAfterTimeout);
+        assert_eq!(TEST_KEYMAP.lookup("ge").wait(), KeyWait::Ready);
+        assert_eq!(TEST_KEYMAP.lookup("x").wait(), KeyWait::NoMatch);
+    }
+}
@@ -5,17 +5,29 @@ use std::path::PathBuf;
 // import lines_editor_module lines_editor_module w/ these 2 lines:
 mod lines_editor_module;
 use lines_editor_module::{
-    LinesError, get_default_filepath, is_in_home_directory, lines_full_file_editor,
-    memo_mode_mini_editor_loop, print_help, prompt_for_filename, stack_format_it,
+    EXIT_UNSAVED_CHANGES_DISCARDED, InputScriptPaths, LineRangeFormat, LinesError, StartColumn,
+    bindiff_exit_status, get_default_filepath, is_in_home_directory, limits, lines_bindiff_command,
+    lines_full_file_editor, lines_print_range_command, lines_selfcheck_command,
+    memo_mode_mini_editor_loop, parse_file_location, parse_print_range, parse_start_column,
+    print_help, prompt_for_filename, set_auto_reload, set_final_newline_on_save, set_raw_keys,
+    set_screen_reader_output, set_scroll_off_rows, set_security_mode, set_session_encryption,
+    set_start_column, set_verbose_logging, stack_format_it, unsaved_changes_discarded,
+    write_lines_message_template,
 };
 
+#[cfg(debug_assertions)]
+use lines_editor_module::set_diagnostic_overlay;
+
 mod buttons_reversible_edit_changelog_module;
 mod toggle_comment_indent_module;
 
 // To make a smaller binary, you can remove source-it.
 /// "Source-It" allows build source code transparency: --source
 mod source_it_module;
-use source_it_module::{SourcedFile, handle_sourceit_command};
+use source_it_module::{
+    SourcedFile, handle_source_verify_command, handle_sourceit_archive_command,
+    handle_sourceit_command,
+};
 
 mod buffy_format_write_module;
 use buffy_format_write_module::{BuffyFormatArg, buffy_print, buffy_println};
@@ -23,10 +35,84 @@ use buffy_format_write_module::{BuffyFormatArg, buffy_print, buffy_println};
 // for 'ki' keyboard-event based input mode
 mod raw_terminal_x86_module;
 
+// CRC32 / SHA-256 for the 'sum' command
+mod checksum_module;
+
+// --record / --replay of editor input, for bug reproductions
+mod input_record_replay_module;
+
+// Test/debug-build I/O fault injection (no-op in release builds)
+mod fault_injection_module;
+
+// ChaCha20 passphrase encryption of session files (--secure-encrypt)
+mod session_encryption_module;
+
+// NFC / NFD conversion for the 'nfc' command and normalization-aware search
+mod unicode_normalization_module;
+
+// Arrow / Home / End / Page key escape sequences in +Enter input lines
+mod escape_sequence_module;
+
+// Multi-key command sequences (gg, dd, yw) as a keymap state machine
+mod keymap_module;
+
+// --raw-keys: single-keypress Normal/Visual commands via stty
+mod stty_key_input_module;
+
+// "Still working" line for long line counts and save-as copies
+mod progress_module;
+
+// archive/ backups of saved files: listing for the 'arc' preview
+mod archive_module;
+
+// Notes on lines ('note', 'notes'), kept in a sidecar under lines_data
+mod annotation_module;
+
+// .patch / .diff files: line kinds for colouring, hunk headers (']h', '[h')
+mod diff_review_module;
+
+// Pattern search and replace ('rx/', 'rxs/'): a small regex matcher
+mod regex_lite_module;
+
+// Termux (Android) memo paths, Redox scheme paths, portable ANSI subset
+mod platform_module;
+
+// SIGINT / SIGTERM / SIGHUP (and panics): restore the terminal, note and
+// keep the session
+mod signal_session_module;
+use signal_session_module::install_panic_session_hook;
+
+// UI text overrides (LINES_MESSAGES file), English compiled in
+mod message_catalog_module;
+use message_catalog_module::{MESSAGES_ENV_VAR, load_message_overrides};
+
+/// Git commit this binary was built from, "-dirty" if it had local
+/// changes (set by build.rs)
+const BUILD_COMMIT: &str = env!("LINES_BUILD_COMMIT");
+/// When this binary was built, UTC (build.rs; SOURCE_DATE_EPOCH if set)
+const BUILD_TIMESTAMP: &str = env!("LINES_BUILD_TIMESTAMP");
+/// Target triple this binary was built for (build.rs)
+const BUILD_TARGET: &str = env!("LINES_BUILD_TARGET");
+
+/// Name of the build metadata file written next to extracted source
+const BUILD_INFO_FILE_NAME: &str = "BUILD_INFO.txt";
+
+/// Version and build metadata, one item per line, as --version prints it
+fn build_info_text() -> String {
+    format!(
+        "Lines-Editor Version: {}\ncommit: {}\nbuilt:  {}\ntarget: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        BUILD_COMMIT,
+        BUILD_TIMESTAMP,
+        BUILD_TARGET
+    )
+}
+
 // To make a smaller binary, you can remove source-it.
 /// Source-It: Developer explicitly lists files to embed w/
 const SOURCE_FILES: &[SourcedFile] = &[
     SourcedFile::new("Cargo.toml", include_str!("../Cargo.toml")),
+    SourcedFile::new("build.rs", include_str!("../build.rs")),
     SourcedFile::new("src/main.rs", include_str!("main.rs")),
     SourcedFile::new("src/tests.rs", include_str!("tests.rs")),
     SourcedFile::new(
@@ -53,6 +139,55 @@ const SOURCE_FILES: &[SourcedFile] = &[
         "src/raw_terminal_x86_module.rs",
         include_str!("raw_terminal_x86_module.rs"),
     ),
+    SourcedFile::new("src/checksum_module.rs", include_str!("checksum_module.rs")),
+    SourcedFile::new(
+        "src/input_record_replay_module.rs",
+        include_str!("input_record_replay_module.rs"),
+    ),
+    SourcedFile::new(
+        "src/session_encryption_module.rs",
+        include_str!("session_encryption_module.rs"),
+    ),
+    SourcedFile::new(
+        "src/fault_injection_module.rs",
+        include_str!("fault_injection_module.rs"),
+    ),
+    SourcedFile::new(
+        "src/unicode_normalization_module.rs",
+        include_str!("unicode_normalization_module.rs"),
+    ),
+    SourcedFile::new(
+        "src/escape_sequence_module.rs",
+        include_str!("escape_sequence_module.rs"),
+    ),
+    SourcedFile::new("src/keymap_module.rs", include_str!("keymap_module.rs")),
+    SourcedFile::new(
+        "src/stty_key_input_module.rs",
+        include_str!("stty_key_input_module.rs"),
+    ),
+    SourcedFile::new(
+        "src/message_catalog_module.rs",
+        include_str!("message_catalog_module.rs"),
+    ),
+    SourcedFile::new("src/progress_module.rs", include_str!("progress_module.rs")),
+    SourcedFile::new("src/platform_module.rs", include_str!("platform_module.rs")),
+    SourcedFile::new("src/archive_module.rs", include_str!("archive_module.rs")),
+    SourcedFile::new(
+        "src/annotation_module.rs",
+        include_str!("annotation_module.rs"),
+    ),
+    SourcedFile::new(
+        "src/diff_review_module.rs",
+        include_str!("diff_review_module.rs"),
+    ),
+    SourcedFile::new(
+        "src/signal_session_module.rs",
+        include_str!("signal_session_module.rs"),
+    ),
+    SourcedFile::new(
+        "src/regex_lite_module.rs",
+        include_str!("regex_lite_module.rs"),
+    ),
     SourcedFile::new("src/tests.rs", include_str!("tests.rs")),
     // SourcedFile::new("src/lib.rs", include_str!("lib.rs")),
     SourcedFile::new("README.md", include_str!("../README.md")),
@@ -73,33 +208,94 @@ mod tests;
 /// # Fields
 /// * `file_path` - Optional path to file to edit
 /// * `starting_line` - Optional line number to jump to (from file:123 syntax)
+/// * `starting_column` - Optional character of that line (from file:123:45 syntax)
 /// * `session_path` - Optional path to existing session directory for crash recovery
-/// * `mode` - Special mode flags (help, version, source, append)
+/// * `bindiff_paths` - The two files to compare (from --bindiff a b)
+/// * `print_range` - File and inclusive line range (from --print file:A-B)
+/// * `print_numbers` - --print output starts each line with its number (--numbers)
+/// * `porcelain` - --print / --bindiff / --selfcheck write tab-separated records (--porcelain)
+/// * `source_destination` - Optional directory for --source extraction
+/// * `source_archive` - --source writes one .tar instead of a directory (--archive)
+/// * `source_verify_path` - Checkout to compare (from --source-verify dir)
+/// * `verbose` - Also log Info-level traces (--verbose)
+/// * `diagnostic` - Draw the cursor/window-map overlay (--diagnostic, debug builds)
+/// * `secure` - Start the editor in security mode (--secure)
+/// * `secure_encrypt` - Also encrypt session files under a passphrase (--secure-encrypt)
+/// * `final_newline` - Add a missing final newline when saving (--final-newline)
+/// * `scroll_off` - Rows kept visible above/below the cursor (--scroll-off N)
+/// * `start_column` - Where the cursor starts on its line (--start-column)
+/// * `raw_keys` - Normal/Visual commands act per keypress, no Enter (--raw-keys)
+/// * `screen_reader` - Plain sequential status text, no repaint (--screen-reader)
+/// * `auto_reload` - Reload a clean buffer when the file changes on disk (--auto-reload)
+/// * `input_script` - Editor input recording / replay files (--record, --replay)
+/// * `mode` - Special mode flags (help, version, source, append, bindiff, selfcheck)
 #[derive(Debug)]
 struct ParsedArgs {
     file_path: Option<PathBuf>,
     starting_line: Option<usize>,
+    starting_column: Option<usize>,
     session_path: Option<PathBuf>,
+    bindiff_paths: Option<(PathBuf, PathBuf)>,
+    print_range: Option<(PathBuf, usize, usize)>,
+    print_numbers: bool,
+    porcelain: bool,
+    source_destination: Option<PathBuf>,
+    source_archive: bool,
+    source_verify_path: Option<PathBuf>,
+    verbose: bool,
+    diagnostic: bool,
+    secure: bool,
+    secure_encrypt: bool,
+    final_newline: bool,
+    scroll_off: usize,
+    start_column: StartColumn,
+    raw_keys: bool,
+    screen_reader: bool,
+    auto_reload: bool,
+    input_script: InputScriptPaths,
     mode: ArgMode,
 }
 
 /// Special argument modes that don't start the editor
 #[derive(Debug, PartialEq)]
 enum ArgMode {
-    Normal,     // Start editor normally
-    Help,       // Print help and exit
-    Version,    // Print version and exit
-    Source,     // Extract source and exit, // To make a smaller binary, you can remove source-it.
+    Normal,           // Start editor normally
+    Help,             // Print help and exit
+    Version,          // Print version and exit
+    Source, // Extract source and exit, // To make a smaller binary, you can remove source-it.
     AppendMode, // Memo mode (append-only)
+    BinDiff, // Compare two files byte by byte, optionally open in hex mode
+    PrintRange, // Print lines A-B of a file to stdout
+    SourceVerify, // Compare a checkout against the embedded source
+    MessagesTemplate, // Print a LINES_MESSAGES override file to edit
+    SelfCheck, // Check data directory, session files, timestamps, rendering
 }
 
 /// Parses command line arguments into structured format
 ///
 /// # Purpose
 /// Processes raw command line arguments and extracts:
-/// - File path with optional :line_number suffix
+/// - File path with optional :line_number or :line_number:column suffix
 /// - --session flag with path argument
 /// - -a/--append flag for memo mode
+/// - --bindiff flag with two file path arguments
+/// - --print flag with a file:A-B argument, and --numbers
+/// - --porcelain flag (script-readable --print / --bindiff / --selfcheck output)
+/// - --source flag with optional destination directory, and --archive
+/// - --source-verify flag with checkout directory argument
+/// - --messages-template flag (print a UI text override file)
+/// - --selfcheck flag (pass/fail report on this install)
+/// - --verbose flag (Info-level log traces)
+/// - --diagnostic flag (debug-build overlay)
+/// - --secure flag (security mode: zero buffers after use)
+/// - --secure-encrypt flag (--secure plus encrypted session files)
+/// - --final-newline flag (POSIX final newline on save)
+/// - --scroll-off flag with a row count (j/k context margin)
+/// - --start-column flag with a column or "nonblank" (cursor on open)
+/// - --raw-keys flag (single-keypress Normal/Visual commands via stty)
+/// - --screen-reader flag (plain text output for braille / speech)
+/// - --auto-reload flag (reload when another program changes the file)
+/// - --record / --replay flags with a recording file argument
 /// - Special flags (--help, --version, --source)
 ///
 /// # Argument Patterns Supported
@@ -107,11 +303,28 @@ enum ArgMode {
 /// lines
 /// lines file.txt
 /// lines file.txt:123
+/// lines file.txt:123:45
 /// lines --session <path>
 /// lines --session <path> file.txt
 /// lines file.txt --session <path>
 /// lines file.txt:123 --session <path>
 /// lines -a file.txt
+/// lines --bindiff a.bin b.bin
+/// lines --print notes.txt:100-150 --numbers
+/// lines --print notes.txt:100-150 --porcelain
+/// lines --bindiff a.bin b.bin --porcelain
+/// lines --source
+/// lines --source /tmp/audit
+/// lines --source --archive
+/// lines --source-verify ./lines_editor
+/// lines --messages-template > my_messages.txt
+/// lines --selfcheck
+/// lines --selfcheck --porcelain
+/// lines --verbose notes.txt
+/// lines --scroll-off 3 notes.txt
+/// lines --start-column nonblank main.rs:120
+/// lines notes.txt --record bug.rec
+/// lines notes.txt --replay bug.rec
 /// lines --help
 /// ```
 ///
@@ -124,12 +337,40 @@ enum ArgMode {
 ///
 /// # Error Cases
 /// - `--session` flag without path argument
+/// - `--bindiff` flag without two path arguments
+/// - `--print` flag without a file:N or file:N-M argument
+/// - `--numbers` flag without `--print`
+/// - `--porcelain` flag without `--print`, `--bindiff` or `--selfcheck`
+/// - `--source-verify` flag without path argument
+/// - `--record` / `--replay` flag without file argument
+/// - `--scroll-off` flag without a row count (0-99)
+/// - `--start-column` flag without a column (1, 2, ...) or "nonblank"
+/// - `--archive` flag without `--source`
 /// - Unknown flags
 /// - Too many non-flag arguments
 fn parse_arguments(args: &[String]) -> Result<ParsedArgs, String> {
     let mut file_path: Option<PathBuf> = None;
     let mut starting_line: Option<usize> = None;
+    let mut starting_column: Option<usize> = None;
     let mut session_path: Option<PathBuf> = None;
+    let mut bindiff_paths: Option<(PathBuf, PathBuf)> = None;
+    let mut print_range: Option<(PathBuf, usize, usize)> = None;
+    let mut print_numbers = false;
+    let mut porcelain = false;
+    let mut source_destination: Option<PathBuf> = None;
+    let mut source_archive = false;
+    let mut source_verify_path: Option<PathBuf> = None;
+    let mut verbose = false;
+    let mut diagnostic = false;
+    let mut secure = false;
+    let mut secure_encrypt = false;
+    let mut final_newline = false;
+    let mut scroll_off: usize = 0;
+    let mut start_column = StartColumn::LineStart;
+    let mut raw_keys = false;
+    let mut screen_reader = false;
+    let mut auto_reload = false;
+    let mut input_script = InputScriptPaths::default();
     let mut mode = ArgMode::Normal;
 
     // Skip program name (args[0])
@@ -148,11 +389,132 @@ fn parse_arguments(args: &[String]) -> Result<ParsedArgs, String> {
                 mode = ArgMode::Version;
                 i += 1;
             }
+            "--messages-template" => {
+                mode = ArgMode::MessagesTemplate;
+                i += 1;
+            }
 
             // To make a smaller binary, you can remove source-it.
             "--source" | "--source_it" => {
                 mode = ArgMode::Source;
                 i += 1;
+                // Optional destination directory (any non-flag next argument)
+                if i < args.len() && !args[i].starts_with('-') {
+                    source_destination = Some(PathBuf::from(&args[i]));
+                    i += 1;
+                }
+            }
+
+            "--archive" => {
+                source_archive = true;
+                i += 1;
+                // Optional directory may also follow --archive
+                if source_destination.is_none() && i < args.len() && !args[i].starts_with('-') {
+                    source_destination = Some(PathBuf::from(&args[i]));
+                    i += 1;
+                }
+            }
+
+            "--source-verify" => {
+                if i + 1 >= args.len() {
+                    return Err("Error: --source-verify flag requires a directory".to_string());
+                }
+                mode = ArgMode::SourceVerify;
+                source_verify_path = Some(PathBuf::from(&args[i + 1]));
+                i += 2;
+            }
+
+            // Debug-build cursor/window-map overlay (full editor)
+            "--diagnostic" => {
+                diagnostic = true;
+                i += 1;
+            }
+
+            // Security mode: zero reused buffers after use (full editor)
+            "--secure" => {
+                secure = true;
+                i += 1;
+            }
+
+            // Security mode plus passphrase encryption of session files
+            "--secure-encrypt" => {
+                secure = true;
+                secure_encrypt = true;
+                i += 1;
+            }
+
+            // Saves add '\n' to a file that does not end with one
+            "--final-newline" => {
+                final_newline = true;
+                i += 1;
+            }
+
+            // Normal/Visual commands act on the keypress (no Enter)
+            "--raw-keys" => {
+                raw_keys = true;
+                i += 1;
+            }
+
+            // Plain status lines instead of full-screen repaints
+            "--screen-reader" => {
+                screen_reader = true;
+                i += 1;
+            }
+
+            // Clean buffer reloads when the file changes on disk
+            "--auto-reload" => {
+                auto_reload = true;
+                i += 1;
+            }
+
+            // Rows of context kept above/below the cursor on j/k
+            "--scroll-off" => {
+                scroll_off = match args.get(i + 1).and_then(|rows| rows.parse::<usize>().ok()) {
+                    Some(rows) if rows <= limits::SCROLL_OFF_MAX_ROWS => rows,
+                    _ => {
+                        return Err(format!(
+                            "Error: --scroll-off flag requires a row count (0-{})",
+                            limits::SCROLL_OFF_MAX_ROWS
+                        ));
+                    }
+                };
+                i += 2;
+            }
+
+            // Where the cursor starts on its line when the file opens
+            "--start-column" => {
+                start_column = match args.get(i + 1).and_then(|value| parse_start_column(value)) {
+                    Some(start_column) => start_column,
+                    None => {
+                        return Err(
+                            "Error: --start-column flag requires a column (1, 2, ...) or 'nonblank'"
+                                .to_string(),
+                        );
+                    }
+                };
+                i += 2;
+            }
+
+            // Info-level log traces (combines with any mode)
+            "--verbose" => {
+                verbose = true;
+                i += 1;
+            }
+
+            // Editor input recording / replay (full editor only)
+            "--record" => {
+                if i + 1 >= args.len() {
+                    return Err("Error: --record flag requires a file path".to_string());
+                }
+                input_script.record_path = Some(PathBuf::from(&args[i + 1]));
+                i += 2;
+            }
+            "--replay" => {
+                if i + 1 >= args.len() {
+                    return Err("Error: --replay flag requires a file path".to_string());
+                }
+                input_script.replay_path = Some(PathBuf::from(&args[i + 1]));
+                i += 2;
             }
 
             "-a" | "--append" => {
@@ -169,6 +531,43 @@ fn parse_arguments(args: &[String]) -> Result<ParsedArgs, String> {
                 session_path = Some(PathBuf::from(&args[i]));
                 i += 1;
             }
+            // Binary diff flag with two path arguments
+            "--bindiff" => {
+                if i + 2 >= args.len() {
+                    return Err("Error: --bindiff flag requires two file paths".to_string());
+                }
+                mode = ArgMode::BinDiff;
+                bindiff_paths = Some((PathBuf::from(&args[i + 1]), PathBuf::from(&args[i + 2])));
+                i += 3;
+            }
+            // Print a line range and exit
+            "--print" => {
+                print_range = match args.get(i + 1).and_then(|spec| parse_print_range(spec)) {
+                    Some(range) => Some(range),
+                    None => {
+                        return Err(
+                            "Error: --print requires FILE:N or FILE:N-M (lines from 1)".to_string()
+                        );
+                    }
+                };
+                mode = ArgMode::PrintRange;
+                i += 2;
+            }
+            // --print: line numbers before each line
+            "--numbers" => {
+                print_numbers = true;
+                i += 1;
+            }
+            // --print / --bindiff: stable tab-separated output for scripts
+            "--porcelain" => {
+                porcelain = true;
+                i += 1;
+            }
+            // Check this install can run, then exit
+            "--selfcheck" => {
+                mode = ArgMode::SelfCheck;
+                i += 1;
+            }
             // Unknown flag
             arg_str if arg_str.starts_with("--") || arg_str.starts_with('-') => {
                 return Err(stack_format_it(
@@ -183,34 +582,70 @@ fn parse_arguments(args: &[String]) -> Result<ParsedArgs, String> {
                     return Err("Error: Multiple file paths specified".to_string());
                 }
 
-                // Parse "filename:line" format
-                let (file_path_str, line_num) = if let Some(colon_pos) = arg.rfind(':') {
-                    let file_part = &arg[..colon_pos];
-                    let line_part = &arg[colon_pos + 1..];
-
-                    match line_part.parse::<usize>() {
-                        Ok(line) if line > 0 => (file_part.to_string(), Some(line)),
-                        _ => (arg.to_string(), None), // Invalid line, treat as filename
-                    }
-                } else {
-                    (arg.to_string(), None)
-                };
+                // Parse "filename:line" and "filename:line:column" formats
+                let (path, line_num, column) = parse_file_location(arg);
 
-                file_path = Some(PathBuf::from(file_path_str));
+                file_path = Some(path);
                 starting_line = line_num;
+                starting_column = column;
                 i += 1;
             }
         }
     }
 
+    if source_archive && mode != ArgMode::Source {
+        return Err("Error: --archive is only valid with --source".to_string());
+    }
+    if print_numbers && mode != ArgMode::PrintRange {
+        return Err("Error: --numbers is only valid with --print".to_string());
+    }
+    if porcelain
+        && mode != ArgMode::PrintRange
+        && mode != ArgMode::BinDiff
+        && mode != ArgMode::SelfCheck
+    {
+        return Err(
+            "Error: --porcelain is only valid with --print, --bindiff or --selfcheck".to_string(),
+        );
+    }
+
     Ok(ParsedArgs {
         file_path,
         starting_line,
+        starting_column,
         session_path,
+        bindiff_paths,
+        print_range,
+        print_numbers,
+        porcelain,
+        source_destination,
+        source_archive,
+        source_verify_path,
+        verbose,
+        diagnostic,
+        secure,
+        secure_encrypt,
+        final_newline,
+        scroll_off,
+        start_column,
+        raw_keys,
+        screen_reader,
+        auto_reload,
+        input_script,
         mode,
     })
 }
 
+/// Exit status of a full-editor run: an error as usual (1), otherwise 0,
+/// or EXIT_UNSAVED_CHANGES_DISCARDED when `q!` dropped unsaved changes
+fn finish_full_editor(result: Result<(), LinesError>) -> Result<(), LinesError> {
+    result?;
+    if unsaved_changes_discarded() {
+        std::process::exit(EXIT_UNSAVED_CHANGES_DISCARDED);
+    }
+    Ok(())
+}
+
 /// Main entry point - routes between memo mode and full editor mode
 ///
 /// # Purpose
@@ -221,12 +656,20 @@ fn parse_arguments(args: &[String]) -> Result<ParsedArgs, String> {
 /// lines                                    # Memo mode (if in home) or prompt
 /// lines file.txt                          # Full editor with file
 /// lines file.txt:123                      # Full editor, jump to line 123
+/// lines file.txt:123:45                   # Full editor, line 123, character 45
 /// lines --session ./sessions/20250103/    # Full editor with session recovery
 /// lines file.txt --session <path>         # Full editor with file and session
 /// lines -a file.txt                       # Memo mode (append-only)
+/// lines --bindiff a.bin b.bin             # Compare two files byte by byte
+/// lines --print notes.txt:100-150         # Print lines 100 to 150
 /// lines --help                            # Print help
-/// lines --version                         # Print version
+/// lines --version                         # Print version, commit, build date, target
 /// lines --source                          # Extract source code
+/// lines --source /tmp/audit               # Extract source code into a directory
+/// lines --source --archive                # Extract source code as one .tar file
+/// lines --source-verify ./lines_editor    # Compare a checkout to embedded source
+/// lines notes.txt --record bug.rec       # Full editor, input appended to bug.rec
+/// lines notes.txt --replay bug.rec       # Full editor, bug.rec input played first
 /// ```
 ///
 /// # Mode Selection Logic
@@ -240,10 +683,15 @@ fn parse_arguments(args: &[String]) -> Result<ParsedArgs, String> {
 /// - Absolute: `lines --session /full/path/to/sessions/20250103_143022 file.txt`
 ///
 /// # Exit Codes
-/// - 0: Success
+/// - 0: Success (saved, or nothing to save)
 /// - 1: General error
 /// - 2: Invalid arguments
+/// - 3: Quit with `q!`, unsaved changes discarded
+/// - 128+N: Ended by signal N (130 Ctrl-C, 143 SIGTERM); session kept
 fn main() -> Result<(), LinesError> {
+    // Last resort: even a panic leaves the session, described and resumable
+    install_panic_session_hook();
+
     let args: Vec<String> = std::env::args().collect();
 
     // Parse command line arguments
@@ -255,10 +703,33 @@ fn main() -> Result<(), LinesError> {
             eprintln!("Usage: lines [OPTIONS] [FILE[:LINE]]");
             eprintln!("Options:");
             eprintln!("  -h, --help              Print help information");
-            eprintln!("  -v, --version           Print version information");
-            eprintln!("  --source                Extract source code");
+            eprintln!("  -v, --version           Print version, git commit, build date, target");
+            eprintln!("  --source [DIR]          Extract source code (DIR: missing or empty)");
+            eprintln!("  --source --archive [DIR]  Write source as lines_source_<version>.tar");
+            eprintln!("  --source-verify DIR     Compare a checkout to the embedded source");
+            eprintln!("  --messages-template     Print a LINES_MESSAGES text override file");
+            eprintln!("  --selfcheck             Check data dir, sessions, timestamps, rendering");
             eprintln!("  -a, --append FILE       Memo mode (append-only)");
             eprintln!("  -s, --session PATH      Use existing session directory");
+            eprintln!("  --verbose               Also log Info-level traces");
+            eprintln!("  --diagnostic            Cursor/window-map overlay (debug builds)");
+            eprintln!("  --secure                Zero buffers and session files after use");
+            eprintln!(
+                "  --secure-encrypt        --secure, plus passphrase-encrypted session files"
+            );
+            eprintln!("  --final-newline         Saving adds a missing final newline");
+            eprintln!("  --scroll-off N          Keep N rows visible above/below the cursor");
+            eprintln!("  --start-column N|nonblank  Cursor column when the file opens");
+            eprintln!("  --raw-keys              Commands act on the keypress (no Enter)");
+            eprintln!("  --screen-reader         Plain text output, no screen repaint");
+            eprintln!("  --auto-reload           Reload when the file changes (no unsaved edits)");
+            eprintln!("  --record FILE           Append all editor input to FILE");
+            eprintln!("  --replay FILE           Play a --record FILE back, then continue");
+            eprintln!(
+                "  --bindiff FILE FILE     Compare two files byte by byte (exit 0/1/2 as cmp)"
+            );
+            eprintln!("  --print FILE:N-M        Print lines N to M and exit (--numbers)");
+            eprintln!("  --porcelain             --print / --bindiff / --selfcheck: tab records");
             eprintln!();
             eprintln!("Examples:");
             eprintln!("  lines                               # Quick-Edit: new Documents/ file");
@@ -267,10 +738,51 @@ fn main() -> Result<(), LinesError> {
             eprintln!("  lines -a notes.txt                  # Quick-Edit: Memo-Append Mode");
             eprintln!("  lines --session ./sessions/2025../  # Recover session");
             eprintln!("  lines notes.txt --session <path>    # Edit with session");
+            eprintln!("  lines --bindiff old.bin new.bin     # List differing byte ranges");
             std::process::exit(2);
         }
     };
 
+    // Info-level traces only when asked for (default: Warn and Error)
+    set_verbose_logging(parsed.verbose);
+
+    // Security mode for every editor state created from here on
+    set_security_mode(parsed.secure);
+    set_session_encryption(parsed.secure_encrypt);
+
+    // POSIX final newline on save (default: keep the file's ending as-is)
+    set_final_newline_on_save(parsed.final_newline);
+
+    // j/k context margin (default 0: scroll only at the window edge)
+    set_scroll_off_rows(parsed.scroll_off);
+
+    // Cursor column on open (default: start of the line)
+    set_start_column(parsed.start_column);
+
+    // Keypress commands (default: the "+Enter" system)
+    set_raw_keys(parsed.raw_keys);
+
+    // Plain sequential output for braille / speech (default: full screen)
+    set_screen_reader_output(parsed.screen_reader);
+
+    // Outside changes to the file: reload a clean buffer (default: offer `rl`)
+    set_auto_reload(parsed.auto_reload);
+
+    // Replacement UI text, before anything is shown (default: English)
+    if let Some(messages_path) = env::var_os(MESSAGES_ENV_VAR)
+        && let Err(e) = load_message_overrides(std::path::Path::new(&messages_path))
+    {
+        eprintln!("Ignoring {}: {}", MESSAGES_ENV_VAR, e);
+    }
+
+    // Diagnostic overlay: compiled into debug builds only
+    #[cfg(debug_assertions)]
+    set_diagnostic_overlay(parsed.diagnostic);
+    #[cfg(not(debug_assertions))]
+    if parsed.diagnostic {
+        eprintln!("Note: --diagnostic is only available in debug builds; ignored");
+    }
+
     // Check if we're in home directory
     let in_home = is_in_home_directory()?;
 
@@ -281,21 +793,77 @@ fn main() -> Result<(), LinesError> {
             return Ok(());
         }
         ArgMode::Version => {
-            buffy_print(
-                "Lines-Editor Version: {}",
-                &[BuffyFormatArg::Str(env!("CARGO_PKG_VERSION"))],
-            )?;
-
+            print!("{}", build_info_text());
             return Ok(());
         }
         ArgMode::Source => {
             // To make a smaller binary, you can remove source-it.
-            match handle_sourceit_command("lines_editor", None, SOURCE_FILES) {
-                Ok(path) => buffy_print("Source extracted to: {}", &[BuffyFormatArg::Path(&path)])?,
-                Err(e) => eprintln!("Failed to extract source: {}", e),
+            let extracted = if parsed.source_archive {
+                // One file: lines_source_<version>.tar (DIR is where it goes)
+                handle_sourceit_archive_command(
+                    concat!("lines_source_", env!("CARGO_PKG_VERSION")),
+                    parsed.source_destination.as_deref(),
+                    SOURCE_FILES,
+                )
+            } else {
+                handle_sourceit_command(
+                    "lines_editor",
+                    parsed.source_destination.as_deref(),
+                    SOURCE_FILES,
+                )
+            };
+            match extracted {
+                Ok(path) => {
+                    buffy_print("Source extracted to: {}", &[BuffyFormatArg::Path(&path)])?;
+                    // Which binary the source came from; beside the tree,
+                    // not in it, so MANIFEST and --source-verify are unchanged
+                    if path.is_dir()
+                        && let Err(e) =
+                            std::fs::write(path.join(BUILD_INFO_FILE_NAME), build_info_text())
+                    {
+                        eprintln!("Could not write {}: {}", BUILD_INFO_FILE_NAME, e);
+                    }
+                    println!();
+                    print!("{}", build_info_text());
+                }
+                Err(e) => {
+                    eprintln!("Failed to extract source: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return Ok(());
+        }
+        ArgMode::MessagesTemplate => {
+            let mut stdout = std::io::stdout().lock();
+            if let Err(e) = write_lines_message_template(&mut stdout) {
+                eprintln!("Failed to write the message template: {}", e);
+                std::process::exit(1);
             }
             return Ok(());
         }
+        ArgMode::SourceVerify => {
+            if let Some(tree_root) = parsed.source_verify_path {
+                match handle_source_verify_command(&tree_root, SOURCE_FILES) {
+                    Ok(true) => return Ok(()),
+                    Ok(false) => std::process::exit(1),
+                    Err(e) => {
+                        eprintln!("Failed to verify source: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                eprintln!("Error: --source-verify flag requires a directory");
+                std::process::exit(2);
+            }
+        }
+        ArgMode::SelfCheck => match lines_selfcheck_command(parsed.porcelain) {
+            Ok(true) => return Ok(()),
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("Self-check could not run: {}", e);
+                std::process::exit(1);
+            }
+        },
         ArgMode::AppendMode => {
             // Memo mode (append-only) - requires file path
             if let Some(file_path) = parsed.file_path {
@@ -310,6 +878,41 @@ fn main() -> Result<(), LinesError> {
                 std::process::exit(2);
             }
         }
+        ArgMode::BinDiff => {
+            if let Some((file_a_path, file_b_path)) = parsed.bindiff_paths {
+                // As cmp: 0 same, 1 different, 2 error
+                let outcome = lines_bindiff_command(&file_a_path, &file_b_path, parsed.porcelain);
+                if let Err(e) = &outcome {
+                    eprintln!("Error: {}", e);
+                }
+                match bindiff_exit_status(&outcome) {
+                    0 => return Ok(()),
+                    status => std::process::exit(status),
+                }
+            } else {
+                eprintln!("Error: --bindiff flag requires two file paths");
+                std::process::exit(2);
+            }
+        }
+        ArgMode::PrintRange => {
+            if let Some((file_path, first, last)) = parsed.print_range {
+                let format = if parsed.porcelain {
+                    LineRangeFormat::Porcelain
+                } else if parsed.print_numbers {
+                    LineRangeFormat::Numbered
+                } else {
+                    LineRangeFormat::Plain
+                };
+                if let Err(e) = lines_print_range_command(&file_path, first, last, format) {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+                return Ok(());
+            } else {
+                eprintln!("Error: --print requires FILE:N or FILE:N-M");
+                std::process::exit(2);
+            }
+        }
         ArgMode::Normal => {
             // Continue to normal editor mode logic below
         }
@@ -339,11 +942,20 @@ fn main() -> Result<(), LinesError> {
                 pub fn lines_full_file_editor(
                     original_file_path: Option<PathBuf>,
                     starting_line: Option<usize>,
+                    starting_column: Option<usize>,
                     use_this_session: Option<PathBuf>,
                     state_persists: bool,
+                    input_script: &InputScriptPaths,
                 ) -> Result<()> {
                 */
-                lines_full_file_editor(Some(original_file_path), None, parsed.session_path, false)
+                finish_full_editor(lines_full_file_editor(
+                    Some(original_file_path),
+                    None,
+                    None,
+                    parsed.session_path,
+                    false,
+                    &parsed.input_script,
+                ))
             }
         }
         Some(file_path) => {
@@ -369,17 +981,21 @@ fn main() -> Result<(), LinesError> {
                 pub fn lines_full_file_editor(
                     original_file_path: Option<PathBuf>,
                     starting_line: Option<usize>,
+                    starting_column: Option<usize>,
                     use_this_session: Option<PathBuf>,
                     state_persists: bool,
+                    input_script: &InputScriptPaths,
                 ) -> Result<()> {
                 */
                 // Full editor mode with file
-                lines_full_file_editor(
+                finish_full_editor(lines_full_file_editor(
                     Some(file_path),
                     parsed.starting_line,
+                    parsed.starting_column,
                     parsed.session_path,
                     false,
-                )
+                    &parsed.input_script,
+                ))
             }
         }
     }
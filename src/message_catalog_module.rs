@@ -0,0 +1,357 @@
+//! message_catalog_module.rs - Translatable / renameable UI strings
+//!
+//! ## Project Context
+//! Lines' user-facing text (info-bar messages, the help menu and help
+//! pages) is English, compiled in. This module lets a plain text file
+//! replace any of those strings, for a translation or for different
+//! terminology, without a rebuild:
+//! ```text
+//! LINES_MESSAGES=~/lines_de.txt lines notes.txt
+//! lines --messages-template > lines_de.txt      (then edit the right column)
+//! ```
+//!
+//! ## File format
+//! One message per line: the English text, one TAB, the replacement.
+//! Blank lines and lines starting with '#' are skipped. An English text
+//! not in the file (or no file at all) is shown as compiled in.
+//! ```text
+//! # lines message overrides
+//! *clipboard empty*<TAB>*Zwischenablage leer*
+//! ```
+//!
+//! ## The catalog
+//! The English text itself is the key, so every fixed message is
+//! overridable where it is used. CATALOG_MESSAGES lists the info-bar
+//! messages for the template; a test checks it against the source.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Environment variable naming the message override file
+pub const MESSAGES_ENV_VAR: &str = "LINES_MESSAGES";
+
+/// Largest override file read, in bytes
+const MAX_OVERRIDE_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Most overrides accepted from one file
+const MAX_OVERRIDE_ENTRIES: usize = 4096;
+
+/// Overrides loaded at startup (English text -> replacement)
+static MESSAGE_OVERRIDES: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Fixed info-bar messages, as compiled in (English)
+pub const CATALOG_MESSAGES: &[&str] = &[
+    "(Added a Byte)",
+    "*clear failed*",
+    "*clipboard empty*",
+    "*input too long*",
+    "*insert fail*",
+    "*insert failed*",
+    "*no input*",
+    "*path resolution failed*",
+    "^clipboard cleared^",
+    "Already at end of file",
+    "Already at start of file",
+    "already NFC",
+    "archive is read only; q back",
+    "archive preview failed",
+    "arcr: show one with arc first",
+    "At end of file",
+    "At start of file",
+    "back to file",
+    "bsdn Redo clear failed",
+    "Byte written",
+    "Cannot determine file directory",
+    "changed on disk; unsaved here",
+    "cannot find line end",
+    "cannot get cursor position",
+    "cannot get cwd",
+    "cannot insert file into itself",
+    "cannot open file",
+    "cannot read file",
+    "cannot read line",
+    "cannot seek to cursor",
+    "cannot seek to line",
+    "checksum copy failed",
+    "checksum failed",
+    "clipboard read failed",
+    "Column number too long",
+    "Columns start at 1",
+    "config error",
+    "Config error",
+    "Copy failed",
+    "cursor error",
+    "cursor position error",
+    "data dir over LINES_DATA_QUOTA",
+    "data inspector off",
+    "data inspector on",
+    "delete failed",
+    "display error",
+    "display update failed",
+    "Edit failed",
+    "empty item",
+    "End of file",
+    "end of line",
+    "err:nO uNdo",
+    "Error: Cannot read file size",
+    "Error: No file open",
+    "ESC>exit DEL>bckspc ki>key-ins",
+    "Failed to Insert byte",
+    "Failed to Insert bytes",
+    "Failed to Remove byte",
+    "Failed to Remove bytes",
+    "File already exists.",
+    "File is empty",
+    "file changed: rl reloads",
+    "file locked; not saved",
+    "file not found",
+    "file too large",
+    "Filename too long",
+    "Fill failed",
+    "fill: f{start}-{end} NN",
+    "gh cursor position unavailable",
+    "gl cursor pos. unavailable",
+    "hex dual view off",
+    "hex dual view on",
+    "hex dump failed",
+    "hex dump: xxd {path}",
+    "hex patch: xxr {path}",
+    "hex position not synced",
+    "insert pos error",
+    "insert: NN NN..-i (max 27)",
+    "inserted (undo disabled)",
+    "invalid cursor",
+    "invalid cursor position",
+    "Invalid filename (non-UTF8)",
+    "invalid input",
+    "invalid range",
+    "invalid rank",
+    "item cleared",
+    "key not used in hex mode",
+    "ki unavailable (no tty)",
+    "ki: Esc>normal  type ascii",
+    "line bounds error",
+    "line ends before that column",
+    "Line not found",
+    "Line number too long",
+    "Line numbers start at 1",
+    "line too long",
+    "long word limit",
+    "New filename same as original (use 's' to save)",
+    "Next line",
+    "nfc done, undo disabled",
+    "nfc: empty selection",
+    "nfc: result too long",
+    "nfc: selection is not UTF-8",
+    "nfc: selection too long (max 4096 bytes)",
+    "no archives for this file",
+    "No byte to remove",
+    "no edit to repeat",
+    "no f/t to repeat",
+    "no fuzzy match",
+    "No file open",
+    "No file open to save as",
+    "no line here",
+    "no match",
+    "no match in selection",
+    "no next hunk",
+    "no note on this line",
+    "no notes in secure mode",
+    "no position here",
+    "no previous hunk",
+    "no screenshots in secure mode",
+    "no such search in hist",
+    "no search to repeat",
+    "no session directory",
+    "no such note",
+    "no target file",
+    "no text here",
+    "no word here",
+    "not a file",
+    "nothing to replace",
+    "note failed",
+    "note removed",
+    "notes unreadable",
+    "nothing to yank",
+    "paste ended early",
+    "pasted",
+    "pasty mode iteration limit",
+    "Patch failed",
+    "patch rejected: bad dump",
+    "path error",
+    "Position # too long",
+    "Previous line",
+    "range bounds error",
+    "range past end of file",
+    "raw keys unavailable; +Enter",
+    "read error",
+    "read error chunk",
+    "Read failed",
+    "read-copy failed; view only",
+    "read-copy made",
+    "redo clear failed",
+    "reload failed",
+    "reloaded from disk",
+    "restore failed",
+    "Redo clear failed",
+    "Redo-clear failed",
+    "Removed Byte",
+    "ruler off",
+    "rx: * + ? need something before",
+    "rx: [ without ]",
+    "rx: \\ at the end",
+    "rx: empty pattern",
+    "rx: pattern too long (max 64)",
+    "rx: too complex, gave up",
+    "ruler: no guide column",
+    "Replace failed",
+    "replace: s/NN../NN../",
+    "s: old/new too long (max 64)",
+    "Saved",
+    "screenshot failed",
+    "screenshot in lines_data",
+    "Search failed",
+    "search text too long",
+    "search: /NN NN.. (max 27)",
+    "select: move, then y/d/f NN",
+    "selection cleared",
+    "shift error",
+    "Start of file",
+    "start of line",
+    "that note's line changed",
+    "too few rows for the ruler",
+    "too many matches to undo",
+    "too many notes (max 1000)",
+    "undo disabled",
+    "Undo log failed",
+    "undo log failed",
+    "undo log incomplete",
+    "Undo log path fail",
+    "undo log path failed",
+    "unknown key sequence",
+    "unsaved changes; not reloaded",
+    "unsaved changes; not restored",
+    "unsaved: wq saves, q! discards",
+    "Use: arc, arc{n} (1 = newest)",
+    "Use: cw ce cb c$ c0 cc cf{char}",
+    "Use: f{char} or t{char}",
+    "Use: gb{offset} or gb0x{hex}",
+    "Use: hist, hist{n} (Normal)",
+    "Use: ruler, ruler{col}, ruler0",
+    "Use: sa FILENAME",
+    "Use: so{rows}, e.g. so3 (so0 = off)",
+    "view: read-copy made at first edit",
+    "Write failed",
+    "yank: y | y{start}-{end}",
+    "yanked word",
+    "|o| SaveAs faiL |o|",
+];
+
+/// The text to show for `english`: its override, if one was loaded
+pub fn ui_message(english: &str) -> &str {
+    MESSAGE_OVERRIDES
+        .get()
+        .and_then(|overrides| overrides.get(english))
+        .map_or(english, String::as_str)
+}
+
+/// Reads an override file and makes its messages the ones shown
+///
+/// Call once at startup, before any message is shown; a later call is
+/// ignored (the first file loaded stays in use).
+///
+/// # Returns
+/// The number of overrides, or a short message naming the problem.
+pub fn load_message_overrides(path: &Path) -> Result<usize, String> {
+    let mut text = String::new();
+    File::open(path)
+        .and_then(|file| {
+            file.take(MAX_OVERRIDE_FILE_BYTES + 1)
+                .read_to_string(&mut text)
+        })
+        .map_err(|error| format!("cannot read {}: {}", path.display(), error))?;
+    if text.len() as u64 > MAX_OVERRIDE_FILE_BYTES {
+        return Err(format!("{} is over 1 MiB", path.display()));
+    }
+
+    let overrides = parse_message_overrides(&text)?;
+    let count = overrides.len();
+    let _ = MESSAGE_OVERRIDES.set(overrides);
+    Ok(count)
+}
+
+/// Parses override file text (see the module docs for the format)
+pub fn parse_message_overrides(text: &str) -> Result<HashMap<String, String>, String> {
+    let mut overrides = HashMap::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (english, replacement) = line
+            .split_once('\t')
+            .ok_or_else(|| format!("line {}: no TAB between message and replacement", index + 1))?;
+        if english.is_empty() || replacement.is_empty() {
+            return Err(format!("line {}: empty message or replacement", index + 1));
+        }
+        if overrides.len() >= MAX_OVERRIDE_ENTRIES {
+            return Err(format!("more than {} messages", MAX_OVERRIDE_ENTRIES));
+        }
+        overrides.insert(english.to_string(), replacement.to_string());
+    }
+    Ok(overrides)
+}
+
+/// Writes an override file listing every catalog message and `extra_lines`
+/// (help text) unchanged, ready to edit
+pub fn write_message_template<W: Write>(out: &mut W, extra_lines: &[&str]) -> io::Result<()> {
+    writeln!(out, "# lines message overrides: English<TAB>replacement")?;
+    writeln!(out, "# Use with: {}=this_file lines ...", MESSAGES_ENV_VAR)?;
+    writeln!(out, "# Delete the lines you do not change.")?;
+    for english in CATALOG_MESSAGES.iter().chain(extra_lines) {
+        if !english.trim().is_empty() && !english.contains('\t') {
+            writeln!(out, "{}\t{}", english, english)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod message_catalog_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_message_overrides() {
+        let overrides = parse_message_overrides(
+            "# comment\n\n*clipboard empty*\t*leer*\r\nSaved\tGespeichert\n",
+        )
+        .unwrap();
+        assert_eq!(overrides.len(), 2);
+        assert_eq!(overrides["*clipboard empty*"], "*leer*");
+        assert_eq!(overrides["Saved"], "Gespeichert");
+
+        assert!(parse_message_overrides("Saved Gespeichert\n").is_err());
+        assert!(parse_message_overrides("Saved\t\n").is_err());
+    }
+
+    #[test]
+    fn test_template_round_trips() {
+        let mut template = Vec::new();
+        write_message_template(&mut template, &["  HELP LINE", ""]).unwrap();
+        let overrides = parse_message_overrides(std::str::from_utf8(&template).unwrap()).unwrap();
+        assert_eq!(overrides.len(), CATALOG_MESSAGES.len() + 1);
+        assert_eq!(overrides["  HELP LINE"], "  HELP LINE");
+    }
+
+    #[test]
+    fn test_message_without_overrides_is_english() {
+        assert_eq!(
+            ui_message("no such message loaded"),
+            "no such message loaded"
+        );
+    }
+}
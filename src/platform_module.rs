@@ -0,0 +1,294 @@
+//! platform_module.rs - Termux (Android) and Redox differences
+//!
+//! ## Project Context
+//! Lines assumes a Unix home directory: memos go in
+//! `~/Documents/lines_editor/`, and starting in `~` means memo mode.
+//! Two places it runs differ from that:
+//! ```text
+//! Termux    HOME is /data/data/com.termux/files/home, private to the
+//!           app. Files the user can see in Android's Files app and
+//!           other apps live in shared storage, which Termux links as
+//!           ~/storage/shared after `termux-setup-storage`.
+//! Redox     Paths are scheme paths; older releases canonicalize to
+//!           "file:/home/user/..." instead of "/home/user/...".
+//! ```
+//! Termux is found at run time (a Linux x86_64 build runs there
+//! unchanged, e.g. on Chromebooks); Redox only by target.
+//!
+//! ## What changes
+//! - Termux with shared storage set up: memos go in
+//!   `~/storage/shared/Documents/lines_editor/`, and starting in the
+//!   shared storage root also means memo mode. Without it, as elsewhere.
+//! - Redox: a "file:" scheme is dropped from canonical paths before they
+//!   are compared or shown.
+//!
+//! ## Terminal output
+//! The screen is drawn with a small ANSI subset, PORTABLE_CSI_FINALS and
+//! PORTABLE_SGR_CODES, that Termux's terminal and Redox's console both
+//! implement. A test reads the drawing modules' source and fails on any
+//! escape sequence outside it, so a new one is a deliberate choice.
+//! Bracketed paste mode (escape_sequence_module) is also switched; a
+//! terminal without it ignores the request.
+//! (Italic, dim and underline may show as plain text on a console that
+//! lacks them; nothing depends on them.)
+//!
+//! ## Not covered
+//! The raw-syscall modules (raw_terminal_x86_module, signal handling) are
+//! Linux x86_64 only; a native Android or Redox build still needs those
+//! ported or left out.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Where Termux links Android shared storage, relative to HOME
+pub const TERMUX_SHARED_STORAGE: &str = "storage/shared";
+
+/// Part of Termux's PREFIX (`/data/data/com.termux/files/usr`)
+const TERMUX_PACKAGE_NAME: &str = "com.termux";
+
+#[cfg(test)]
+/// CSI final bytes the screen drawing uses: cursor position, erase
+/// display, erase line, style
+pub const PORTABLE_CSI_FINALS: &[u8] = b"HJKm";
+
+#[cfg(test)]
+/// SGR parameters the screen drawing uses
+pub const PORTABLE_SGR_CODES: &[u8] = &[
+    0, 1, 2, 3, 4, 30, 31, 32, 33, 34, 35, 36, 37, 40, 41, 42, 43, 44, 45, 46, 47, 97,
+];
+
+/// The kind of system lines is running on, as far as it matters here
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    /// Linux, macOS, the BSDs: home directory conventions apply as is
+    Unix,
+    /// Termux on Android
+    Termux,
+    /// Redox OS
+    Redox,
+}
+
+/// Decides the platform from the target and Termux's environment
+///
+/// Termux sets `TERMUX_VERSION` and a PREFIX inside its package
+/// directory; either one is enough.
+pub fn platform_from(
+    is_redox: bool,
+    prefix: Option<&OsStr>,
+    termux_version: Option<&OsStr>,
+) -> Platform {
+    if is_redox {
+        return Platform::Redox;
+    }
+    let termux_prefix =
+        prefix.is_some_and(|prefix| prefix.to_string_lossy().contains(TERMUX_PACKAGE_NAME));
+    let termux_version = termux_version.is_some_and(|version| !version.is_empty());
+    if termux_prefix || termux_version {
+        Platform::Termux
+    } else {
+        Platform::Unix
+    }
+}
+
+/// The platform of this process, decided once
+pub fn current_platform() -> Platform {
+    static PLATFORM: OnceLock<Platform> = OnceLock::new();
+    *PLATFORM.get_or_init(|| {
+        platform_from(
+            cfg!(target_os = "redox"),
+            std::env::var_os("PREFIX").as_deref(),
+            std::env::var_os("TERMUX_VERSION").as_deref(),
+        )
+    })
+}
+
+/// Directory that holds the `lines_editor/` memo directory
+///
+/// `home/storage/shared/Documents` on Termux once shared storage is set
+/// up, so memos can be opened from other Android apps; `home/Documents`
+/// otherwise.
+pub fn memo_documents_dir_from(platform: Platform, home: &Path, shared_storage: bool) -> PathBuf {
+    if platform == Platform::Termux && shared_storage {
+        home.join(TERMUX_SHARED_STORAGE).join("Documents")
+    } else {
+        home.join("Documents")
+    }
+}
+
+/// memo_documents_dir_from for this process
+pub fn memo_documents_dir(home: &Path) -> PathBuf {
+    let platform = current_platform();
+    let shared_storage = platform == Platform::Termux && home.join(TERMUX_SHARED_STORAGE).is_dir();
+    memo_documents_dir_from(platform, home, shared_storage)
+}
+
+/// Besides `home` itself, the directory where starting lines also
+/// means memo mode: Termux's shared storage root
+pub fn memo_home_alias(home: &Path) -> Option<PathBuf> {
+    (current_platform() == Platform::Termux).then(|| home.join(TERMUX_SHARED_STORAGE))
+}
+
+/// "file:/home/user" -> "/home/user"; other paths unchanged
+pub fn strip_file_scheme(path: &Path) -> PathBuf {
+    let text = path.as_os_str().to_string_lossy();
+    match text.strip_prefix("file:") {
+        Some(rest) if rest.starts_with('/') => PathBuf::from(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// A canonical path in the form lines compares and shows: on Redox
+/// without a "file:" scheme, elsewhere unchanged
+pub fn platform_path(path: PathBuf) -> PathBuf {
+    if current_platform() == Platform::Redox {
+        strip_file_scheme(&path)
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+/// Whether one escape sequence (from ESC up to and including its final
+/// byte) is in the portable subset
+pub fn is_portable_escape(sequence: &[u8]) -> bool {
+    let Some(body) = sequence.strip_prefix(b"\x1b[") else {
+        return false;
+    };
+    let Some((&final_byte, parameters)) = body.split_last() else {
+        return false;
+    };
+    if !PORTABLE_CSI_FINALS.contains(&final_byte) {
+        return false;
+    }
+    let Ok(parameters) = std::str::from_utf8(parameters) else {
+        return false;
+    };
+    match final_byte {
+        b'm' => parameters.split(';').all(|code| {
+            code.parse::<u8>()
+                .is_ok_and(|code| PORTABLE_SGR_CODES.contains(&code))
+        }),
+        // "\x1b[H", "\x1b[12;3H", "\x1b[{};{}H" (filled in at run time)
+        b'H' => parameters.split(';').all(|row_or_column| {
+            matches!(row_or_column, "" | "{}") || row_or_column.parse::<u16>().is_ok()
+        }),
+        _ => matches!(parameters, "" | "0" | "1" | "2"),
+    }
+}
+
+#[cfg(test)]
+mod platform_tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_from_environment() {
+        let termux_prefix = Some(OsStr::new("/data/data/com.termux/files/usr"));
+        assert_eq!(platform_from(false, None, None), Platform::Unix);
+        assert_eq!(
+            platform_from(false, Some(OsStr::new("/usr/local")), Some(OsStr::new(""))),
+            Platform::Unix
+        );
+        assert_eq!(platform_from(false, termux_prefix, None), Platform::Termux);
+        assert_eq!(
+            platform_from(false, None, Some(OsStr::new("0.118.0"))),
+            Platform::Termux
+        );
+        assert_eq!(platform_from(true, termux_prefix, None), Platform::Redox);
+    }
+
+    #[test]
+    fn test_memo_documents_dir() {
+        let home = Path::new("/data/data/com.termux/files/home");
+        assert_eq!(
+            memo_documents_dir_from(Platform::Termux, home, true),
+            home.join("storage/shared/Documents")
+        );
+        assert_eq!(
+            memo_documents_dir_from(Platform::Termux, home, false),
+            home.join("Documents")
+        );
+        assert_eq!(
+            memo_documents_dir_from(Platform::Unix, Path::new("/home/a"), true),
+            PathBuf::from("/home/a/Documents")
+        );
+    }
+
+    #[test]
+    fn test_strip_file_scheme() {
+        assert_eq!(
+            strip_file_scheme(Path::new("file:/home/user/notes.txt")),
+            PathBuf::from("/home/user/notes.txt")
+        );
+        assert_eq!(
+            strip_file_scheme(Path::new("/home/user")),
+            PathBuf::from("/home/user")
+        );
+        assert_eq!(
+            strip_file_scheme(Path::new("file:notes")),
+            PathBuf::from("file:notes")
+        );
+    }
+
+    #[test]
+    fn test_is_portable_escape() {
+        assert!(is_portable_escape(b"\x1b[0m"));
+        assert!(is_portable_escape(b"\x1b[1;31m"));
+        assert!(is_portable_escape(b"\x1b[2J"));
+        assert!(is_portable_escape(b"\x1b[2K"));
+        assert!(is_portable_escape(b"\x1b[1;1H"));
+        assert!(is_portable_escape(b"\x1b[{};{}H"));
+        assert!(!is_portable_escape(b"\x1b[38;5;208m"));
+        assert!(!is_portable_escape(b"\x1b[5m"));
+        assert!(!is_portable_escape(b"\x1b[?1049h"));
+        assert!(!is_portable_escape(b"\x1bOA"));
+    }
+
+    /// Every escape sequence written by the drawing code is in the subset
+    #[test]
+    fn test_drawing_code_uses_portable_escapes() {
+        const DRAWING_SOURCES: [(&str, &str); 3] = [
+            (
+                "lines_editor_module.rs",
+                include_str!("lines_editor_module.rs"),
+            ),
+            (
+                "buffy_format_write_module.rs",
+                include_str!("buffy_format_write_module.rs"),
+            ),
+            ("progress_module.rs", include_str!("progress_module.rs")),
+        ];
+
+        for (file_name, source) in DRAWING_SOURCES {
+            for (line_index, line) in source.lines().enumerate() {
+                let code = line.trim_start();
+                // Comments, and tables documenting codes, are not output
+                if code.starts_with("//") || !code.contains('"') {
+                    continue;
+                }
+                for (start, _) in code.match_indices("\\x1b[") {
+                    let rest = &code.as_bytes()[start + 5..];
+                    let Some(final_offset) = rest
+                        .iter()
+                        .position(|&byte| byte.is_ascii_alphabetic() || byte == b'~')
+                    else {
+                        continue;
+                    };
+                    let mut sequence = b"\x1b[".to_vec();
+                    sequence.extend_from_slice(&rest[..=final_offset]);
+                    // Keys read from the terminal (arrows) are not output
+                    if matches!(sequence.last(), Some(b'A' | b'B' | b'C' | b'D' | b'~')) {
+                        continue;
+                    }
+                    assert!(
+                        is_portable_escape(&sequence),
+                        "{}:{}: {} is outside the portable ANSI subset",
+                        file_name,
+                        line_index + 1,
+                        String::from_utf8_lossy(&sequence)
+                    );
+                }
+            }
+        }
+    }
+}
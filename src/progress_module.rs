@@ -0,0 +1,189 @@
+//! progress_module.rs - "Still working" line for long file operations
+//!
+//! ## Project Context
+//! Most commands finish before the next redraw could matter. A few stream a
+//! whole file: counting lines for `ge` or a goto past the window, copying
+//! for save-as. On a multi-gigabyte file or a slow network filesystem that
+//! can take long enough to look like a hang. A ProgressMeter prints one
+//! line under the info bar while such an operation runs:
+//! ```text
+//! counting lines 37% (1.2 of 3.4 GiB)
+//! ```
+//! and clears it when the operation ends (or is dropped early by `?`).
+//! The next frame redraws the screen as usual.
+//!
+//! ## Bounded updates
+//! Nothing is printed for the first PROGRESS_FIRST_UPDATE_MS, so quick
+//! operations never flicker, and then at most once every
+//! PROGRESS_UPDATE_INTERVAL_MS however often `update` is called. Callers
+//! may call `update` once per read chunk.
+//!
+//! ## Quiet
+//! A meter made with `enabled == false` (stdout not a terminal, screen
+//! reader output, tests) prints nothing at all.
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// Quiet time before the first progress line is shown
+pub const PROGRESS_FIRST_UPDATE_MS: u64 = 500;
+
+/// Least time between two progress lines
+pub const PROGRESS_UPDATE_INTERVAL_MS: u64 = 250;
+
+/// Progress of one streaming operation over `total_bytes`
+pub struct ProgressMeter {
+    /// What is being done: "counting lines", "saving copy"
+    label: &'static str,
+    total_bytes: u64,
+    started: Instant,
+    /// When the last line was printed, None until the first
+    last_shown: Option<Instant>,
+    enabled: bool,
+}
+
+impl ProgressMeter {
+    /// Starts timing an operation; prints nothing yet
+    pub fn new(label: &'static str, total_bytes: u64, enabled: bool) -> Self {
+        ProgressMeter {
+            label,
+            total_bytes,
+            started: Instant::now(),
+            last_shown: None,
+            enabled,
+        }
+    }
+
+    /// Reports `done_bytes` processed; prints a line only when one is due
+    pub fn update(&mut self, done_bytes: u64) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(line) = self.due_line(done_bytes, Instant::now()) {
+            let mut stdout = io::stdout();
+            // Progress is a courtesy: a failed write must not fail the operation
+            let _ = write!(stdout, "\r\x1b[2K{}", line);
+            let _ = stdout.flush();
+        }
+    }
+
+    /// Clears the progress line, if one was printed
+    pub fn finish(&mut self) {
+        if self.enabled && self.last_shown.take().is_some() {
+            let mut stdout = io::stdout();
+            let _ = write!(stdout, "\r\x1b[2K");
+            let _ = stdout.flush();
+        }
+    }
+
+    /// The line to print at `now`, or None if it is too soon
+    fn due_line(&mut self, done_bytes: u64, now: Instant) -> Option<String> {
+        let due = match self.last_shown {
+            None => {
+                now.duration_since(self.started) >= Duration::from_millis(PROGRESS_FIRST_UPDATE_MS)
+            }
+            Some(last) => {
+                now.duration_since(last) >= Duration::from_millis(PROGRESS_UPDATE_INTERVAL_MS)
+            }
+        };
+        if !due {
+            return None;
+        }
+        self.last_shown = Some(now);
+        Some(progress_line(self.label, done_bytes, self.total_bytes))
+    }
+}
+
+impl Drop for ProgressMeter {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// "counting lines 37% (1.2 of 3.4 GiB)"; just the amount done when the
+/// total is unknown (0)
+pub fn progress_line(label: &str, done_bytes: u64, total_bytes: u64) -> String {
+    if total_bytes == 0 {
+        return format!("{} {}", label, short_size(done_bytes));
+    }
+    let done_bytes = done_bytes.min(total_bytes);
+    let percent = done_bytes.saturating_mul(100) / total_bytes;
+    let (scale, unit) = size_unit(total_bytes);
+    format!(
+        "{} {}% ({} of {} {})",
+        label,
+        percent,
+        tenths(done_bytes, scale),
+        tenths(total_bytes, scale),
+        unit
+    )
+}
+
+/// Divisor and name of the unit `bytes` is best shown in
+fn size_unit(bytes: u64) -> (u64, &'static str) {
+    const UNITS: [(u64, &str); 4] = [
+        (1 << 30, "GiB"),
+        (1 << 20, "MiB"),
+        (1 << 10, "KiB"),
+        (1, "B"),
+    ];
+    UNITS
+        .into_iter()
+        .find(|&(scale, _)| bytes >= scale)
+        .unwrap_or((1, "B"))
+}
+
+fn short_size(bytes: u64) -> String {
+    let (scale, unit) = size_unit(bytes);
+    format!("{} {}", tenths(bytes, scale), unit)
+}
+
+/// `bytes / scale` with one decimal, rounded down; whole bytes stay whole
+fn tenths(bytes: u64, scale: u64) -> String {
+    if scale == 1 {
+        return bytes.to_string();
+    }
+    format!("{}.{}", bytes / scale, (bytes % scale) * 10 / scale)
+}
+
+#[cfg(test)]
+mod progress_tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_line_text() {
+        assert_eq!(
+            progress_line("counting lines", 3 << 29, 3 << 30),
+            "counting lines 50% (1.5 of 3.0 GiB)"
+        );
+        assert_eq!(
+            progress_line("saving copy", 512, 2048),
+            "saving copy 25% (0.5 of 2.0 KiB)"
+        );
+        assert_eq!(progress_line("x", 900, 1000), "x 90% (900 of 1000 B)");
+        assert_eq!(progress_line("x", 5000, 1000), "x 100% (1000 of 1000 B)");
+        assert_eq!(progress_line("x", 1536, 0), "x 1.5 KiB");
+    }
+
+    #[test]
+    fn test_updates_are_delayed_then_rate_limited() {
+        let mut meter = ProgressMeter::new("x", 100, false);
+        let start = meter.started;
+        let ms = |millis| start + Duration::from_millis(millis);
+
+        assert_eq!(meter.due_line(10, ms(100)), None);
+        assert_eq!(
+            meter.due_line(20, ms(PROGRESS_FIRST_UPDATE_MS)),
+            Some("x 20% (20 of 100 B)".to_string())
+        );
+        assert_eq!(meter.due_line(30, ms(PROGRESS_FIRST_UPDATE_MS + 10)), None);
+        assert!(
+            meter
+                .due_line(
+                    40,
+                    ms(PROGRESS_FIRST_UPDATE_MS + PROGRESS_UPDATE_INTERVAL_MS)
+                )
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,454 @@
+//! regex_lite_module.rs - A small pattern matcher for `rx/` and `rxs/`
+//!
+//! ## Project Context
+//! lines uses no third-party crates, so there is no regex crate. Search
+//! and replace mostly want exact text (`/text`, `s/old/new/`); for the
+//! times a shape is wanted instead, this matcher takes a few of the usual
+//! regular expression pieces:
+//! ```text
+//! .        any character
+//! [a-zé_]  one character from the set; [^0-9] one character not in it
+//! x* x+ x? zero or more / one or more / zero or one of the piece before
+//! ^ $      start / end of the line (first / last in the pattern only)
+//! \.       the next character as itself (\\ \[ \* ...)
+//! ```
+//! No groups, no alternation, no counted repeats.
+//!
+//! ## How it matches
+//! The pattern is compiled into at most MAX_PATTERN_BYTES pieces held in
+//! a fixed array. Matching backtracks, greedy as usual: the leftmost
+//! start wins, and at that start each repeat takes as much as still lets
+//! the rest match. Text is bytes, taken a UTF-8 character at a time (a
+//! byte that does not start a valid character is one on its own), so a
+//! match never starts or ends inside a character. A line is matched
+//! without its newline, so `$` is the end of the slice. Every piece tried counts one step, and
+//! a match that takes more than MAX_MATCH_STEPS gives up (TooComplex)
+//! rather than run on.
+
+/// Longest pattern, in bytes (as for `/text` search)
+pub const MAX_PATTERN_BYTES: usize = 64;
+
+/// Most pieces tried by one find before it gives up
+pub const MAX_MATCH_STEPS: usize = 1_000_000;
+
+/// Most non-ASCII class ranges in one pattern (each takes 2+ bytes)
+const MAX_CLASS_RANGES: usize = MAX_PATTERN_BYTES / 2;
+
+/// Why a pattern could not be compiled or matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegexError {
+    /// Nothing to match
+    Empty,
+    /// Longer than MAX_PATTERN_BYTES
+    TooLong,
+    /// `[` without its `]`
+    UnclosedClass,
+    /// `*`, `+` or `?` with nothing before it
+    NothingToRepeat,
+    /// `\` at the end of the pattern
+    TrailingBackslash,
+    /// More than MAX_MATCH_STEPS steps
+    TooComplex,
+}
+
+impl RegexError {
+    /// Short text for the info bar
+    pub fn message(self) -> &'static str {
+        match self {
+            RegexError::Empty => "rx: empty pattern",
+            RegexError::TooLong => "rx: pattern too long (max 64)",
+            RegexError::UnclosedClass => "rx: [ without ]",
+            RegexError::NothingToRepeat => "rx: * + ? need something before",
+            RegexError::TrailingBackslash => "rx: \\ at the end",
+            RegexError::TooComplex => "rx: too complex, gave up",
+        }
+    }
+}
+
+impl std::fmt::Display for RegexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for RegexError {}
+
+/// One character of text or pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    /// An ASCII byte, or a byte that does not start a valid character
+    Byte(u8),
+    /// A non-ASCII UTF-8 character
+    Char(char),
+}
+
+/// The unit at `bytes[position]` and how many bytes it takes
+fn unit_at(bytes: &[u8], position: usize) -> (Unit, usize) {
+    let lead = bytes[position];
+    let width = match lead {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return (Unit::Byte(lead), 1),
+    };
+    if let Some(encoded) = bytes.get(position..position + width)
+        && let Ok(text) = std::str::from_utf8(encoded)
+        && let Some(character) = text.chars().next()
+    {
+        return (Unit::Char(character), width);
+    }
+    (Unit::Byte(lead), 1)
+}
+
+/// Start of the unit that ends at `end`, no earlier than `floor`
+/// (`floor` is where a run of units started)
+fn unit_start_before(text: &[u8], floor: usize, end: usize) -> usize {
+    let mut start = end - 1;
+    while start > floor && end - start < 4 && text[start] & 0xC0 == 0x80 {
+        start -= 1;
+    }
+    if unit_at(text, start).1 == end - start {
+        start
+    } else {
+        end - 1
+    }
+}
+
+/// `[...]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ClassSet {
+    /// Bit b of the 256-bit set is Unit::Byte(b)
+    bytes: [u64; 4],
+    /// Non-ASCII characters: class_ranges[first_range..][..range_count]
+    first_range: usize,
+    range_count: usize,
+    /// `[^...]`
+    negated: bool,
+}
+
+/// One character's worth of pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Atom {
+    /// This unit
+    Unit(Unit),
+    /// `.`
+    Any,
+    Class(ClassSet),
+}
+
+/// How many times an atom may match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repeat {
+    One,
+    /// `*`
+    ZeroOrMore,
+    /// `+`
+    OneOrMore,
+    /// `?`
+    ZeroOrOne,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Piece {
+    atom: Atom,
+    repeat: Repeat,
+}
+
+const EMPTY_PIECE: Piece = Piece {
+    atom: Atom::Any,
+    repeat: Repeat::One,
+};
+
+/// A compiled pattern (see the module docs for what it takes)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regex {
+    pieces: [Piece; MAX_PATTERN_BYTES],
+    piece_count: usize,
+    /// `^`: matches only at the start of the text
+    anchored_start: bool,
+    /// `$`: matches only up to the end of the text
+    anchored_end: bool,
+    /// Non-ASCII ranges of all the classes, in pattern order
+    class_ranges: [(char, char); MAX_CLASS_RANGES],
+    class_range_count: usize,
+}
+
+impl Regex {
+    /// Compiles `pattern`
+    ///
+    /// # Returns
+    /// * `Ok(regex)` - Ready to match
+    /// * `Err(RegexError)` - Empty, too long, or malformed
+    pub fn new(pattern: &[u8]) -> Result<Regex, RegexError> {
+        if pattern.is_empty() {
+            return Err(RegexError::Empty);
+        }
+        if pattern.len() > MAX_PATTERN_BYTES {
+            return Err(RegexError::TooLong);
+        }
+
+        let (anchored_start, pattern) = match pattern.strip_prefix(b"^") {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        // A '$' at the end is an anchor unless escaped ("\$")
+        let escaped_dollar = pattern.len() >= 2
+            && pattern[pattern.len() - 2] == b'\\'
+            && !ends_in_escaped_backslash(&pattern[..pattern.len() - 1]);
+        let (anchored_end, pattern) = match pattern.strip_suffix(b"$") {
+            Some(rest) if !escaped_dollar => (true, rest),
+            _ => (false, pattern),
+        };
+
+        let mut regex = Regex {
+            pieces: [EMPTY_PIECE; MAX_PATTERN_BYTES],
+            piece_count: 0,
+            anchored_start,
+            anchored_end,
+            class_ranges: [('\0', '\0'); MAX_CLASS_RANGES],
+            class_range_count: 0,
+        };
+        let mut index = 0;
+        while index < pattern.len() {
+            let repeat = match pattern[index] {
+                b'*' => Some(Repeat::ZeroOrMore),
+                b'+' => Some(Repeat::OneOrMore),
+                b'?' => Some(Repeat::ZeroOrOne),
+                _ => None,
+            };
+            if let Some(repeat) = repeat {
+                // Repeats apply to the piece before, once
+                match regex.piece_count.checked_sub(1) {
+                    Some(last) if regex.pieces[last].repeat == Repeat::One => {
+                        regex.pieces[last].repeat = repeat;
+                    }
+                    _ => return Err(RegexError::NothingToRepeat),
+                }
+                index += 1;
+                continue;
+            }
+
+            let atom = match pattern[index] {
+                b'.' => {
+                    index += 1;
+                    Atom::Any
+                }
+                b'\\' => {
+                    if index + 1 == pattern.len() {
+                        return Err(RegexError::TrailingBackslash);
+                    }
+                    let (unit, width) = unit_at(pattern, index + 1);
+                    index += 1 + width;
+                    Atom::Unit(unit)
+                }
+                b'[' => {
+                    let (class, length) = regex.parse_class(&pattern[index..])?;
+                    index += length;
+                    Atom::Class(class)
+                }
+                _ => {
+                    let (unit, width) = unit_at(pattern, index);
+                    index += width;
+                    Atom::Unit(unit)
+                }
+            };
+            // At most one piece per pattern byte, so this always fits
+            regex.pieces[regex.piece_count] = Piece {
+                atom,
+                repeat: Repeat::One,
+            };
+            regex.piece_count += 1;
+        }
+        Ok(regex)
+    }
+
+    /// The leftmost match in `text`, as (start, end) byte offsets
+    ///
+    /// # Returns
+    /// * `Ok(Some((start, end)))` - `text[start..end]` matched (may be empty)
+    /// * `Ok(None)` - No match
+    /// * `Err(RegexError::TooComplex)` - Gave up after MAX_MATCH_STEPS
+    pub fn find(&self, text: &[u8]) -> Result<Option<(usize, usize)>, RegexError> {
+        self.find_from(text, 0)
+    }
+
+    /// As find, for matches starting at `from` or later (`^` still means
+    /// the start of `text`)
+    pub fn find_from(
+        &self,
+        text: &[u8],
+        from: usize,
+    ) -> Result<Option<(usize, usize)>, RegexError> {
+        let mut steps = 0usize;
+        // The first character boundary at or after `from`
+        let mut start = 0;
+        while start < from && start < text.len() {
+            start += unit_at(text, start).1;
+        }
+        if start < from {
+            return Ok(None);
+        }
+        loop {
+            if self.anchored_start && start > 0 {
+                return Ok(None);
+            }
+            if let Some(end) = self.match_here(text, start, 0, &mut steps)? {
+                return Ok(Some((start, end)));
+            }
+            if start == text.len() {
+                return Ok(None);
+            }
+            start += unit_at(text, start).1;
+        }
+    }
+
+    /// Whether `atom` matches the text unit `unit`
+    fn atom_matches(&self, atom: &Atom, unit: Unit) -> bool {
+        match atom {
+            Atom::Unit(expected) => unit == *expected,
+            Atom::Any => true,
+            Atom::Class(class) => {
+                let member = match unit {
+                    Unit::Byte(byte) => {
+                        class.bytes[usize::from(byte >> 6)] & (1 << (byte & 63)) != 0
+                    }
+                    Unit::Char(character) => self.class_ranges[class.first_range..]
+                        [..class.range_count]
+                        .iter()
+                        .any(|&(low, high)| (low..=high).contains(&character)),
+                };
+                member != class.negated
+            }
+        }
+    }
+
+    /// End of a match of pieces[piece..] at text[position..], if any
+    fn match_here(
+        &self,
+        text: &[u8],
+        position: usize,
+        piece: usize,
+        steps: &mut usize,
+    ) -> Result<Option<usize>, RegexError> {
+        *steps += 1;
+        if *steps > MAX_MATCH_STEPS {
+            return Err(RegexError::TooComplex);
+        }
+        let Some(current) = self.pieces[..self.piece_count].get(piece) else {
+            let at_end_if_needed = !self.anchored_end || position == text.len();
+            return Ok(at_end_if_needed.then_some(position));
+        };
+
+        let (least, most) = match current.repeat {
+            Repeat::One => (1, 1),
+            Repeat::ZeroOrOne => (0, 1),
+            Repeat::ZeroOrMore => (0, usize::MAX),
+            Repeat::OneOrMore => (1, usize::MAX),
+        };
+        // Greedy: take as many as match, then give back one at a time
+        let mut taken = 0usize;
+        let mut end = position;
+        while taken < most && end < text.len() {
+            let (unit, width) = unit_at(text, end);
+            if !self.atom_matches(&current.atom, unit) {
+                break;
+            }
+            taken += 1;
+            end += width;
+        }
+        if taken < least {
+            return Ok(None);
+        }
+        loop {
+            if let Some(found) = self.match_here(text, end, piece + 1, steps)? {
+                return Ok(Some(found));
+            }
+            if taken == least {
+                return Ok(None);
+            }
+            taken -= 1;
+            end = unit_start_before(text, position, end);
+        }
+    }
+
+    /// Parses `[...]` at the start of `pattern`: the set and how many
+    /// pattern bytes it took. `]` first in the set is itself; `a-z` is a
+    /// range; `\` escapes the next character. Non-ASCII ranges go into
+    /// class_ranges.
+    fn parse_class(&mut self, pattern: &[u8]) -> Result<(ClassSet, usize), RegexError> {
+        let mut class = ClassSet {
+            bytes: [0u64; 4],
+            first_range: self.class_range_count,
+            range_count: 0,
+            negated: pattern.get(1) == Some(&b'^'),
+        };
+        let mut index = if class.negated { 2 } else { 1 };
+        let first = index;
+
+        // The unit at `index` (after a `\`, if any) and the index after it
+        let unit_from = |mut index: usize| -> Result<(Unit, usize), RegexError> {
+            if *pattern.get(index).ok_or(RegexError::UnclosedClass)? == b'\\' {
+                index += 1;
+                pattern.get(index).ok_or(RegexError::UnclosedClass)?;
+            }
+            let (unit, width) = unit_at(pattern, index);
+            Ok((unit, index + width))
+        };
+
+        loop {
+            if *pattern.get(index).ok_or(RegexError::UnclosedClass)? == b']' && index > first {
+                index += 1;
+                break;
+            }
+            let (low, after_low) = unit_from(index)?;
+            index = after_low;
+
+            // "a-z", but a '-' before the closing ']' is itself
+            let mut high = low;
+            if pattern.get(index) == Some(&b'-')
+                && let Some(&end) = pattern.get(index + 1)
+                && end != b']'
+            {
+                (high, index) = unit_from(index + 1)?;
+            }
+
+            match (low, high) {
+                (Unit::Byte(low), Unit::Byte(high)) => {
+                    for byte in low..=high.max(low) {
+                        class.bytes[usize::from(byte >> 6)] |= 1 << (byte & 63);
+                    }
+                }
+                _ => {
+                    let low = unit_char(low);
+                    let high = unit_char(high).max(low);
+                    // ASCII text is matched by the byte set
+                    if low.is_ascii() {
+                        for byte in low as u8..=high.min('\x7f') as u8 {
+                            class.bytes[usize::from(byte >> 6)] |= 1 << (byte & 63);
+                        }
+                    }
+                    // At least two pattern bytes per range, so this always fits
+                    self.class_ranges[self.class_range_count] = (low.max('\u{80}'), high);
+                    self.class_range_count += 1;
+                    class.range_count += 1;
+                }
+            }
+        }
+        Ok((class, index))
+    }
+}
+
+/// Whether `pattern` ends in an escaped backslash ("\\"), counting the
+/// run of backslashes at its end
+fn ends_in_escaped_backslash(pattern: &[u8]) -> bool {
+    let run = pattern.iter().rev().take_while(|&&b| b == b'\\').count();
+    run > 0 && run.is_multiple_of(2)
+}
+
+/// `unit` as a character (a lone byte as the character of that number)
+fn unit_char(unit: Unit) -> char {
+    match unit {
+        Unit::Byte(byte) => char::from(byte),
+        Unit::Char(character) => character,
+    }
+}
@@ -0,0 +1,767 @@
+//! session_encryption_module.rs - ChaCha20 encryption of session files at rest
+//!
+//! ## Project Context
+//! With `--secure-encrypt`, files Lines keeps in its session directory can be
+//! stored encrypted under a passphrase, for editing secrets on shared
+//! machines. Pure Rust, no crates, no unsafe:
+//! - ChaCha20 stream cipher (RFC 8439, 256-bit key, 96-bit nonce)
+//! - PBKDF2-HMAC-SHA256 passphrase key derivation (SHA-256 from checksum_module)
+//! - Nonces and salts from /dev/urandom (no weaker fallback: encryption is
+//!   refused if it is unavailable)
+//!
+//! ## What Gets Encrypted When
+//! - Clipboard items: encrypted as soon as they are written; decrypted to a
+//!   short-lived temp file (zeroed after use) when pasted
+//! - Changelogs (undo/redo): encrypted as each log file is written;
+//!   decrypted in memory when read
+//! - Read-copies: edited in place by byte offset while the editor runs, so
+//!   they are plaintext during the session; they are encrypted when a kept
+//!   session closes and decrypted when it is reopened with --session (see
+//!   lines_full_file_editor_with_recovery). After a crash, the read-copy is
+//!   left in plaintext.
+//!
+//! The key for the open session is held process-wide
+//! (`set_active_session_cipher`), like the --secure switch, so the clipboard
+//! and changelog code can reach it without threading it through every call.
+//!
+//! ## File Format
+//! ```text
+//! [ciphertext: same length as plaintext][nonce: 12 bytes]["LINESEC1"]
+//! ```
+//! The nonce and magic are a trailer, not a header, so encryption and
+//! decryption work in place chunk by chunk: the plaintext blocks are
+//! overwritten by ciphertext, never copied elsewhere first.
+//!
+//! ## Limitations (By Design)
+//! - Confidentiality only: there is no MAC, so tampering is not detected
+//!   (a wrong passphrase IS detected, by the session key check)
+//! - Files up to ~256 GiB (the 32-bit ChaCha20 block counter)
+//! - Copy-on-write filesystems and SSDs may keep old plaintext blocks
+
+use crate::checksum_module::Sha256;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// ChaCha20 key length (256 bits)
+pub const CHACHA20_KEY_BYTES: usize = 32;
+
+/// ChaCha20 nonce length (96 bits, RFC 8439)
+pub const CHACHA20_NONCE_BYTES: usize = 12;
+
+/// One ChaCha20 keystream block
+const CHACHA20_BLOCK_BYTES: usize = 64;
+
+/// Block counter of the first data block (0 is left unused, as in RFC 8439 AEAD)
+const FIRST_DATA_BLOCK_COUNTER: u32 = 1;
+
+/// Marks an encrypted file (last bytes of the file)
+pub const ENCRYPTED_FILE_MAGIC: &[u8; 8] = b"LINESEC1";
+
+/// Trailer length: nonce + magic
+const ENCRYPTED_TRAILER_BYTES: usize = CHACHA20_NONCE_BYTES + ENCRYPTED_FILE_MAGIC.len();
+
+/// PBKDF2 iterations for passphrase key derivation
+pub const PBKDF2_ITERATIONS: u32 = 100_000;
+
+/// Salt length for passphrase key derivation
+pub const SESSION_SALT_BYTES: usize = 16;
+
+/// Session key file: salt, then key check value
+pub const SESSION_KEY_FILE_NAME: &str = "encryption_key_check";
+
+/// Context string hashed with the key to make the key check value
+const KEY_CHECK_CONTEXT: &[u8] = b"lines session key check v1";
+
+/// Chunk size for in-place file encryption (stack buffer)
+const CRYPT_CHUNK_BYTES: usize = 4096;
+
+/// Longest accepted passphrase, in bytes
+pub const MAX_PASSPHRASE_BYTES: usize = 256;
+
+// ============================================================================
+// CHACHA20 (RFC 8439)
+// ============================================================================
+
+/// "expand 32-byte k"
+const CHACHA20_CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// One 64-byte keystream block for (key, counter, nonce)
+pub fn chacha20_block(
+    key: &[u8; CHACHA20_KEY_BYTES],
+    counter: u32,
+    nonce: &[u8; CHACHA20_NONCE_BYTES],
+) -> [u8; CHACHA20_BLOCK_BYTES] {
+    let mut initial = [0u32; 16];
+    initial[..4].copy_from_slice(&CHACHA20_CONSTANTS);
+    for (word, bytes) in initial[4..12].iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    initial[12] = counter;
+    for (word, bytes) in initial[13..16].iter_mut().zip(nonce.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let mut working = initial;
+    // 20 rounds = 10 double rounds (column round + diagonal round)
+    for _ in 0..10 {
+        quarter_round(&mut working, 0, 4, 8, 12);
+        quarter_round(&mut working, 1, 5, 9, 13);
+        quarter_round(&mut working, 2, 6, 10, 14);
+        quarter_round(&mut working, 3, 7, 11, 15);
+        quarter_round(&mut working, 0, 5, 10, 15);
+        quarter_round(&mut working, 1, 6, 11, 12);
+        quarter_round(&mut working, 2, 7, 8, 13);
+        quarter_round(&mut working, 3, 4, 9, 14);
+    }
+
+    let mut block = [0u8; CHACHA20_BLOCK_BYTES];
+    for (index, bytes) in block.chunks_exact_mut(4).enumerate() {
+        bytes.copy_from_slice(&working[index].wrapping_add(initial[index]).to_le_bytes());
+    }
+    block
+}
+
+/// XORs `data` with the keystream starting at byte `stream_offset`
+///
+/// Seekable: any range of a file can be processed on its own, which is
+/// what in-place chunked encryption needs. The same call encrypts and
+/// decrypts.
+///
+/// # Errors
+/// - InvalidInput if the range runs past the 32-bit block counter
+pub fn chacha20_xor_at(
+    key: &[u8; CHACHA20_KEY_BYTES],
+    nonce: &[u8; CHACHA20_NONCE_BYTES],
+    stream_offset: u64,
+    data: &mut [u8],
+) -> io::Result<()> {
+    let counter_limit = u64::from(u32::MAX) - u64::from(FIRST_DATA_BLOCK_COUNTER);
+    let last_block = (stream_offset + data.len() as u64) / CHACHA20_BLOCK_BYTES as u64;
+    if last_block > counter_limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "File too large for ChaCha20 block counter",
+        ));
+    }
+
+    let mut position = stream_offset;
+    let mut done = 0;
+    while done < data.len() {
+        let block_index = position / CHACHA20_BLOCK_BYTES as u64;
+        let offset_in_block = (position % CHACHA20_BLOCK_BYTES as u64) as usize;
+        let counter = FIRST_DATA_BLOCK_COUNTER + block_index as u32;
+        let keystream = chacha20_block(key, counter, nonce);
+
+        let take = (CHACHA20_BLOCK_BYTES - offset_in_block).min(data.len() - done);
+        for (byte, key_byte) in data[done..done + take]
+            .iter_mut()
+            .zip(&keystream[offset_in_block..offset_in_block + take])
+        {
+            *byte ^= key_byte;
+        }
+        done += take;
+        position += take as u64;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// PASSPHRASE KEY DERIVATION (PBKDF2-HMAC-SHA256)
+// ============================================================================
+
+/// HMAC-SHA256 of `message` under `key`
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut key_block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        let mut hasher = Sha256::new();
+        hasher.update(key);
+        key_block[..32].copy_from_slice(&hasher.finalize());
+    } else {
+        key_block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner_pad = [0x36u8; BLOCK];
+    let mut outer_pad = [0x5cu8; BLOCK];
+    for index in 0..BLOCK {
+        inner_pad[index] ^= key_block[index];
+        outer_pad[index] ^= key_block[index];
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(&inner_pad);
+    inner.update(message);
+    let inner_digest = inner.finalize();
+
+    let mut outer = Sha256::new();
+    outer.update(&outer_pad);
+    outer.update(&inner_digest);
+    let digest = outer.finalize();
+
+    wipe(&mut key_block);
+    wipe(&mut inner_pad);
+    wipe(&mut outer_pad);
+    digest
+}
+
+/// PBKDF2-HMAC-SHA256, first 32-byte output block
+pub fn pbkdf2_hmac_sha256(passphrase: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    // U1 = HMAC(P, S || INT(1))
+    let mut salted = [0u8; SESSION_SALT_BYTES + 4];
+    let salt_length = salt.len().min(SESSION_SALT_BYTES);
+    salted[..salt_length].copy_from_slice(&salt[..salt_length]);
+    salted[salt_length..salt_length + 4].copy_from_slice(&1u32.to_be_bytes());
+
+    let mut previous = hmac_sha256(passphrase, &salted[..salt_length + 4]);
+    let mut output = previous;
+    for _ in 1..iterations.max(1) {
+        previous = hmac_sha256(passphrase, &previous);
+        for (out, byte) in output.iter_mut().zip(previous.iter()) {
+            *out ^= byte;
+        }
+    }
+    wipe(&mut previous);
+    output
+}
+
+/// Overwrites key material with zeros (kept by black_box)
+fn wipe(bytes: &mut [u8]) {
+    bytes.fill(0);
+    std::hint::black_box(bytes);
+}
+
+/// Fills `buffer` from /dev/urandom
+///
+/// # Errors
+/// Any error opening or reading /dev/urandom. There is no fallback:
+/// predictable nonces or salts would undermine the encryption.
+pub fn random_bytes(buffer: &mut [u8]) -> io::Result<()> {
+    File::open("/dev/urandom")?.read_exact(buffer)
+}
+
+// ============================================================================
+// SESSION CIPHER
+// ============================================================================
+
+/// Key for one session directory, derived from the passphrase
+///
+/// The key is zeroed when the cipher is dropped.
+#[derive(Clone)]
+pub struct SessionCipher {
+    key: [u8; CHACHA20_KEY_BYTES],
+}
+
+impl std::fmt::Debug for SessionCipher {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the key
+        formatter.write_str("SessionCipher(..)")
+    }
+}
+
+impl Drop for SessionCipher {
+    fn drop(&mut self) {
+        wipe(&mut self.key);
+    }
+}
+
+impl SessionCipher {
+    /// Derives the key for `session_dir` and checks it against the session
+    ///
+    /// A new session gets a random salt and a key check value, saved in
+    /// SESSION_KEY_FILE_NAME. An existing session's check value must match,
+    /// so a mistyped passphrase cannot decrypt into garbage.
+    ///
+    /// # Errors
+    /// - PermissionDenied: passphrase does not match this session
+    /// - InvalidData: damaged key check file
+    /// - Other I/O errors reading or writing the key check file
+    pub fn open_session(session_dir: &Path, passphrase: &[u8]) -> io::Result<Self> {
+        Self::open_session_with_iterations(session_dir, passphrase, PBKDF2_ITERATIONS)
+    }
+
+    /// open_session with a chosen PBKDF2 iteration count (tests use few)
+    pub fn open_session_with_iterations(
+        session_dir: &Path,
+        passphrase: &[u8],
+        iterations: u32,
+    ) -> io::Result<Self> {
+        let key_file_path = session_dir.join(SESSION_KEY_FILE_NAME);
+
+        if key_file_path.exists() {
+            let stored = std::fs::read(&key_file_path)?;
+            if stored.len() != SESSION_SALT_BYTES + 32 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Damaged session key check file",
+                ));
+            }
+            let cipher = SessionCipher {
+                key: pbkdf2_hmac_sha256(passphrase, &stored[..SESSION_SALT_BYTES], iterations),
+            };
+            if cipher.key_check() != stored[SESSION_SALT_BYTES..] {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "Passphrase does not match this session",
+                ));
+            }
+            Ok(cipher)
+        } else {
+            let mut salt = [0u8; SESSION_SALT_BYTES];
+            random_bytes(&mut salt)?;
+            let cipher = SessionCipher {
+                key: pbkdf2_hmac_sha256(passphrase, &salt, iterations),
+            };
+
+            let mut stored = [0u8; SESSION_SALT_BYTES + 32];
+            stored[..SESSION_SALT_BYTES].copy_from_slice(&salt);
+            stored[SESSION_SALT_BYTES..].copy_from_slice(&cipher.key_check());
+            std::fs::write(&key_file_path, stored)?;
+            Ok(cipher)
+        }
+    }
+
+    /// Value stored with the salt to recognise the right passphrase
+    fn key_check(&self) -> [u8; 32] {
+        hmac_sha256(&self.key, KEY_CHECK_CONTEXT)
+    }
+
+    /// Encrypts a file in place and appends the trailer
+    ///
+    /// Already-encrypted files are left alone, so this is safe to repeat.
+    pub fn encrypt_file_in_place(&self, path: &Path) -> io::Result<()> {
+        if is_encrypted_file(path)? {
+            return Ok(());
+        }
+
+        let mut nonce = [0u8; CHACHA20_NONCE_BYTES];
+        random_bytes(&mut nonce)?;
+
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let data_length = file.metadata()?.len();
+        self.xor_file_range(&mut file, &nonce, data_length)?;
+
+        file.seek(SeekFrom::Start(data_length))?;
+        file.write_all(&nonce)?;
+        file.write_all(ENCRYPTED_FILE_MAGIC)?;
+        file.sync_all()
+    }
+
+    /// Decrypts a file in place and removes the trailer
+    ///
+    /// Plaintext files are left alone.
+    pub fn decrypt_file_in_place(&self, path: &Path) -> io::Result<()> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let nonce = match read_trailer_nonce(&mut file)? {
+            Some(nonce) => nonce,
+            None => return Ok(()),
+        };
+        let data_length = file.metadata()?.len() - ENCRYPTED_TRAILER_BYTES as u64;
+
+        self.xor_file_range(&mut file, &nonce, data_length)?;
+        file.set_len(data_length)?;
+        file.sync_all()
+    }
+
+    /// Writes the plaintext of an encrypted file to a new file
+    ///
+    /// `destination` must not exist. A plaintext source is copied as is.
+    pub fn decrypt_file_to(&self, source: &Path, destination: &Path) -> io::Result<()> {
+        let mut input = File::open(source)?;
+        let nonce = read_trailer_nonce(&mut input)?;
+        let total_length = input.metadata()?.len();
+        let data_length = match nonce {
+            Some(_) => total_length - ENCRYPTED_TRAILER_BYTES as u64,
+            None => total_length,
+        };
+
+        let mut output = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(destination)?;
+        input.seek(SeekFrom::Start(0))?;
+
+        let mut chunk = [0u8; CRYPT_CHUNK_BYTES];
+        let mut position: u64 = 0;
+        while position < data_length {
+            let take = (data_length - position).min(CRYPT_CHUNK_BYTES as u64) as usize;
+            input.read_exact(&mut chunk[..take])?;
+            if let Some(nonce) = &nonce {
+                chacha20_xor_at(&self.key, nonce, position, &mut chunk[..take])?;
+            }
+            output.write_all(&chunk[..take])?;
+            position += take as u64;
+        }
+        wipe(&mut chunk);
+        output.flush()
+    }
+
+    /// Decrypts file content already read into memory
+    ///
+    /// Content without the trailer is left as is.
+    pub fn decrypt_bytes(&self, content: &mut Vec<u8>) -> io::Result<()> {
+        let data_length = match content.len().checked_sub(ENCRYPTED_TRAILER_BYTES) {
+            Some(length)
+                if content[length + CHACHA20_NONCE_BYTES..] == ENCRYPTED_FILE_MAGIC[..] =>
+            {
+                length
+            }
+            _ => return Ok(()),
+        };
+
+        let mut nonce = [0u8; CHACHA20_NONCE_BYTES];
+        nonce.copy_from_slice(&content[data_length..data_length + CHACHA20_NONCE_BYTES]);
+        content.truncate(data_length);
+        chacha20_xor_at(&self.key, &nonce, 0, content)
+    }
+
+    /// XORs bytes [0..data_length) of `file` with the keystream, in place
+    fn xor_file_range(
+        &self,
+        file: &mut File,
+        nonce: &[u8; CHACHA20_NONCE_BYTES],
+        data_length: u64,
+    ) -> io::Result<()> {
+        let mut chunk = [0u8; CRYPT_CHUNK_BYTES];
+        let mut position: u64 = 0;
+        while position < data_length {
+            let take = (data_length - position).min(CRYPT_CHUNK_BYTES as u64) as usize;
+            file.seek(SeekFrom::Start(position))?;
+            file.read_exact(&mut chunk[..take])?;
+            chacha20_xor_at(&self.key, nonce, position, &mut chunk[..take])?;
+            file.seek(SeekFrom::Start(position))?;
+            file.write_all(&chunk[..take])?;
+            position += take as u64;
+        }
+        wipe(&mut chunk);
+        Ok(())
+    }
+}
+
+/// Reads the trailer nonce if the file ends with ENCRYPTED_FILE_MAGIC
+fn read_trailer_nonce(file: &mut File) -> io::Result<Option<[u8; CHACHA20_NONCE_BYTES]>> {
+    let length = file.metadata()?.len();
+    if length < ENCRYPTED_TRAILER_BYTES as u64 {
+        return Ok(None);
+    }
+
+    let mut trailer = [0u8; ENCRYPTED_TRAILER_BYTES];
+    file.seek(SeekFrom::Start(length - ENCRYPTED_TRAILER_BYTES as u64))?;
+    file.read_exact(&mut trailer)?;
+    if &trailer[CHACHA20_NONCE_BYTES..] != ENCRYPTED_FILE_MAGIC {
+        return Ok(None);
+    }
+
+    let mut nonce = [0u8; CHACHA20_NONCE_BYTES];
+    nonce.copy_from_slice(&trailer[..CHACHA20_NONCE_BYTES]);
+    Ok(Some(nonce))
+}
+
+/// Whether a file carries the encrypted-file trailer
+pub fn is_encrypted_file(path: &Path) -> io::Result<bool> {
+    Ok(read_trailer_nonce(&mut File::open(path)?)?.is_some())
+}
+
+// ============================================================================
+// ACTIVE SESSION CIPHER (--secure-encrypt)
+// ============================================================================
+
+/// Key of the open encrypted session; None when encryption is off
+static ACTIVE_SESSION_CIPHER: Mutex<Option<SessionCipher>> = Mutex::new(None);
+
+/// Sets (or with None, clears and zeroes) the open session's key
+pub fn set_active_session_cipher(cipher: Option<SessionCipher>) {
+    let mut active = ACTIVE_SESSION_CIPHER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *active = cipher;
+}
+
+/// Runs `action` with the open session's key, if there is one
+fn with_active_session_cipher<T>(action: impl FnOnce(Option<&SessionCipher>) -> T) -> T {
+    let active = ACTIVE_SESSION_CIPHER
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    action(active.as_ref())
+}
+
+/// Encrypts a just-written session file in place if encryption is on
+pub fn encrypt_if_session_encrypted(path: &Path) -> io::Result<()> {
+    with_active_session_cipher(|cipher| match cipher {
+        Some(cipher) => cipher.encrypt_file_in_place(path),
+        None => Ok(()),
+    })
+}
+
+/// Reads a session file, decrypting it in memory if it is encrypted
+///
+/// # Errors
+/// - PermissionDenied: file is encrypted and no session key is set
+pub fn read_session_file(path: &Path) -> io::Result<Vec<u8>> {
+    let mut content = std::fs::read(path)?;
+    with_active_session_cipher(|cipher| match cipher {
+        Some(cipher) => cipher.decrypt_bytes(&mut content),
+        None if content.ends_with(ENCRYPTED_FILE_MAGIC) => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "Encrypted session file, no session key",
+        )),
+        None => Ok(()),
+    })?;
+    Ok(content)
+}
+
+/// Writes the plaintext of a session file to a new file
+///
+/// # Errors
+/// - PermissionDenied: file is encrypted and no session key is set
+pub fn decrypt_session_file_to(source: &Path, destination: &Path) -> io::Result<()> {
+    with_active_session_cipher(|cipher| match cipher {
+        Some(cipher) => cipher.decrypt_file_to(source, destination),
+        None if is_encrypted_file(source)? => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "Encrypted session file, no session key",
+        )),
+        None => std::fs::copy(source, destination).map(|_| ()),
+    })
+}
+
+// ============================================================================
+// PASSPHRASE PROMPT
+// ============================================================================
+
+/// Passphrase held in a fixed buffer, zeroed when dropped
+pub struct PassphraseBuffer {
+    bytes: [u8; MAX_PASSPHRASE_BYTES],
+    length: usize,
+}
+
+impl PassphraseBuffer {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.length]
+    }
+}
+
+impl Drop for PassphraseBuffer {
+    fn drop(&mut self) {
+        wipe(&mut self.bytes);
+    }
+}
+
+/// Reads a passphrase without echo
+///
+/// Uses the terminal in raw mode (so nothing is echoed); without a
+/// terminal, reads one line from stdin. Must be called before anything
+/// else holds the stdin lock.
+///
+/// # Errors
+/// - Interrupted: user pressed Ctrl+C
+/// - InvalidInput: empty passphrase
+pub fn prompt_for_passphrase(prompt: &str) -> io::Result<PassphraseBuffer> {
+    eprint!("{}", prompt);
+    let _ = io::stderr().flush();
+
+    let mut passphrase = PassphraseBuffer {
+        bytes: [0u8; MAX_PASSPHRASE_BYTES],
+        length: 0,
+    };
+    let result = match crate::raw_terminal_x86_module::RawTerminal::new() {
+        Ok(mut terminal) => read_passphrase_from(&mut terminal, &mut passphrase.bytes),
+        Err(_) => read_passphrase_from(&mut io::stdin().lock(), &mut passphrase.bytes),
+    };
+    eprintln!();
+
+    passphrase.length = result?;
+    if passphrase.length == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Empty passphrase",
+        ));
+    }
+    Ok(passphrase)
+}
+
+/// Reads passphrase bytes up to Enter (or end of input)
+///
+/// Backspace (0x7f or 0x08) removes the last byte; Ctrl+C (0x03) aborts.
+/// Bytes past the buffer length are dropped.
+pub fn read_passphrase_from<R: Read>(reader: &mut R, passphrase: &mut [u8]) -> io::Result<usize> {
+    let mut length = 0;
+    let mut byte = [0u8; 1];
+
+    // Bounded: a passphrase line longer than this is not a passphrase
+    for _ in 0..MAX_PASSPHRASE_BYTES * 4 {
+        if reader.read(&mut byte)? == 0 {
+            break;
+        }
+        match byte[0] {
+            b'\r' | b'\n' => break,
+            0x03 => {
+                wipe(passphrase);
+                return Err(io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    "Passphrase entry cancelled",
+                ));
+            }
+            0x7f | 0x08 => {
+                if length > 0 {
+                    length -= 1;
+                    passphrase[length] = 0;
+                }
+            }
+            value if length < passphrase.len() => {
+                passphrase[length] = value;
+                length += 1;
+            }
+            _ => {}
+        }
+    }
+    wipe(&mut byte);
+    Ok(length)
+}
+
+#[cfg(test)]
+mod session_encryption_tests {
+    use super::*;
+
+    fn test_key() -> [u8; CHACHA20_KEY_BYTES] {
+        let mut key = [0u8; CHACHA20_KEY_BYTES];
+        for (index, byte) in key.iter_mut().enumerate() {
+            *byte = index as u8;
+        }
+        key
+    }
+
+    #[test]
+    fn test_chacha20_block_rfc8439_vector() {
+        // RFC 8439 section 2.3.2
+        let nonce = [0, 0, 0, 0x09, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+        let block = chacha20_block(&test_key(), 1, &nonce);
+        assert_eq!(
+            block[..16],
+            [
+                0x10, 0xf1, 0xe7, 0xe4, 0xd1, 0x3b, 0x59, 0x15, 0x50, 0x0f, 0xdd, 0x1f, 0xa3, 0x20,
+                0x71, 0xc4
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chacha20_encryption_rfc8439_vector() {
+        // RFC 8439 section 2.4.2 (initial counter 1 == FIRST_DATA_BLOCK_COUNTER)
+        let nonce = [0, 0, 0, 0, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+        let mut data = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.".to_vec();
+        chacha20_xor_at(&test_key(), &nonce, 0, &mut data).expect("xor");
+        assert_eq!(
+            data[..16],
+            [
+                0x6e, 0x2e, 0x35, 0x9a, 0x25, 0x68, 0xf9, 0x80, 0x41, 0xba, 0x07, 0x28, 0xdd, 0x0d,
+                0x69, 0x81
+            ]
+        );
+        assert_eq!(data[data.len() - 2..], [0x87, 0x4d]);
+    }
+
+    #[test]
+    fn test_chacha20_xor_at_is_seekable() {
+        let nonce = [7u8; CHACHA20_NONCE_BYTES];
+        let mut whole = [0xAAu8; 300];
+        chacha20_xor_at(&test_key(), &nonce, 0, &mut whole).expect("xor");
+
+        // The same bytes processed in uneven pieces
+        let mut pieces = [0xAAu8; 300];
+        chacha20_xor_at(&test_key(), &nonce, 0, &mut pieces[..13]).expect("xor");
+        chacha20_xor_at(&test_key(), &nonce, 13, &mut pieces[13..200]).expect("xor");
+        chacha20_xor_at(&test_key(), &nonce, 200, &mut pieces[200..]).expect("xor");
+        assert_eq!(whole, pieces);
+    }
+
+    #[test]
+    fn test_pbkdf2_hmac_sha256_vector() {
+        // RFC 7914 section 11: P="passwd", S="salt", c=1 (first 32 bytes)
+        let derived = pbkdf2_hmac_sha256(b"passwd", b"salt", 1);
+        assert_eq!(
+            derived[..8],
+            [0x55, 0xac, 0x04, 0x6e, 0x56, 0xe3, 0x08, 0x9f]
+        );
+    }
+
+    #[test]
+    fn test_file_round_trip_and_wrong_passphrase() {
+        let session_dir =
+            std::env::temp_dir().join(format!("lines_encrypt_test_{}", std::process::id()));
+        std::fs::create_dir_all(&session_dir).expect("create dir");
+        let item_path = session_dir.join("item.txt");
+        let plain_path = session_dir.join("item_plain.txt");
+        let content = vec![b'z'; 5000];
+        std::fs::write(&item_path, &content).expect("write");
+
+        let cipher = SessionCipher::open_session_with_iterations(&session_dir, b"correct horse", 2)
+            .expect("new session");
+        cipher.encrypt_file_in_place(&item_path).expect("encrypt");
+        assert!(is_encrypted_file(&item_path).expect("check"));
+        let stored = std::fs::read(&item_path).expect("read");
+        assert_eq!(stored.len(), content.len() + ENCRYPTED_TRAILER_BYTES);
+        assert_ne!(stored[..content.len()], content[..]);
+
+        // Encrypting twice is a no-op
+        cipher
+            .encrypt_file_in_place(&item_path)
+            .expect("encrypt again");
+        assert_eq!(std::fs::read(&item_path).expect("read"), stored);
+
+        cipher
+            .decrypt_file_to(&item_path, &plain_path)
+            .expect("decrypt to");
+        assert_eq!(std::fs::read(&plain_path).expect("read"), content);
+
+        // Same passphrase reopens the session; a different one is refused
+        let reopened =
+            SessionCipher::open_session_with_iterations(&session_dir, b"correct horse", 2)
+                .expect("reopen");
+        let refused = SessionCipher::open_session_with_iterations(&session_dir, b"wrong", 2);
+        assert_eq!(
+            refused.err().map(|e| e.kind()),
+            Some(io::ErrorKind::PermissionDenied)
+        );
+
+        // In-memory decryption of the same file
+        let mut in_memory = std::fs::read(&item_path).expect("read");
+        reopened
+            .decrypt_bytes(&mut in_memory)
+            .expect("decrypt bytes");
+        assert_eq!(in_memory, content);
+
+        reopened.decrypt_file_in_place(&item_path).expect("decrypt");
+        assert_eq!(std::fs::read(&item_path).expect("read"), content);
+
+        let _ = std::fs::remove_dir_all(&session_dir);
+    }
+
+    #[test]
+    fn test_read_passphrase_from_handles_backspace_and_cancel() {
+        let mut passphrase = [0u8; 8];
+        let mut input = io::Cursor::new(b"seca\x7fret too long\nignored".to_vec());
+        let length = read_passphrase_from(&mut input, &mut passphrase).expect("read");
+        assert_eq!(&passphrase[..length], b"secret t");
+
+        let mut cancelled = io::Cursor::new(b"abc\x03".to_vec());
+        let result = read_passphrase_from(&mut cancelled, &mut passphrase);
+        assert_eq!(
+            result.err().map(|e| e.kind()),
+            Some(io::ErrorKind::Interrupted)
+        );
+        assert_eq!(passphrase, [0u8; 8]);
+    }
+}
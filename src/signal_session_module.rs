@@ -0,0 +1,615 @@
+//! signal_session_module.rs - Ctrl-C, SIGTERM and SIGHUP keep the session
+//!
+//! ## Project Context
+//! A full-editor session lives in `lines_data/sessions/<timestamp>/`: the
+//! read-copy with every edit, the undo/redo changelogs, the clipboard.
+//! A normal quit removes it; an interrupted one should leave it, plus a
+//! note saying what it belonged to, so `lines --session <dir> <file>`
+//! picks it up again. Without handlers, Ctrl-C (SIGINT), `kill` (SIGTERM)
+//! or a closed terminal window (SIGHUP) end the process wherever it is:
+//! possibly halfway through writing a changelog, with the terminal still
+//! in --raw-keys mode, and with nothing saying where the session is.
+//!
+//! ## What a signal does now
+//! ```text
+//! signal while waiting for input      signal while a command edits
+//!   (almost always)                     (CriticalSection held)
+//!          |                                     |
+//!          |                            remembered; the command
+//!          |                            finishes its writes first
+//!          v                                     v
+//!   restore the terminal settings saved at start-up
+//!   write lines-session-info.txt into the session directory (fsync)
+//!   print where the session is and how to resume
+//!   exit 128 + signal number (130 for Ctrl-C), session directory kept
+//! ```
+//! Edits and changelogs are written to disk as each command runs, so once
+//! no command is mid-write there is nothing else to flush.
+//!
+//! ## Signal safety
+//! A handler may only do async-signal-safe things: no allocation, no
+//! locks, no std I/O. So the main loop *publishes* the session note ahead
+//! of time (`publish_session_snapshot`, every command) into one of two
+//! static buffers and flips an atomic index; the handler reads the
+//! published one and writes it with raw syscalls (open, write, fsync,
+//! close, ioctl, exit_group). The main thread never writes the buffer the
+//! index points at, so a signal arriving mid-publish sees the previous,
+//! complete note.
+//!
+//! ## Panics
+//! The same published note is the last resort for a panic
+//! (`install_panic_session_hook`, set in main): the hook puts the
+//! terminal back, writes the note with a "panic:" line as the emergency
+//! marker, prints the resume command, then hands over to the default
+//! hook (message, backtrace) and the unwind or abort. The hook is not in a
+//! signal handler, so it uses std's file I/O.
+//!
+//! ## No libc
+//! As in raw_terminal_x86_module, the kernel is called directly with
+//! inline assembly: `rt_sigaction` with our own `rt_sigreturn`
+//! trampoline (SA_RESTORER), which is what the C library's `sigaction`
+//! does underneath. Linux x86_64 only, like the rest of the build: on
+//! other targets the syscalls are stubs that fail with ENOSYS, and the
+//! editor does not install the handlers (signals end it as before).
+
+use crate::escape_sequence_module::{BRACKETED_PASTE_OFF, bracketed_paste_active};
+use std::cell::UnsafeCell;
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+
+// ============================================================================
+// LINUX x86_64 CONSTANTS
+// ============================================================================
+
+const SYS_WRITE: u64 = 1;
+const SYS_OPEN: u64 = 2;
+const SYS_CLOSE: u64 = 3;
+const SYS_RT_SIGACTION: u64 = 13;
+const SYS_IOCTL: u64 = 16;
+const SYS_FSYNC: u64 = 74;
+const SYS_EXIT_GROUP: u64 = 231;
+
+const SIGHUP: i32 = 1;
+const SIGINT: i32 = 2;
+const SIGTERM: i32 = 15;
+
+/// Signals that end the editor but keep the session
+const SESSION_SIGNALS: [i32; 3] = [SIGHUP, SIGINT, SIGTERM];
+
+/// sa_flags: our own rt_sigreturn trampoline is in sa_restorer
+const SA_RESTORER: u64 = 0x0400_0000;
+/// sa_flags: reads interrupted while a command holds a CriticalSection
+/// carry on (the signal is acted on when the command is done)
+const SA_RESTART: u64 = 0x1000_0000;
+
+/// open(2) flags: O_WRONLY | O_CREAT | O_TRUNC | O_CLOEXEC
+const OPEN_WRITE_FLAGS: u64 = 0o1 | 0o100 | 0o1000 | 0o2000000;
+/// The note is readable by its owner only, like the session files
+const OPEN_MODE: u64 = 0o600;
+
+const TCGETS: u64 = 0x5401;
+const TCSETS: u64 = 0x5402;
+/// struct termios as TCGETS/TCSETS use it on x86_64 (see raw_terminal_x86_module)
+const TERMIOS_BYTES: usize = 36;
+
+const STDIN_FD: u64 = 0;
+const STDOUT_FD: u64 = 1;
+const STDERR_FD: u64 = 2;
+
+// ============================================================================
+// SNAPSHOT BUFFERS
+// ============================================================================
+
+/// File written into the session directory when a signal ends the editor
+/// (no '_' in the name: read-copy drafts are found by "_<file name>")
+pub const SESSION_INFO_FILE_NAME: &str = "lines-session-info.txt";
+
+/// Longest note path kept, with its terminating NUL
+const SNAPSHOT_PATH_BYTES: usize = 1024;
+/// Longest note kept (longer notes are cut)
+const SNAPSHOT_TEXT_BYTES: usize = 4096;
+/// Longest stderr notice kept
+const SNAPSHOT_NOTICE_BYTES: usize = 2048;
+
+/// What the handler writes: a prepared note and a prepared notice
+#[derive(Clone, Copy)]
+struct Snapshot {
+    /// NUL-terminated path of the note; length 0 = write no note
+    info_path: [u8; SNAPSHOT_PATH_BYTES],
+    info_path_length: usize,
+    info_text: [u8; SNAPSHOT_TEXT_BYTES],
+    info_text_length: usize,
+    notice: [u8; SNAPSHOT_NOTICE_BYTES],
+    notice_length: usize,
+}
+
+impl Snapshot {
+    const EMPTY: Snapshot = Snapshot {
+        info_path: [0; SNAPSHOT_PATH_BYTES],
+        info_path_length: 0,
+        info_text: [0; SNAPSHOT_TEXT_BYTES],
+        info_text_length: 0,
+        notice: [0; SNAPSHOT_NOTICE_BYTES],
+        notice_length: 0,
+    };
+}
+
+/// Two snapshots: the published one and the one being written
+struct SnapshotSlots(UnsafeCell<[Snapshot; 2]>);
+
+// SAFETY: only the main thread writes, and only to the slot that is not
+// published; the handler only reads the published slot (see module doc).
+unsafe impl Sync for SnapshotSlots {}
+
+static SNAPSHOTS: SnapshotSlots = SnapshotSlots(UnsafeCell::new([Snapshot::EMPTY; 2]));
+
+/// 0 = nothing published yet, else published slot index + 1
+static PUBLISHED_SLOT: AtomicUsize = AtomicUsize::new(0);
+
+/// Terminal settings from before the editor changed any
+struct SavedTerminal(UnsafeCell<[u32; TERMIOS_BYTES / 4]>);
+
+// SAFETY: written once in install_session_signal_handlers, before any
+// handler is installed and before TERMINAL_SAVED is set; read-only after.
+unsafe impl Sync for SavedTerminal {}
+
+static SAVED_TERMINAL: SavedTerminal = SavedTerminal(UnsafeCell::new([0; TERMIOS_BYTES / 4]));
+static TERMINAL_SAVED: AtomicBool = AtomicBool::new(false);
+
+static HANDLERS_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Commands writing to disk right now (nested sections count up)
+static CRITICAL_DEPTH: AtomicUsize = AtomicUsize::new(0);
+/// A signal that arrived during a CriticalSection; 0 = none
+static PENDING_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+// ============================================================================
+// PUBLIC INTERFACE
+// ============================================================================
+
+/// Saves the terminal settings and routes SIGHUP, SIGINT and SIGTERM to
+/// the session-keeping exit
+///
+/// Call once the session directory exists; later calls do nothing. A
+/// stdin that is not a terminal is fine (there is just nothing to restore).
+///
+/// Linux x86_64 only: the editor does not call it on other targets.
+///
+/// # Errors
+/// The kernel refused a handler (should not happen for these signals).
+#[cfg_attr(
+    not(all(target_os = "linux", target_arch = "x86_64")),
+    allow(dead_code)
+)]
+pub fn install_session_signal_handlers() -> io::Result<()> {
+    if HANDLERS_INSTALLED.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    // SAFETY: SAVED_TERMINAL is a 36-byte, 4-aligned buffer, exactly what
+    // TCGETS writes on x86_64; no handler that reads it is installed yet.
+    let result = unsafe { syscall3(SYS_IOCTL, STDIN_FD, TCGETS, SAVED_TERMINAL.0.get() as u64) };
+    TERMINAL_SAVED.store(result == 0, Ordering::SeqCst);
+
+    let mut blocked_while_handling: u64 = 0;
+    for signal in SESSION_SIGNALS {
+        blocked_while_handling |= 1 << (signal - 1);
+    }
+    let action = KernelSigaction {
+        handler: on_session_signal as *const () as u64,
+        flags: SA_RESTORER | SA_RESTART,
+        restorer: lines_signal_restorer as *const () as u64,
+        // One handler at a time: a second Ctrl-C waits for the first
+        mask: blocked_while_handling,
+    };
+    for signal in SESSION_SIGNALS {
+        // SAFETY: `action` is a valid kernel sigaction for the duration of
+        // the call, no old action is asked for (null), and the mask size
+        // is the kernel's 8 bytes.
+        let result = unsafe {
+            syscall4(
+                SYS_RT_SIGACTION,
+                signal as u64,
+                &action as *const KernelSigaction as u64,
+                0,
+                8,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::from_raw_os_error(-result as i32));
+        }
+    }
+    Ok(())
+}
+
+/// Prepares what a signal writes: `info_text` into `info_path` (None: no
+/// note, e.g. --secure) and `notice` on stderr
+///
+/// Call before every wait for input; cheap (two buffer copies). Text past
+/// a buffer's size is cut; a note path too long for its buffer means no
+/// note.
+pub fn publish_session_snapshot(info_path: Option<&Path>, info_text: &str, notice: &str) {
+    let next_slot = match PUBLISHED_SLOT.load(Ordering::Acquire) {
+        1 => 1,
+        _ => 0,
+    };
+
+    // SAFETY: the handler only reads the published slot; `next_slot` is
+    // the other one, and only this (main) thread writes snapshots.
+    let snapshot = unsafe { &mut (*SNAPSHOTS.0.get())[next_slot] };
+
+    snapshot.info_path_length = 0;
+    if let Some(path) = info_path {
+        let path_bytes = path.as_os_str().as_bytes();
+        if path_bytes.len() < SNAPSHOT_PATH_BYTES && !path_bytes.contains(&0) {
+            snapshot.info_path[..path_bytes.len()].copy_from_slice(path_bytes);
+            snapshot.info_path[path_bytes.len()] = 0;
+            snapshot.info_path_length = path_bytes.len();
+        }
+    }
+    snapshot.info_text_length = copy_cut(&mut snapshot.info_text, info_text.as_bytes());
+    snapshot.notice_length = copy_cut(&mut snapshot.notice, notice.as_bytes());
+
+    PUBLISHED_SLOT.store(next_slot + 1, Ordering::Release);
+}
+
+/// Holds off the session-keeping exit while a command writes to disk
+///
+/// A signal arriving while any CriticalSection is alive is remembered;
+/// the exit happens when the last one is dropped, so an edit and its
+/// changelog are never cut in half.
+pub struct CriticalSection(());
+
+impl CriticalSection {
+    pub fn enter() -> Self {
+        CRITICAL_DEPTH.fetch_add(1, Ordering::SeqCst);
+        CriticalSection(())
+    }
+}
+
+impl Drop for CriticalSection {
+    fn drop(&mut self) {
+        if CRITICAL_DEPTH.fetch_sub(1, Ordering::SeqCst) == 1 {
+            let signal = PENDING_SIGNAL.swap(0, Ordering::SeqCst);
+            if signal != 0 {
+                exit_keeping_session(signal);
+            }
+        }
+    }
+}
+
+/// Sets the panic hook that keeps and describes the session (main, first)
+///
+/// Before a session is published, a panic only gets the default hook.
+pub fn install_panic_session_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        restore_saved_terminal();
+        default_hook(panic_info);
+
+        // Where in the code: the message itself may hold file contents
+        let location = panic_info
+            .location()
+            .map(|location| format!("{}:{}", location.file(), location.line()))
+            .unwrap_or_else(|| "unknown".to_string());
+        if let Some(notice) = write_session_note_after_panic(&location) {
+            eprint!("{}", notice);
+        }
+    }));
+}
+
+/// Writes the published note plus "panic: <location>"; returns the
+/// notice to show, or None if no session was published
+fn write_session_note_after_panic(location: &str) -> Option<String> {
+    let published = PUBLISHED_SLOT.load(Ordering::Acquire);
+    if published == 0 {
+        return None;
+    }
+    // SAFETY: the published slot is complete; this runs on the thread
+    // that publishes, which is busy panicking, not publishing.
+    let snapshot = unsafe { &(*SNAPSHOTS.0.get())[published - 1] };
+
+    if snapshot.info_path_length > 0 {
+        let info_path = Path::new(std::ffi::OsStr::from_bytes(
+            &snapshot.info_path[..snapshot.info_path_length],
+        ));
+        let mut note = snapshot.info_text[..snapshot.info_text_length].to_vec();
+        note.extend_from_slice(format!("panic: {}\n", location).as_bytes());
+        // Best effort: the process is going down either way
+        if let Ok(mut file) = std::fs::File::create(info_path) {
+            let _ = file.write_all(&note);
+            let _ = file.sync_all();
+        }
+    }
+    Some(String::from_utf8_lossy(&snapshot.notice[..snapshot.notice_length]).into_owned())
+}
+
+// ============================================================================
+// HANDLER (async-signal-safe code only below)
+// ============================================================================
+
+extern "C" fn on_session_signal(signal: i32) {
+    if CRITICAL_DEPTH.load(Ordering::SeqCst) > 0 {
+        PENDING_SIGNAL.store(signal, Ordering::SeqCst);
+        return;
+    }
+    exit_keeping_session(signal);
+}
+
+/// Restores the terminal, writes the published note and notice, exits
+///
+/// Raw syscalls only: safe in a signal handler. Failures are ignored;
+/// there is no one left to report them to.
+fn exit_keeping_session(signal: i32) -> ! {
+    restore_saved_terminal();
+
+    let published = PUBLISHED_SLOT.load(Ordering::Acquire);
+    if published != 0 {
+        // SAFETY: the published slot is complete and is not written while
+        // it is published (see publish_session_snapshot).
+        let snapshot = unsafe { &(*SNAPSHOTS.0.get())[published - 1] };
+        if snapshot.info_path_length > 0 {
+            write_session_info(snapshot, signal);
+        }
+        write_all_raw(STDERR_FD, &snapshot.notice[..snapshot.notice_length]);
+    }
+
+    // SAFETY: exit_group takes one integer and does not return.
+    unsafe {
+        syscall3(SYS_EXIT_GROUP, 128 + signal as u64, 0, 0);
+    }
+    // exit_group cannot fail; satisfy the type checker all the same
+    loop {
+        std::hint::spin_loop();
+    }
+}
+
+/// Puts back the terminal settings saved at install, if any were, and
+/// switches bracketed paste off if Insert mode had it on
+fn restore_saved_terminal() {
+    if bracketed_paste_active() {
+        write_all_raw(STDOUT_FD, BRACKETED_PASTE_OFF);
+    }
+    if TERMINAL_SAVED.load(Ordering::SeqCst) {
+        // SAFETY: SAVED_TERMINAL holds what TCGETS wrote at install time
+        // and is never written again.
+        unsafe {
+            syscall3(SYS_IOCTL, STDIN_FD, TCSETS, SAVED_TERMINAL.0.get() as u64);
+        }
+    }
+}
+
+fn write_session_info(snapshot: &Snapshot, signal: i32) {
+    // SAFETY: info_path is NUL-terminated (publish_session_snapshot).
+    let fd = unsafe {
+        syscall3(
+            SYS_OPEN,
+            snapshot.info_path.as_ptr() as u64,
+            OPEN_WRITE_FLAGS,
+            OPEN_MODE,
+        )
+    };
+    if fd < 0 {
+        return;
+    }
+    let fd = fd as u64;
+    write_all_raw(fd, &snapshot.info_text[..snapshot.info_text_length]);
+    write_all_raw(fd, signal_line(signal));
+    // SAFETY: fsync and close take only the descriptor we opened.
+    unsafe {
+        syscall3(SYS_FSYNC, fd, 0, 0);
+        syscall3(SYS_CLOSE, fd, 0, 0);
+    }
+}
+
+/// Last line of the note: which signal ended the editor
+fn signal_line(signal: i32) -> &'static [u8] {
+    match signal {
+        SIGHUP => b"signal: SIGHUP (terminal closed)\n",
+        SIGINT => b"signal: SIGINT (Ctrl-C)\n",
+        SIGTERM => b"signal: SIGTERM\n",
+        _ => b"signal: other\n",
+    }
+}
+
+/// write(2) until done or an error; bounded by the byte count
+fn write_all_raw(fd: u64, mut bytes: &[u8]) {
+    while !bytes.is_empty() {
+        // SAFETY: `bytes` is valid for reading for its whole length.
+        let written = unsafe { syscall3(SYS_WRITE, fd, bytes.as_ptr() as u64, bytes.len() as u64) };
+        if written <= 0 {
+            return;
+        }
+        bytes = &bytes[(written as usize).min(bytes.len())..];
+    }
+}
+
+/// Copies as much of `source` as fits; returns the length copied
+fn copy_cut(destination: &mut [u8], source: &[u8]) -> usize {
+    let length = source.len().min(destination.len());
+    destination[..length].copy_from_slice(&source[..length]);
+    length
+}
+
+// ============================================================================
+// KERNEL INTERFACE
+// ============================================================================
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+use kernel::{KernelSigaction, lines_signal_restorer, syscall3, syscall4};
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+use kernel_stub::{KernelSigaction, lines_signal_restorer, syscall3, syscall4};
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod kernel {
+    use core::arch::{asm, global_asm};
+
+    /// struct sigaction as the x86_64 kernel reads it (not the C library's)
+    #[repr(C)]
+    pub struct KernelSigaction {
+        pub handler: u64,
+        pub flags: u64,
+        pub restorer: u64,
+        pub mask: u64,
+    }
+
+    // Return path from a handler: the kernel jumps here, which asks it to
+    // restore the interrupted context (rt_sigreturn, syscall 15)
+    global_asm!(
+        ".globl lines_signal_restorer",
+        "lines_signal_restorer:",
+        "mov rax, 15",
+        "syscall",
+    );
+
+    unsafe extern "C" {
+        pub fn lines_signal_restorer();
+    }
+
+    /// Three-argument Linux x86_64 syscall; returns the raw result (-errno)
+    ///
+    /// # Safety
+    /// The arguments must be what syscall `number` expects; pointers must be
+    /// valid for the access the syscall makes.
+    #[inline]
+    pub unsafe fn syscall3(number: u64, first: u64, second: u64, third: u64) -> i64 {
+        let result: i64;
+        // SAFETY: the caller upholds the syscall's contract. The `syscall`
+        // instruction clobbers RCX and R11 and does not touch our stack.
+        unsafe {
+            asm!(
+                "syscall",
+                inlateout("rax") number as i64 => result,
+                in("rdi") first,
+                in("rsi") second,
+                in("rdx") third,
+                out("rcx") _,
+                out("r11") _,
+                options(nostack),
+            );
+        }
+        result
+    }
+
+    /// Four-argument Linux x86_64 syscall (4th argument in R10)
+    ///
+    /// # Safety
+    /// As for `syscall3`.
+    #[inline]
+    pub unsafe fn syscall4(number: u64, first: u64, second: u64, third: u64, fourth: u64) -> i64 {
+        let result: i64;
+        // SAFETY: as in syscall3.
+        unsafe {
+            asm!(
+                "syscall",
+                inlateout("rax") number as i64 => result,
+                in("rdi") first,
+                in("rsi") second,
+                in("rdx") third,
+                in("r10") fourth,
+                out("rcx") _,
+                out("r11") _,
+                options(nostack),
+            );
+        }
+        result
+    }
+}
+
+/// Other targets: no raw syscalls; each one fails with ENOSYS
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+mod kernel_stub {
+    const ENOSYS: i64 = 38;
+
+    /// Built by install, never handed to a kernel
+    #[allow(dead_code)]
+    pub struct KernelSigaction {
+        pub handler: u64,
+        pub flags: u64,
+        pub restorer: u64,
+        pub mask: u64,
+    }
+
+    pub extern "C" fn lines_signal_restorer() {}
+
+    /// # Safety
+    /// Nothing is called; safe for any arguments.
+    pub unsafe fn syscall3(_number: u64, _first: u64, _second: u64, _third: u64) -> i64 {
+        -ENOSYS
+    }
+
+    /// # Safety
+    /// Nothing is called; safe for any arguments.
+    pub unsafe fn syscall4(
+        _number: u64,
+        _first: u64,
+        _second: u64,
+        _third: u64,
+        _fourth: u64,
+    ) -> i64 {
+        -ENOSYS
+    }
+}
+
+#[cfg(test)]
+mod signal_session_tests {
+    use super::*;
+
+    fn published() -> Snapshot {
+        let slot = PUBLISHED_SLOT.load(Ordering::Acquire);
+        assert_ne!(slot, 0, "nothing published");
+        unsafe { (*SNAPSHOTS.0.get())[slot - 1] }
+    }
+
+    /// The snapshot slots are process-wide: one test at a time
+    static SNAPSHOT_TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn test_publish_alternates_slots_and_cuts() {
+        let _lock = SNAPSHOT_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        publish_session_snapshot(Some(Path::new("/tmp/one/info")), "first\n", "n1");
+        let first_slot = PUBLISHED_SLOT.load(Ordering::Acquire);
+        publish_session_snapshot(None, &"x".repeat(SNAPSHOT_TEXT_BYTES + 10), "n2");
+        assert_ne!(PUBLISHED_SLOT.load(Ordering::Acquire), first_slot);
+
+        let snapshot = published();
+        assert_eq!(snapshot.info_path_length, 0);
+        assert_eq!(snapshot.info_text_length, SNAPSHOT_TEXT_BYTES);
+        assert_eq!(&snapshot.notice[..snapshot.notice_length], b"n2");
+
+        publish_session_snapshot(Some(Path::new("/tmp/two/info")), "second\n", "n3");
+        let snapshot = published();
+        assert_eq!(
+            &snapshot.info_path[..snapshot.info_path_length + 1],
+            b"/tmp/two/info\0"
+        );
+        assert_eq!(
+            &snapshot.info_text[..snapshot.info_text_length],
+            b"second\n"
+        );
+    }
+
+    #[test]
+    fn test_panic_note_adds_marker() {
+        let _lock = SNAPSHOT_TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let info_path =
+            std::env::temp_dir().join(format!("lines_panic_note_{}.txt", std::process::id()));
+        publish_session_snapshot(Some(&info_path), "file: a.txt\n", "kept\n");
+
+        let notice = write_session_note_after_panic("src/x.rs:7");
+        assert_eq!(notice.as_deref(), Some("kept\n"));
+        assert_eq!(
+            std::fs::read_to_string(&info_path).expect("note written"),
+            "file: a.txt\npanic: src/x.rs:7\n"
+        );
+        let _ = std::fs::remove_file(&info_path);
+    }
+
+    #[test]
+    fn test_signal_lines_name_the_signal() {
+        assert_eq!(signal_line(SIGINT), b"signal: SIGINT (Ctrl-C)\n");
+        assert!(signal_line(99).starts_with(b"signal: "));
+    }
+}
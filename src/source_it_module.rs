@@ -18,15 +18,25 @@
 //! Embeds source files at compile-time and provides extraction at runtime.
 //! This ensures open-source code remains accessible independent of external repositories.
 //!
+//! Extraction also writes a MANIFEST of SHA-256 checksums computed from the
+//! embedded copies (not the written files), so the extracted tree can be
+//! checked as untampered with: `cd <dir> && sha256sum -c MANIFEST`
+//! SHA-256 comes from checksum_module (vanilla Rust, no OS tools needed).
+//!
 
 use std::error::Error;
 use std::fmt;
 use std::fs;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::SystemTime;
 
+use crate::checksum_module::{SHA256_HEX_LENGTH, Sha256, sha256_to_hex};
+
+/// Name of the integrity manifest written into the extraction directory
+pub const MANIFEST_FILE_NAME: &str = "MANIFEST";
+
 /*
 Example:
 
@@ -80,6 +90,15 @@ impl SourcedFile {
     pub const fn new(path: &'static str, content: &'static str) -> Self {
         Self { path, content }
     }
+
+    /// SHA-256 of the embedded content, as 64 lowercase hex digits
+    pub fn sha256_hex(&self) -> String {
+        let mut sha256 = Sha256::new();
+        sha256.update(self.content.as_bytes());
+        let digest = sha256.finalize();
+        let mut hex_buffer = [0u8; SHA256_HEX_LENGTH];
+        sha256_to_hex(&digest, &mut hex_buffer).to_string()
+    }
 }
 
 /// Custom error type for source extraction operations
@@ -96,13 +115,20 @@ impl fmt::Display for SourceExtractionError {
 
 impl Error for SourceExtractionError {}
 
-/// Extracts embedded source files to a timestamped directory
+/// Extracts embedded source files to a chosen or timestamped directory
 ///
 /// # Arguments
 /// * `crate_name` - Name of the crate being extracted
-/// * `output_path` - Optional output directory (defaults to current working directory)
+/// * `output_path` - Optional destination directory, used as-is. When `None`,
+///   a `source_crate_<name>_<timestamp>` directory in the current working
+///   directory is used.
 /// * `source_files` - Array of files to extract
 ///
+/// # Collision Detection
+/// The destination must not exist yet, or be an empty directory. An existing
+/// file, or a directory with anything in it, is an error: extraction never
+/// overwrites or mixes into existing files.
+///
 /// # Returns
 /// * `Ok(PathBuf)` - Absolute path to the created source directory
 /// * `Err(SourceExtractionError)` - If extraction fails
@@ -139,42 +165,40 @@ pub fn handle_sourceit_command(
         });
     }
 
-    // Determine base output directory
-    let base_path = match output_path {
+    // Determine the extraction directory
+    let extraction_path = match output_path {
         Some(path) => {
             // Convert to absolute path
-            match path.canonicalize() {
-                Ok(p) => p,
-                Err(_) => {
-                    // If path doesn't exist yet, try to get absolute path differently
-                    match std::env::current_dir() {
-                        Ok(cwd) => cwd.join(path),
-                        Err(e) => {
-                            return Err(SourceExtractionError {
-                                message: format!("Failed to determine current directory: {}", e),
-                            });
-                        }
+            if path.is_absolute() {
+                path.to_path_buf()
+            } else {
+                match std::env::current_dir() {
+                    Ok(cwd) => cwd.join(path),
+                    Err(e) => {
+                        return Err(SourceExtractionError {
+                            message: format!("Failed to determine current directory: {}", e),
+                        });
                     }
                 }
             }
         }
         None => {
-            // Use current working directory
-            match std::env::current_dir() {
+            // Use a timestamped directory in the current working directory
+            let base_path = match std::env::current_dir() {
                 Ok(cwd) => cwd,
                 Err(e) => {
                     return Err(SourceExtractionError {
                         message: format!("Failed to get current working directory: {}", e),
                     });
                 }
-            }
+            };
+            let timestamp = create_timestamp();
+            base_path.join(format!("source_crate_{}_{}", crate_name, timestamp))
         }
     };
 
-    // Create timestamped directory name
-    let timestamp = create_timestamp();
-    let dir_name = format!("source_crate_{}_{}", crate_name, timestamp);
-    let extraction_path = base_path.join(dir_name);
+    // Refuse to overwrite or mix into existing files
+    check_extraction_target_is_free(&extraction_path)?;
 
     // Create the extraction directory
     if let Err(e) = fs::create_dir_all(&extraction_path) {
@@ -192,6 +216,13 @@ pub fn handle_sourceit_command(
         }
     }
 
+    // Write the integrity manifest from the embedded copies
+    if let Err(e) = write_manifest(&extraction_path, source_files) {
+        return Err(SourceExtractionError {
+            message: format!("Failed to write {}: {}", MANIFEST_FILE_NAME, e),
+        });
+    }
+
     // Generate SHA256 checksums for extracted files (Linux/macOS only)
     if let Err(e) = generate_sha256_checksums(&extraction_path, source_files) {
         // Non-fatal: just warn if checksums can't be generated
@@ -207,6 +238,42 @@ pub fn handle_sourceit_command(
     }
 }
 
+/// Checks an extraction target is missing or an empty directory
+///
+/// # Returns
+/// * `Ok(())` - Path does not exist, or is an empty directory
+/// * `Err(SourceExtractionError)` - Path is a file, a non-empty directory,
+///   or cannot be inspected
+fn check_extraction_target_is_free(target: &Path) -> Result<(), SourceExtractionError> {
+    if !target.exists() {
+        return Ok(());
+    }
+
+    if !target.is_dir() {
+        return Err(SourceExtractionError {
+            message: format!(
+                "Destination exists and is not a directory: {}",
+                target.display()
+            ),
+        });
+    }
+
+    match fs::read_dir(target) {
+        Ok(mut entries) => {
+            if entries.next().is_some() {
+                Err(SourceExtractionError {
+                    message: format!("Destination directory is not empty: {}", target.display()),
+                })
+            } else {
+                Ok(())
+            }
+        }
+        Err(e) => Err(SourceExtractionError {
+            message: format!("Cannot read destination directory: {}", e),
+        }),
+    }
+}
+
 /// Creates a timestamp string in format YYYYMMDD_HHMMSS
 fn create_timestamp() -> String {
     match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
@@ -264,6 +331,42 @@ fn extract_file(base_path: &Path, sourced_file: &SourcedFile) -> Result<(), Box<
     Ok(())
 }
 
+/// Writes MANIFEST: one `sha256  relative/path` line per embedded file
+///
+/// Hashes are of the embedded content compiled into the binary, so a file
+/// edited after extraction no longer matches. The format is what
+/// `sha256sum -c` / `shasum -a 256 -c` expect when run inside the
+/// extraction directory (no header lines, two spaces, relative paths).
+///
+/// # Arguments
+/// * `extraction_path` - Directory the files were extracted into
+/// * `source_files` - Embedded files (duplicates listed once)
+fn write_manifest(
+    extraction_path: &Path,
+    source_files: &[SourcedFile],
+) -> Result<(), Box<dyn Error>> {
+    let mut manifest_file = fs::File::create(extraction_path.join(MANIFEST_FILE_NAME))?;
+
+    for (index, sourced_file) in source_files.iter().enumerate() {
+        // Same path listed twice in SOURCE_FILES: one entry is enough
+        if source_files[..index]
+            .iter()
+            .any(|earlier| earlier.path == sourced_file.path)
+        {
+            continue;
+        }
+        writeln!(
+            manifest_file,
+            "{}  {}",
+            sourced_file.sha256_hex(),
+            sourced_file.path
+        )?;
+    }
+
+    manifest_file.flush()?;
+    Ok(())
+}
+
 /// Generates SHA256 checksums for extracted files using OS-native commands
 ///
 /// This function creates a SHA256SUMS.txt file containing checksums that can be
@@ -507,6 +610,367 @@ fn calculate_sha256_windows(file_path: &Path) -> Result<String, Box<dyn Error>>
     Ok(hash.to_lowercase()) // Normalize to lowercase for consistency
 }
 
+/// Result of comparing a working tree against the embedded source
+#[derive(Debug, Default)]
+pub struct SourceVerifyReport {
+    /// Files identical to the embedded copy
+    pub matched: Vec<&'static str>,
+    /// Files present but different from the embedded copy
+    pub mismatched: Vec<&'static str>,
+    /// Embedded files with no counterpart in the tree
+    pub missing: Vec<&'static str>,
+}
+
+impl SourceVerifyReport {
+    /// True when every embedded file is present and identical
+    pub fn is_clean(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Compares files in an existing checkout against the embedded copies
+///
+/// Each embedded file is looked up at the same relative path under
+/// `tree_root` and compared byte-by-byte, streaming the on-disk file in
+/// fixed-size chunks (the checkout copy is never loaded whole).
+/// Files in the tree that are not embedded are ignored.
+///
+/// # Arguments
+/// * `tree_root` - Root of the checkout (the directory holding Cargo.toml)
+/// * `source_files` - Embedded files (duplicates compared once)
+///
+/// # Returns
+/// * `Ok(SourceVerifyReport)` - Per-file outcome
+/// * `Err(SourceExtractionError)` - `tree_root` is not a directory, or a
+///   present file cannot be read
+pub fn verify_source_tree(
+    tree_root: &Path,
+    source_files: &[SourcedFile],
+) -> Result<SourceVerifyReport, SourceExtractionError> {
+    if !tree_root.is_dir() {
+        return Err(SourceExtractionError {
+            message: format!("Not a directory: {}", tree_root.display()),
+        });
+    }
+
+    let mut report = SourceVerifyReport::default();
+
+    for (index, sourced_file) in source_files.iter().enumerate() {
+        // Same path listed twice in SOURCE_FILES: compare once
+        if source_files[..index]
+            .iter()
+            .any(|earlier| earlier.path == sourced_file.path)
+        {
+            continue;
+        }
+
+        let file_path = tree_root.join(sourced_file.path);
+        if !file_path.is_file() {
+            report.missing.push(sourced_file.path);
+            continue;
+        }
+
+        match file_matches_content(&file_path, sourced_file.content.as_bytes()) {
+            Ok(true) => report.matched.push(sourced_file.path),
+            Ok(false) => report.mismatched.push(sourced_file.path),
+            Err(e) => {
+                return Err(SourceExtractionError {
+                    message: format!("Failed to read '{}': {}", sourced_file.path, e),
+                });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Streams a file and compares it with expected bytes
+fn file_matches_content(file_path: &Path, expected: &[u8]) -> Result<bool, Box<dyn Error>> {
+    const COMPARE_CHUNK_SIZE: usize = 4096;
+    let mut buffer = [0u8; COMPARE_CHUNK_SIZE];
+
+    // Different length: no need to read
+    if fs::metadata(file_path)?.len() != expected.len() as u64 {
+        return Ok(false);
+    }
+
+    let mut file = fs::File::open(file_path)?;
+    let mut compared: usize = 0;
+
+    // Bounded: one chunk per iteration, plus the EOF read
+    let max_iterations = (expected.len() / COMPARE_CHUNK_SIZE) + 2;
+    for _ in 0..max_iterations {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            return Ok(compared == expected.len());
+        }
+        if compared + bytes_read > expected.len()
+            || buffer[..bytes_read] != expected[compared..compared + bytes_read]
+        {
+            return Ok(false);
+        }
+        compared += bytes_read;
+    }
+
+    // File grew while reading
+    Ok(false)
+}
+
+/// Verifies a checkout against the embedded source and prints a report
+///
+/// # Arguments
+/// * `tree_root` - Root of the checkout to verify
+/// * `source_files` - Array of embedded files
+///
+/// # Returns
+/// * `Ok(true)` - Every embedded file present and identical
+/// * `Ok(false)` - At least one file mismatched or missing
+/// * `Err(SourceExtractionError)` - Verification could not run
+pub fn handle_source_verify_command(
+    tree_root: &Path,
+    source_files: &[SourcedFile],
+) -> Result<bool, SourceExtractionError> {
+    let report = verify_source_tree(tree_root, source_files)?;
+
+    for path in &report.matched {
+        println!("match     {}", path);
+    }
+    for path in &report.mismatched {
+        println!("MISMATCH  {}", path);
+    }
+    for path in &report.missing {
+        println!("MISSING   {}", path);
+    }
+    println!(
+        "{} match, {} mismatch, {} missing",
+        report.matched.len(),
+        report.mismatched.len(),
+        report.missing.len()
+    );
+
+    Ok(report.is_clean())
+}
+
+// ============================================================================
+// Single-Archive Output (ustar)
+// ============================================================================
+
+/// ustar block size: headers and data are padded to this
+const TAR_BLOCK_SIZE: usize = 512;
+
+/// Writes embedded source files into one uncompressed ustar `.tar` file
+///
+/// A minimal POSIX ustar writer in vanilla Rust: regular files only, mode
+/// 0644, owner 0/0. Every entry sits under a top-level directory named
+/// `archive_stem`, and a MANIFEST entry (same format as on extraction) is
+/// added last, so `tar -xf` then `sha256sum -c MANIFEST` verifies it.
+///
+/// # Arguments
+/// * `archive_stem` - File name without `.tar`, also the top-level directory
+/// * `output_dir` - Directory for the archive, created if missing (defaults to
+///   current working directory)
+/// * `source_files` - Array of files to archive (duplicates archived once)
+///
+/// # Collision Detection
+/// An existing file with the archive's name is an error (never overwritten).
+///
+/// # Returns
+/// * `Ok(PathBuf)` - Absolute path to the created `.tar` file
+/// * `Err(SourceExtractionError)` - If the archive cannot be written
+pub fn handle_sourceit_archive_command(
+    archive_stem: &str,
+    output_dir: Option<&Path>,
+    source_files: &[SourcedFile],
+) -> Result<PathBuf, SourceExtractionError> {
+    if archive_stem.is_empty() {
+        return Err(SourceExtractionError {
+            message: "Archive name cannot be empty".to_string(),
+        });
+    }
+
+    if source_files.is_empty() {
+        return Err(SourceExtractionError {
+            message: "No source files provided for extraction".to_string(),
+        });
+    }
+
+    let base_path = match output_dir {
+        Some(path) => path.to_path_buf(),
+        None => match std::env::current_dir() {
+            Ok(cwd) => cwd,
+            Err(e) => {
+                return Err(SourceExtractionError {
+                    message: format!("Failed to get current working directory: {}", e),
+                });
+            }
+        },
+    };
+    // As for --source DIR: the destination need not exist yet
+    if let Err(e) = fs::create_dir_all(&base_path) {
+        return Err(SourceExtractionError {
+            message: format!("Failed to create archive directory: {}", e),
+        });
+    }
+    let archive_path = base_path.join(format!("{}.tar", archive_stem));
+
+    let mtime = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs(),
+        Err(_) => 0,
+    };
+
+    // create_new: refuse to overwrite an existing archive
+    let mut archive_file = match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&archive_path)
+    {
+        Ok(file) => file,
+        Err(e) => {
+            return Err(SourceExtractionError {
+                message: format!("Cannot create archive {}: {}", archive_path.display(), e),
+            });
+        }
+    };
+
+    if let Err(e) = write_ustar_entries(&mut archive_file, archive_stem, source_files, mtime) {
+        // Do not leave a truncated archive behind
+        drop(archive_file);
+        let _ = fs::remove_file(&archive_path);
+        return Err(SourceExtractionError {
+            message: format!("Failed to write archive: {}", e),
+        });
+    }
+
+    match archive_path.canonicalize() {
+        Ok(p) => Ok(p),
+        Err(e) => Err(SourceExtractionError {
+            message: format!("Failed to get absolute path of archive: {}", e),
+        }),
+    }
+}
+
+/// Writes all entries, the MANIFEST entry, and the end-of-archive marker
+fn write_ustar_entries(
+    writer: &mut impl Write,
+    archive_stem: &str,
+    source_files: &[SourcedFile],
+    mtime: u64,
+) -> Result<(), Box<dyn Error>> {
+    let mut manifest = String::new();
+
+    for (index, sourced_file) in source_files.iter().enumerate() {
+        // Same path listed twice in SOURCE_FILES: archive once
+        if source_files[..index]
+            .iter()
+            .any(|earlier| earlier.path == sourced_file.path)
+        {
+            continue;
+        }
+
+        let entry_path = format!("{}/{}", archive_stem, sourced_file.path);
+        write_ustar_file(writer, &entry_path, sourced_file.content.as_bytes(), mtime)?;
+
+        manifest.push_str(&sourced_file.sha256_hex());
+        manifest.push_str("  ");
+        manifest.push_str(sourced_file.path);
+        manifest.push('\n');
+    }
+
+    let manifest_path = format!("{}/{}", archive_stem, MANIFEST_FILE_NAME);
+    write_ustar_file(writer, &manifest_path, manifest.as_bytes(), mtime)?;
+
+    // End of archive: two zero blocks
+    writer.write_all(&[0u8; TAR_BLOCK_SIZE * 2])?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes one regular-file entry: header block, data, zero padding
+fn write_ustar_file(
+    writer: &mut impl Write,
+    entry_path: &str,
+    data: &[u8],
+    mtime: u64,
+) -> Result<(), Box<dyn Error>> {
+    let header = build_ustar_header(entry_path, data.len() as u64, mtime)?;
+    writer.write_all(&header)?;
+    writer.write_all(data)?;
+
+    let remainder = data.len() % TAR_BLOCK_SIZE;
+    if remainder != 0 {
+        writer.write_all(&[0u8; TAR_BLOCK_SIZE][..TAR_BLOCK_SIZE - remainder])?;
+    }
+    Ok(())
+}
+
+/// Builds a ustar header block for a regular file
+///
+/// # ustar Layout (offset, length)
+/// name (0,100) mode (100,8) uid (108,8) gid (116,8) size (124,12)
+/// mtime (136,12) chksum (148,8) typeflag (156,1) magic (257,6)
+/// version (263,2) prefix (345,155). Numbers are zero-padded octal
+/// ending in NUL. Paths over 100 bytes are split at a '/' into
+/// prefix + name.
+fn build_ustar_header(
+    entry_path: &str,
+    size: u64,
+    mtime: u64,
+) -> Result<[u8; TAR_BLOCK_SIZE], Box<dyn Error>> {
+    let mut header = [0u8; TAR_BLOCK_SIZE];
+
+    let (prefix, name) = split_ustar_path(entry_path)?;
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    write_octal_field(&mut header[100..108], 0o644)?;
+    write_octal_field(&mut header[108..116], 0)?;
+    write_octal_field(&mut header[116..124], 0)?;
+    write_octal_field(&mut header[124..136], size)?;
+    write_octal_field(&mut header[136..148], mtime)?;
+    header[156] = b'0'; // regular file
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // Checksum: sum of all header bytes with the chksum field as spaces,
+    // stored as six octal digits, NUL, space
+    header[148..156].copy_from_slice(b"        ");
+    let checksum: u64 = header.iter().map(|&byte| byte as u64).sum();
+    write_octal_field(&mut header[148..155], checksum)?;
+    header[155] = b' ';
+
+    Ok(header)
+}
+
+/// Splits a path into ustar (prefix, name), both as short as the format needs
+fn split_ustar_path(entry_path: &str) -> Result<(&str, &str), Box<dyn Error>> {
+    if entry_path.len() <= 100 {
+        return Ok(("", entry_path));
+    }
+
+    // Longest prefix (<= 155 bytes) ending at a '/' that leaves name <= 100
+    for (slash_index, _) in entry_path.match_indices('/').rev() {
+        let prefix = &entry_path[..slash_index];
+        let name = &entry_path[slash_index + 1..];
+        if prefix.len() <= 155 && name.len() <= 100 && !name.is_empty() {
+            return Ok((prefix, name));
+        }
+    }
+
+    Err(format!("Path too long for ustar: {}", entry_path).into())
+}
+
+/// Writes a zero-padded octal number ending in NUL filling the whole field
+fn write_octal_field(field: &mut [u8], value: u64) -> Result<(), Box<dyn Error>> {
+    let digits = field.len() - 1;
+    let octal = format!("{:0width$o}", value, width = digits);
+    if octal.len() > digits {
+        return Err(format!("Value {} too large for ustar field", value).into());
+    }
+    field[..digits].copy_from_slice(octal.as_bytes());
+    field[digits] = 0;
+    Ok(())
+}
+
 #[cfg(test)]
 mod sourceit_tests {
     use super::*;
@@ -558,10 +1022,12 @@ mod sourceit_tests {
             Ok(dir) => dir,
             Err(_) => return, // Skip test if we can't get temp dir
         };
+        let destination = temp_dir.join("source_it_test_verification");
+        let _ = fs::remove_dir_all(&destination);
 
         // Extract files
         let extracted_path =
-            match handle_sourceit_command("test_verification", Some(&temp_dir), &test_files) {
+            match handle_sourceit_command("test_verification", Some(&destination), &test_files) {
                 Ok(path) => path,
                 Err(_) => return, // Skip test if extraction fails
             };
@@ -570,6 +1036,212 @@ mod sourceit_tests {
         let _ = fs::remove_dir_all(&extracted_path);
     }
 
+    /// Test MANIFEST lists embedded-content hashes by relative path
+    #[test]
+    fn test_manifest_written_from_embedded_content() {
+        let test_files = vec![
+            SourcedFile::new("test1.txt", "Hello World"),
+            SourcedFile::new("subdir/test2.txt", ""),
+            SourcedFile::new("test1.txt", "Hello World"),
+        ];
+
+        let temp_dir = match std::env::temp_dir().canonicalize() {
+            Ok(dir) => dir,
+            Err(_) => return, // Skip test if we can't get temp dir
+        };
+        let destination = temp_dir.join("source_it_test_manifest");
+        let _ = fs::remove_dir_all(&destination);
+
+        let extracted_path =
+            match handle_sourceit_command("test_manifest", Some(&destination), &test_files) {
+                Ok(path) => path,
+                Err(_) => return, // Skip test if extraction fails
+            };
+
+        let manifest = fs::read_to_string(extracted_path.join(MANIFEST_FILE_NAME));
+        let _ = fs::remove_dir_all(&extracted_path);
+
+        let manifest = manifest.expect("MANIFEST should be written");
+        assert_eq!(
+            manifest,
+            "a591a6d40bf420404a011733cfb7b190d62c65bf0bcda32b57b277d9ad9f146e  test1.txt\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  subdir/test2.txt\n"
+        );
+    }
+
+    /// Test a chosen destination must be missing or empty
+    #[test]
+    fn test_non_empty_destination_is_refused() {
+        let test_files = vec![SourcedFile::new("test.txt", "content")];
+
+        let temp_dir = match std::env::temp_dir().canonicalize() {
+            Ok(dir) => dir,
+            Err(_) => return, // Skip test if we can't get temp dir
+        };
+        let destination = temp_dir.join("source_it_test_collision");
+        let _ = fs::remove_dir_all(&destination);
+        if fs::create_dir_all(&destination).is_err() {
+            return; // Skip test if we can't create the directory
+        }
+
+        // Empty directory: accepted
+        let extracted = handle_sourceit_command("test_collision", Some(&destination), &test_files);
+        assert!(extracted.is_ok());
+
+        // Now non-empty: refused, existing file untouched
+        let _ = fs::write(destination.join("test.txt"), "kept");
+        let second = handle_sourceit_command("test_collision", Some(&destination), &test_files);
+        assert!(second.is_err());
+        assert_eq!(
+            fs::read_to_string(destination.join("test.txt")).unwrap_or_default(),
+            "kept"
+        );
+
+        // A file as destination: refused
+        let file_destination = destination.join("test.txt");
+        let third = handle_sourceit_command("test_collision", Some(&file_destination), &test_files);
+        assert!(third.is_err());
+
+        let _ = fs::remove_dir_all(&destination);
+    }
+
+    /// Test verifying a tree reports match, mismatch, and missing files
+    #[test]
+    fn test_verify_source_tree() {
+        let embedded = vec![
+            SourcedFile::new("same.txt", "same content"),
+            SourcedFile::new("sub/changed.txt", "original"),
+            SourcedFile::new("gone.txt", "not in tree"),
+        ];
+
+        let temp_dir = match std::env::temp_dir().canonicalize() {
+            Ok(dir) => dir,
+            Err(_) => return, // Skip test if we can't get temp dir
+        };
+        let tree_root = temp_dir.join("source_it_test_verify_tree");
+        let _ = fs::remove_dir_all(&tree_root);
+        if fs::create_dir_all(tree_root.join("sub")).is_err() {
+            return; // Skip test if we can't create the directory
+        }
+        let _ = fs::write(tree_root.join("same.txt"), "same content");
+        let _ = fs::write(tree_root.join("sub/changed.txt"), "0riginal");
+        let _ = fs::write(tree_root.join("extra.txt"), "not embedded, ignored");
+
+        let report = verify_source_tree(&tree_root, &embedded);
+        let _ = fs::remove_dir_all(&tree_root);
+
+        let report = report.expect("Verification should run");
+        assert_eq!(report.matched, vec!["same.txt"]);
+        assert_eq!(report.mismatched, vec!["sub/changed.txt"]);
+        assert_eq!(report.missing, vec!["gone.txt"]);
+        assert!(!report.is_clean());
+
+        // Not a directory: error
+        assert!(verify_source_tree(&tree_root.join("nowhere"), &embedded).is_err());
+    }
+
+    /// Test ustar archive layout: headers, checksums, data, end marker
+    #[test]
+    fn test_ustar_archive_layout() {
+        let long_name = format!("src/{}.rs", "n".repeat(90));
+        let leaked_long_name: &'static str = Box::leak(long_name.into_boxed_str());
+        let test_files = vec![
+            SourcedFile::new("a.txt", "Hello World"),
+            SourcedFile::new(leaked_long_name, ""),
+        ];
+
+        let mut archive: Vec<u8> = Vec::new();
+        write_ustar_entries(&mut archive, "stem_1.0", &test_files, 0o1234)
+            .expect("Archive should be written");
+
+        // a.txt: header + 1 data block; long name: header only;
+        // MANIFEST: header + 1 data block; end marker: 2 blocks
+        assert_eq!(archive.len(), TAR_BLOCK_SIZE * 7);
+        assert!(archive[TAR_BLOCK_SIZE * 5..].iter().all(|&byte| byte == 0));
+
+        let header = &archive[..TAR_BLOCK_SIZE];
+        assert_eq!(&header[..16], b"stem_1.0/a.txt\0\0");
+        assert_eq!(&header[124..136], b"00000000013\0");
+        assert_eq!(&header[136..148], b"00000001234\0");
+        assert_eq!(&header[257..263], b"ustar\0");
+        assert_eq!(header[156], b'0');
+
+        // Checksum: header sum with chksum field counted as spaces
+        let stored = std::str::from_utf8(&header[148..154]).unwrap();
+        let stored = u64::from_str_radix(stored, 8).unwrap();
+        let computed: u64 = header
+            .iter()
+            .enumerate()
+            .map(|(i, &byte)| {
+                if (148..156).contains(&i) {
+                    32
+                } else {
+                    byte as u64
+                }
+            })
+            .sum();
+        assert_eq!(stored, computed);
+
+        assert_eq!(
+            &archive[TAR_BLOCK_SIZE..TAR_BLOCK_SIZE + 11],
+            b"Hello World"
+        );
+
+        // Long path split into prefix + name
+        let long_header = &archive[TAR_BLOCK_SIZE * 2..TAR_BLOCK_SIZE * 3];
+        assert_eq!(&long_header[345..357], b"stem_1.0/src");
+        assert_eq!(long_header[0], b'n');
+
+        // MANIFEST entry is last
+        let manifest_header = &archive[TAR_BLOCK_SIZE * 3..TAR_BLOCK_SIZE * 4];
+        assert_eq!(&manifest_header[..17], b"stem_1.0/MANIFEST");
+    }
+
+    /// Test archive is not written over an existing file
+    #[test]
+    fn test_archive_refuses_existing_file() {
+        let test_files = vec![SourcedFile::new("test.txt", "content")];
+
+        let temp_dir = match std::env::temp_dir().canonicalize() {
+            Ok(dir) => dir,
+            Err(_) => return, // Skip test if we can't get temp dir
+        };
+        let archive_path = temp_dir.join("source_it_test_archive.tar");
+        let _ = fs::remove_file(&archive_path);
+
+        let first =
+            handle_sourceit_archive_command("source_it_test_archive", Some(&temp_dir), &test_files);
+        assert!(first.is_ok());
+        let second =
+            handle_sourceit_archive_command("source_it_test_archive", Some(&temp_dir), &test_files);
+        assert!(second.is_err());
+
+        let _ = fs::remove_file(&archive_path);
+    }
+
+    /// Test archive destination directory is created when missing
+    #[test]
+    fn test_archive_creates_missing_directory() {
+        let test_files = vec![SourcedFile::new("test.txt", "content")];
+
+        let temp_dir = match std::env::temp_dir().canonicalize() {
+            Ok(dir) => dir,
+            Err(_) => return, // Skip test if we can't get temp dir
+        };
+        let destination = temp_dir.join("source_it_test_archive_dir").join("nested");
+        let _ = fs::remove_dir_all(temp_dir.join("source_it_test_archive_dir"));
+
+        let archived = handle_sourceit_archive_command(
+            "source_it_test_archive",
+            Some(&destination),
+            &test_files,
+        );
+        assert!(archived.is_ok());
+        assert!(destination.join("source_it_test_archive.tar").is_file());
+
+        let _ = fs::remove_dir_all(temp_dir.join("source_it_test_archive_dir"));
+    }
+
     /// Test content verification with modified file
     #[test]
     fn test_content_verification_detects_changes() {
@@ -580,10 +1252,12 @@ mod sourceit_tests {
             Ok(dir) => dir,
             Err(_) => return, // Skip test if we can't get temp dir
         };
+        let destination = temp_dir.join("source_it_test_modification");
+        let _ = fs::remove_dir_all(&destination);
 
         // Extract files
         let extracted_path =
-            match handle_sourceit_command("test_modification", Some(&temp_dir), &test_files) {
+            match handle_sourceit_command("test_modification", Some(&destination), &test_files) {
                 Ok(path) => path,
                 Err(_) => return, // Skip test if extraction fails
             };
@@ -0,0 +1,267 @@
+//! stty_key_input_module.rs - Opt-in single-keypress commands (--raw-keys)
+//!
+//! ## Project Context
+//! Lines normally reads whole lines: a command acts when Enter is pressed
+//! (the "+Enter" system). That stays the default because the terminal is
+//! never left in an odd state and every mode reads input the same way.
+//! With `--raw-keys`, Normal and Visual mode read keypresses instead, so
+//! `j` moves as soon as it is pressed.
+//!
+//! ## How
+//! The terminal is switched with the system `stty` tool, run on /dev/tty:
+//! ```text
+//! stty -g                              save the current settings
+//! stty -icanon -echo min 1 time 0      keypresses, no echo
+//! stty <saved>                         restore
+//! ```
+//! `stty size` also gives the terminal width where a mode needs it
+//! (memo mode wraps its lines with it).
+//!
+//! No terminal crate and no ioctl code: stty exists on Linux, the BSDs,
+//! macOS and Termux. `SttyKeyInput` restores the saved settings when it is
+//! dropped, so leaving the editor by quitting, by an error (`?`), or by a
+//! panic unwinding all give the terminal back as it was.
+//!
+//! ## Keys to commands
+//! `read_key_command` collects keypresses until they make a command; the
+//! caller's keymap decides when (see keymap_module):
+//! ```text
+//! j          acts at once             5j      count, then the key
+//! gg  ge     g waits for one key      g45⏎    g + digits wait for Enter
+//! d   dd     d waits briefly for a second d, then deletes the line
+//! :wq⏎       ':' reads a whole line as in "+Enter" mode (any command)
+//! Enter      alone: repeat the last command; after keys: run them now
+//! Backspace  drops the last pending key
+//! ```
+
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+use crate::keymap_module::{KEY_SEQUENCE_TIMEOUT_DECISECONDS, KeyWait};
+
+/// Most bytes one keypress command collects before it is run anyway
+pub const MAX_KEY_COMMAND_BYTES: usize = 32;
+
+/// Most reads while collecting one command (keys, Backspaces, ...)
+const MAX_KEY_READS_PER_COMMAND: usize = 256;
+
+/// Bytes read per keypress read (fast typing or a paste can bring several)
+const KEY_CHUNK_BYTES: usize = 16;
+
+/// Terminal in keypress mode while active; restores its settings on drop
+pub struct SttyKeyInput {
+    /// `stty -g` output from before keypress mode was first switched on
+    saved_settings: String,
+    /// Whether keypress (non-canonical, no echo) mode is on right now
+    keys_active: Cell<bool>,
+    /// Keys read past the end of the last command, for the next one
+    carried_keys: Cell<[u8; KEY_CHUNK_BYTES]>,
+    carried_length: Cell<usize>,
+}
+
+impl SttyKeyInput {
+    /// Saves the terminal settings and switches keypress mode on
+    ///
+    /// # Errors
+    /// stdin is not a terminal, /dev/tty cannot be opened, or stty is
+    /// missing or fails. The terminal is unchanged in every error case.
+    pub fn enable() -> io::Result<Self> {
+        if !io::stdin().is_terminal() {
+            return Err(io::Error::other("stdin is not a terminal"));
+        }
+        let saved_settings = run_stty(&["-g"])?;
+        if saved_settings.is_empty() {
+            return Err(io::Error::other("stty -g printed no settings"));
+        }
+
+        let key_input = SttyKeyInput {
+            saved_settings,
+            keys_active: Cell::new(false),
+            carried_keys: Cell::new([0u8; KEY_CHUNK_BYTES]),
+            carried_length: Cell::new(0),
+        };
+        key_input.set_key_mode(true)?;
+        Ok(key_input)
+    }
+
+    /// Switches between keypress mode (true) and line mode (false)
+    ///
+    /// Runs stty only when the mode actually changes.
+    pub fn set_key_mode(&self, keys: bool) -> io::Result<()> {
+        if self.keys_active.get() == keys {
+            return Ok(());
+        }
+        if keys {
+            run_stty(&["-icanon", "-echo", "min", "1", "time", "0"])?;
+        } else {
+            run_stty(&[self.saved_settings.as_str()])?;
+        }
+        self.keys_active.set(keys);
+        Ok(())
+    }
+
+    /// Sets how long a keypress read waits: Some(tenths of a second), after
+    /// which it returns 0 bytes, or None to wait for a key
+    fn set_key_timeout(&self, deciseconds: Option<u8>) -> io::Result<()> {
+        let settings = match deciseconds {
+            Some(deciseconds) => [
+                "min".to_string(),
+                "0".into(),
+                "time".into(),
+                deciseconds.to_string(),
+            ],
+            None => ["min".to_string(), "1".into(), "time".into(), "0".into()],
+        };
+        run_stty(&settings.each_ref().map(String::as_str))?;
+        Ok(())
+    }
+
+    /// Keeps keys read past the end of a command for the next read
+    fn carry(&self, keys: &[u8]) {
+        let length = keys.len().min(KEY_CHUNK_BYTES);
+        let mut carried = [0u8; KEY_CHUNK_BYTES];
+        carried[..length].copy_from_slice(&keys[..length]);
+        self.carried_keys.set(carried);
+        self.carried_length.set(length);
+    }
+
+    /// Moves any carried keys into `buffer`; returns how many
+    fn take_carried(&self, buffer: &mut [u8; KEY_CHUNK_BYTES]) -> usize {
+        let length = self.carried_length.replace(0);
+        buffer[..length].copy_from_slice(&self.carried_keys.get()[..length]);
+        length
+    }
+}
+
+impl Drop for SttyKeyInput {
+    fn drop(&mut self) {
+        // Nothing more can be done here if stty fails; `stty sane` by hand
+        let _ = self.set_key_mode(false);
+    }
+}
+
+/// Runs stty on the controlling terminal and returns its trimmed stdout
+fn run_stty(arguments: &[&str]) -> io::Result<String> {
+    let tty = File::open("/dev/tty")?;
+    let output = Command::new("stty")
+        .args(arguments)
+        .stdin(Stdio::from(tty))
+        .stderr(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("stty failed"));
+    }
+    String::from_utf8(output.stdout)
+        .map(|settings| settings.trim().to_string())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "stty output not UTF-8"))
+}
+
+/// Width of the controlling terminal in columns (`stty size`), if known
+///
+/// None when there is no terminal, stty fails, or it reports 0 columns
+/// (some serial and emulated terminals).
+pub fn terminal_columns() -> Option<usize> {
+    let size = run_stty(&["size"]).ok()?;
+    let columns = size.split_whitespace().nth(1)?.parse::<usize>().ok()?;
+    (columns > 0).then_some(columns)
+}
+
+/// Reads keypresses from `input` until they make one command
+///
+/// The command is written to `line` as the "+Enter" reader would have
+/// seen it, without the Enter ("5j", "gg", "\x1b[A"). `keys_wait` says,
+/// for the keys so far, whether to run them or wait (see keymap_module).
+/// A key read past the end of the command is kept for the next call.
+/// ':' switches to line mode for one whole line.
+///
+/// # Returns
+/// Bytes written to `line`: 0 for Enter alone (repeat) or end of input.
+pub fn read_key_command<R: Read>(
+    input: &mut R,
+    key_input: &SttyKeyInput,
+    line: &mut [u8],
+    keys_wait: impl Fn(&[u8]) -> KeyWait,
+) -> io::Result<usize> {
+    let capacity = line.len().min(MAX_KEY_COMMAND_BYTES);
+    let mut pending_length = 0;
+    let mut last_wait = KeyWait::NeedMore;
+    let mut key_buffer = [0u8; KEY_CHUNK_BYTES];
+
+    for _ in 0..MAX_KEY_READS_PER_COMMAND {
+        let bytes_read = match key_input.take_carried(&mut key_buffer) {
+            0 if last_wait == KeyWait::ReadyAfterTimeout => {
+                key_input.set_key_timeout(Some(KEY_SEQUENCE_TIMEOUT_DECISECONDS))?;
+                let timed_read = input.read(&mut key_buffer);
+                key_input.set_key_timeout(None)?;
+                match timed_read? {
+                    // Timed out: the shorter command runs
+                    0 => return Ok(pending_length),
+                    bytes_read => bytes_read,
+                }
+            }
+            0 => input.read(&mut key_buffer)?,
+            carried => carried,
+        };
+        if bytes_read == 0 {
+            return Ok(pending_length);
+        }
+        let keys = &key_buffer[..bytes_read];
+
+        for (index, &key) in keys.iter().enumerate() {
+            match key {
+                // Escape, arrows and other key sequences arrive in one read;
+                // they replace any keys pending
+                0x1B => {
+                    let length = (bytes_read - index).min(line.len());
+                    line[..length].copy_from_slice(&keys[index..index + length]);
+                    return Ok(length);
+                }
+                b'\n' | b'\r' => {
+                    key_input.carry(&keys[index + 1..]);
+                    return Ok(pending_length);
+                }
+                0x7F | 0x08 => {
+                    pending_length = pending_length.saturating_sub(1);
+                    last_wait = KeyWait::NeedMore;
+                }
+                b':' if pending_length == 0 => {
+                    key_input.set_key_mode(false)?;
+                    print!(":");
+                    io::stdout().flush()?;
+                    let line_length = input.read(line)?;
+                    key_input.set_key_mode(true)?;
+                    return Ok(line_length);
+                }
+                _ if pending_length >= capacity => {
+                    key_input.carry(&keys[index..]);
+                    return Ok(pending_length);
+                }
+                _ => {
+                    line[pending_length] = key;
+                    pending_length += 1;
+
+                    match keys_wait(&line[..pending_length]) {
+                        KeyWait::NeedMore => last_wait = KeyWait::NeedMore,
+                        KeyWait::ReadyAfterTimeout => last_wait = KeyWait::ReadyAfterTimeout,
+                        KeyWait::Ready => {
+                            key_input.carry(&keys[index + 1..]);
+                            return Ok(pending_length);
+                        }
+                        // "d" then "j": run the "d", keep the "j"
+                        KeyWait::NoMatch if last_wait == KeyWait::ReadyAfterTimeout => {
+                            key_input.carry(&keys[index..]);
+                            return Ok(pending_length - 1);
+                        }
+                        KeyWait::NoMatch => {
+                            key_input.carry(&keys[index + 1..]);
+                            return Ok(pending_length);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(pending_length)
+}
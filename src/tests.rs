@@ -261,6 +261,62 @@ pub fn create_test_files_with_id(_test_name: &str) -> io::Result<Vec<PathBuf>> {
     Ok(test_files)
 }
 
+/// Editor test helpers shared by the test modules below
+#[cfg(test)]
+mod editor_test_helpers {
+    use super::*;
+
+    /// A fresh `lines_{name}_{pid}` directory in the temp dir holding
+    /// `text.txt` with `content`, and an editor on that file: window
+    /// built, cursor on the first text cell of row 0
+    ///
+    /// # Returns
+    /// * `(state, test_dir, file_path)` - Remove `test_dir` when done
+    pub fn state_for(name: &str, content: &str) -> (EditorState, PathBuf, PathBuf) {
+        let test_dir = std::env::temp_dir().join(format!("lines_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("text.txt");
+        std::fs::write(&file_path, content).expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+        state.cursor.tui_visual_col = (0..state.effective_cols)
+            .find(|&col| matches!(state.get_row_col_file_position(0, col), Ok(Some(_))))
+            .expect("row 0 has a mapped cell");
+        (state, test_dir, file_path)
+    }
+
+    /// As `state_for`, with `test_dir` as the session directory (and its
+    /// `clipboard/` made), for yank and put
+    pub fn session_state_for(name: &str, content: &str) -> (EditorState, PathBuf, PathBuf) {
+        let (mut state, test_dir, file_path) = state_for(name, content);
+        std::fs::create_dir_all(test_dir.join("clipboard")).expect("create clipboard dir");
+        state.session_directory_path = Some(test_dir.clone());
+        (state, test_dir, file_path)
+    }
+
+    /// Absolute file byte under the cursor
+    pub fn cursor_byte(state: &EditorState) -> u64 {
+        state
+            .get_row_col_file_position(state.cursor.tui_row, state.cursor.tui_visual_col)
+            .expect("lookup")
+            .expect("cursor on a file position")
+            .byte_offset_linear_file_absolute_position
+    }
+
+    /// The info bar message, up to its NUL terminator
+    pub fn message(state: &EditorState) -> String {
+        let length = state
+            .info_bar_message_buffer
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(state.info_bar_message_buffer.len());
+        String::from_utf8_lossy(&state.info_bar_message_buffer[..length]).into_owned()
+    }
+}
+
 /// Diagnostic function to print contents of test files
 #[cfg(test)]
 // fn print_test_file_contents(file_path: &Path) -> io::Result<()> {
@@ -519,6 +575,99 @@ mod revised_critical_distinction_tests {
 mod timestamp_tests {
     use super::*;
 
+    #[test]
+    fn test_archive_timestamp_fields_are_zero_padded() {
+        // {:02} pads with '0' as format! does, not with spaces
+        assert_eq!(stack_format_it("{:02}_{:02}", &["7", "45"], "x"), "07_45");
+        assert_eq!(stack_format_it("{:>3}|", &["7"], "x"), "  7|");
+
+        let new_year = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_704_070_805);
+        assert_eq!(
+            createarchive_timestamp_with_precision(new_year, false),
+            "2024_24_01_01_01_00_05"
+        );
+    }
+
+    #[test]
+    fn test_timestamp_format_choices() {
+        assert_eq!(timestamp_format_from(None), TimestampFormat::Lines);
+        assert_eq!(
+            timestamp_format_from(Some(std::ffi::OsStr::new("ISO-Basic"))),
+            TimestampFormat::IsoBasic
+        );
+        assert_eq!(
+            timestamp_format_from(Some(std::ffi::OsStr::new("%Y-%m-%d"))),
+            TimestampFormat::Lines
+        );
+
+        // 2026-10-16 14:12:33.104233 UTC
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_micros(1_792_159_953_104_233);
+        assert_eq!(
+            archive_name_timestamp(time, TimestampFormat::IsoBasic),
+            "20261016T141233.104233Z"
+        );
+        assert_eq!(
+            header_timestamp(time, TimestampFormat::IsoBasic, 0),
+            "20261016T141233Z"
+        );
+        assert_eq!(
+            archive_name_timestamp(time, TimestampFormat::Lines),
+            createarchive_timestamp_with_precision(time, true)
+        );
+        assert_eq!(
+            header_timestamp(time, TimestampFormat::Lines, 0),
+            "2026-10-16, 14-12-33 UTC"
+        );
+
+        // Local time: the header moves, archive names stay UTC
+        assert_eq!(
+            header_timestamp(time, TimestampFormat::Lines, 120),
+            "2026-10-16, 16-12-33 +02:00"
+        );
+        assert_eq!(
+            header_timestamp(time, TimestampFormat::IsoBasic, -330),
+            "20261016T084233-0530"
+        );
+    }
+
+    #[test]
+    fn test_utc_offset_settings() {
+        assert_eq!(parse_utc_offset("+02:00"), Some(120));
+        assert_eq!(parse_utc_offset("-0530"), Some(-330));
+        assert_eq!(parse_utc_offset("+2"), Some(120));
+        assert_eq!(parse_utc_offset("UTC"), Some(0));
+        assert_eq!(parse_utc_offset("+15:00"), None);
+        assert_eq!(parse_utc_offset("+02:75"), None);
+        assert_eq!(parse_utc_offset("2"), None);
+
+        // POSIX TZ: the offset is west of UTC
+        assert_eq!(posix_tz_utc_offset("EST5EDT"), Some(-300));
+        assert_eq!(posix_tz_utc_offset("CET-1CEST,M3.5.0,M10.5.0/3"), Some(60));
+        assert_eq!(posix_tz_utc_offset("<+0530>-5:30"), Some(330));
+        assert_eq!(posix_tz_utc_offset("UTC0"), Some(0));
+        assert_eq!(posix_tz_utc_offset("GMT"), Some(0));
+        assert_eq!(posix_tz_utc_offset("Europe/Berlin"), None);
+        assert_eq!(posix_tz_utc_offset(":Asia/Tokyo"), None);
+
+        use std::ffi::OsStr;
+        assert_eq!(utc_offset_minutes_from(None, None), 0);
+        assert_eq!(
+            utc_offset_minutes_from(None, Some(OsStr::new("EST5EDT"))),
+            -300
+        );
+        // LINES_UTC_OFFSET wins over TZ; a malformed one means UTC
+        assert_eq!(
+            utc_offset_minutes_from(Some(OsStr::new("+01:00")), Some(OsStr::new("EST5EDT"))),
+            60
+        );
+        assert_eq!(
+            utc_offset_minutes_from(Some(OsStr::new("soon")), Some(OsStr::new("EST5EDT"))),
+            0
+        );
+        assert_eq!(format_utc_offset(-330, true), "-05:30");
+        assert_eq!(format_utc_offset(60, false), "+0100");
+    }
+
     #[test]
     fn test_days_to_ymd_boundary_conditions() {
         // Test 1: Zero days (epoch start: 1970-01-01)
@@ -1161,6 +1310,8 @@ mod hex_display_tests {
         let test_cursor = HexCursor {
             byte_offset_linear_file_absolute_position: test_offset,
             bytes_per_row: 26,
+            selection_anchor: None,
+            show_data_inspector: false,
         };
         assert!(test_cursor.current_col() < 26);
     }
@@ -1955,6 +2106,8 @@ mod tests {
 #[cfg(test)]
 mod hexedit_tests {
     use crate::buttons_reversible_edit_changelog_module::*;
+    use crate::diff_review_module::DiffLineKind;
+    use crate::fault_injection_module::*;
 
     use super::*;
     use std::fs;
@@ -2011,10 +2164,33 @@ mod hexedit_tests {
             mode: EditorMode::HexMode,                   // Correct?
             original_file_path: Some(file_path.clone()), // ???
             read_copy_path: Some(file_path),
+            read_copy_deferred: false,
             effective_rows: 40, // ??? What value?
             effective_cols: 77, // ??? What value?
             windowmap_line_byte_start_end_position_pairs: [None; MAX_TUI_ROWS],
+            windowmap_line_visible_start_bytes: [None; MAX_TUI_ROWS],
+            window_nul_byte_count: 0,
             security_mode: false,
+            final_newline_on_save: false,
+            last_char_find: None,
+            last_search_pattern: [0; limits::SEARCH_PATTERN_MAX_BYTES],
+            last_search_length: 0,
+            last_search_forward: true,
+            last_repeatable_edit: None,
+            selection_match_starts: [0; limits::SELECTION_SEARCH_MAX_MATCHES],
+            selection_match_count: 0,
+            selection_match_length: 0,
+            file_stats: None,
+            change_undo_floor: None,
+            last_change_undo_span: None,
+            last_undo_redo_span: None,
+            insert_repeat_count: 1,
+            archive_preview: None,
+            info_bar_history: InfoBarHistory::new(),
+            search_history: SearchHistory::new(),
+            scroll_off_rows: 0,
+            ruler_shown: false,
+            guide_column: Some(DEFAULT_GUIDE_COLUMN),
 
             cursor: WindowPosition {
                 tui_row: 0,
@@ -2024,6 +2200,7 @@ mod hexedit_tests {
             selection_start: None,
             selection_rowline_start: 0,
             is_modified: false,
+            original_file_mtime: None,
 
             // Position tracking - all zeros OK for test?
             line_count_at_top_of_window: 0,
@@ -2035,13 +2212,17 @@ mod hexedit_tests {
             // Display buffers
             utf8_txt_display_buffers: [[0u8; 182]; 45],
             display_utf8txt_buffer_lengths: [0usize; 45],
+            diff_row_kinds: [DiffLineKind::Context; 45],
 
             // Hex cursor - this is what we're testing
             hex_cursor: HexCursor {
                 byte_offset_linear_file_absolute_position: cursor_position,
                 // nibble_position: 0, // ??? Is this field correct?
                 bytes_per_row: 80,
+                selection_anchor: None,
+                show_data_inspector: false,
             },
+            hex_dual_view: false,
 
             eof_fileline_tuirow_tuple: None,
             info_bar_message_buffer: [0u8; INFOBAR_MESSAGE_BUFFER_SIZE],
@@ -2167,6 +2348,656 @@ mod hexedit_tests {
 
         cleanup_test_file(&file_path);
     }
+
+    /// Test 4: Fill range writes value and undo restores original bytes
+    #[test]
+    fn test_hex_fill_range_and_undo() {
+        let test_content = vec![0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99];
+        let file_path = create_test_file("test_hex_fill_range_1.bin", &test_content);
+
+        let mut editor = create_test_editor_state(file_path.clone(), 0);
+
+        // Fill positions 2..=5 with 0x00
+        let result = editor.write_n_log_hex_fill_range(2, 5, 0x00);
+        assert!(result.is_ok(), "Fill should succeed: {:?}", result.err());
+        assert_eq!(result.ok(), Some(4), "Four bytes should change");
+
+        let filled = fs::read(&file_path).expect("Should read file after fill");
+        assert_eq!(
+            filled,
+            vec![0x00, 0x11, 0x00, 0x00, 0x00, 0x00, 0x66, 0x77, 0x88, 0x99]
+        );
+
+        // One undo restores the whole fill
+        execute_command(&mut editor, Command::UndoButtonsCommand).expect("Undo should succeed");
+        let restored = fs::read(&file_path).expect("Should read file after undo");
+        assert_eq!(restored, test_content, "Undo should restore original bytes");
+
+        cleanup_test_file(&file_path);
+    }
+
+    /// Test 4b: A fill over several chunks logs one set per chunk, and one
+    /// undo and one redo take the whole range
+    #[test]
+    fn test_hex_fill_large_range_undoes_and_redoes_as_one() {
+        let test_content: Vec<u8> = (0..400u32).map(|i| (i % 251) as u8 + 1).collect();
+        let file_path = create_test_file("test_hex_fill_range_large.bin", &test_content);
+
+        let mut editor = create_test_editor_state(file_path.clone(), 0);
+
+        let result = editor.write_n_log_hex_fill_range(10, 309, 0x00);
+        assert_eq!(result.ok(), Some(300), "Every byte in range should change");
+        let mut filled = test_content.clone();
+        filled[10..310].fill(0x00);
+        assert_eq!(fs::read(&file_path).expect("Should read file"), filled);
+
+        // One bare log number per 27-byte chunk
+        let changelog_dir =
+            get_undo_changelog_directory_path(&file_path).expect("Should get changelog directory");
+        assert_eq!(
+            get_next_log_number(&changelog_dir).expect("Should read log numbers"),
+            300u128.div_ceil(27)
+        );
+
+        execute_command(&mut editor, Command::UndoButtonsCommand).expect("Undo should succeed");
+        assert_eq!(
+            fs::read(&file_path).expect("Should read file"),
+            test_content
+        );
+        execute_command(&mut editor, Command::RedoButtonsCommand).expect("Redo should succeed");
+        assert_eq!(fs::read(&file_path).expect("Should read file"), filled);
+
+        cleanup_test_file(&file_path);
+    }
+
+    /// Test 5: Fill range past EOF fails and leaves file unchanged
+    #[test]
+    fn test_hex_fill_range_past_eof_fails() {
+        let test_content = vec![0x00, 0x11, 0x22, 0x33];
+        let file_path = create_test_file("test_hex_fill_range_2.bin", &test_content);
+
+        let mut editor = create_test_editor_state(file_path.clone(), 0);
+
+        let result = editor.write_n_log_hex_fill_range(2, 10, 0xFF);
+        assert!(result.is_err(), "Fill past EOF should fail");
+
+        let unchanged = fs::read(&file_path).expect("Should read file");
+        assert_eq!(unchanged, test_content, "File should be unchanged");
+
+        cleanup_test_file(&file_path);
+    }
+
+    /// Test 6: Fill command parsing
+    #[test]
+    fn test_parse_hex_fill_command() {
+        assert_eq!(parse_hex_fill_command("f10-20 00"), Some((10, 20, 0x00)));
+        assert_eq!(parse_hex_fill_command("f0-3 ff"), Some((0, 3, 0xFF)));
+        assert_eq!(parse_hex_fill_command("f20-10 00"), None);
+        assert_eq!(parse_hex_fill_command("f10-20 0"), None);
+        assert_eq!(parse_hex_fill_command("f10-20 zz"), None);
+        assert_eq!(parse_hex_fill_command("fa-i"), None);
+    }
+
+    /// Test 7: Byte sequence insert is undone as one group, and redone
+    #[test]
+    fn test_hex_insert_byte_sequence_one_undo_group() {
+        let test_content = vec![0x00, 0x11, 0x22, 0x33];
+        let file_path = create_test_file("test_hex_insert_sequence_1.bin", &test_content);
+
+        let mut editor = create_test_editor_state(file_path.clone(), 2);
+
+        let result = editor.insert_n_log_hex_byte_sequence(2, &[0xDE, 0xAD, 0xBE, 0xEF]);
+        assert!(result.is_ok(), "Insert should succeed: {:?}", result.err());
+
+        let inserted = fs::read(&file_path).expect("Should read file after insert");
+        assert_eq!(
+            inserted,
+            vec![0x00, 0x11, 0xDE, 0xAD, 0xBE, 0xEF, 0x22, 0x33]
+        );
+
+        // One undo removes the whole run
+        let changelog_dir =
+            get_undo_changelog_directory_path(&file_path).expect("Should get changelog directory");
+        button_undo_redo_next_inverse_changelog_pop_lifo(&file_path, &changelog_dir)
+            .expect("Undo should succeed");
+
+        let restored = fs::read(&file_path).expect("Should read file after undo");
+        assert_eq!(restored, test_content, "One undo should remove all bytes");
+
+        // One redo puts the run back
+        let redo_dir =
+            get_redo_changelog_directory_path(&file_path).expect("Should get redo directory");
+        button_undo_redo_next_inverse_changelog_pop_lifo(&file_path, &redo_dir)
+            .expect("Redo should succeed");
+
+        let redone = fs::read(&file_path).expect("Should read file after redo");
+        assert_eq!(redone, inserted, "Redo should restore inserted bytes");
+
+        cleanup_test_file(&file_path);
+        let _ = fs::remove_dir_all(&redo_dir);
+    }
+
+    /// Test 8: A failed rename reports an error and leaves the file as it was
+    #[test]
+    fn test_hex_insert_failed_rename_keeps_original() {
+        let test_content = vec![0x00, 0x11, 0x22, 0x33];
+        let file_path = create_test_file("test_hex_insert_rename_fault_1.bin", &test_content);
+
+        let mut editor = create_test_editor_state(file_path.clone(), 1);
+
+        inject_fault(FaultPlan {
+            operation: FaultOperation::Rename,
+            nth: 1,
+            repeat: 1,
+            kind: io::ErrorKind::PermissionDenied,
+        });
+        let result = editor.insert_n_log_hex_byte_sequence(1, &[0xAB]);
+        clear_faults();
+
+        assert!(
+            result.is_err(),
+            "Injected rename failure should be reported"
+        );
+        let unchanged = fs::read(&file_path).expect("Should read file after failed insert");
+        assert_eq!(unchanged, test_content, "Original must be untouched");
+
+        cleanup_test_file(&file_path);
+    }
+
+    /// Test 8: Byte sequence command parsing
+    #[test]
+    fn test_parse_hex_byte_sequence_command() {
+        let mut buffer = [0u8; limits::HEX_INSERT_MAX_BYTES];
+
+        assert_eq!(
+            parse_hex_byte_sequence_command("DE AD BE EF-i", &mut buffer),
+            Some(4)
+        );
+        assert_eq!(&buffer[..4], &[0xDE, 0xAD, 0xBE, 0xEF]);
+
+        assert_eq!(
+            parse_hex_byte_sequence_command("cafe-i", &mut buffer),
+            Some(2)
+        );
+        assert_eq!(&buffer[..2], &[0xCA, 0xFE]);
+
+        assert_eq!(parse_hex_byte_sequence_command("DE A-i", &mut buffer), None);
+        assert_eq!(parse_hex_byte_sequence_command("D E-i", &mut buffer), None);
+        assert_eq!(parse_hex_byte_sequence_command("ZZ-i", &mut buffer), None);
+        assert_eq!(parse_hex_byte_sequence_command("DEAD", &mut buffer), None);
+    }
+
+    /// Test 9: Hex yank copies raw bytes verbatim to a clipboard file
+    #[test]
+    fn test_copy_byte_range_to_clipboardfile_raw_bytes() {
+        let test_content = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0xFF];
+        let file_path = create_test_file("test_hex_yank_range_1.bin", &test_content);
+
+        let mut session_dir = std::env::current_dir().expect("Cannot get current dir");
+        session_dir.push("test_files");
+        session_dir.push("test_hex_yank_session");
+        let _ = fs::remove_dir_all(&session_dir);
+        fs::create_dir_all(&session_dir).expect("Cannot create session dir");
+
+        let mut editor = create_test_editor_state(file_path.clone(), 0);
+        editor.session_directory_path = Some(session_dir.clone());
+
+        let clipboard_file = copy_byte_range_to_clipboardfile(&mut editor, &file_path, 0, 7)
+            .expect("Copy should succeed");
+
+        let copied = fs::read(&clipboard_file).expect("Should read clipboard file");
+        assert_eq!(copied, test_content[0..8].to_vec(), "Bytes copied verbatim");
+
+        let _ = fs::remove_dir_all(&session_dir);
+        cleanup_test_file(&file_path);
+    }
+
+    /// Test 10: Hex byte range parsing
+    #[test]
+    fn test_parse_hex_byte_range() {
+        assert_eq!(parse_hex_byte_range("0-7"), Some((0, 7)));
+        assert_eq!(parse_hex_byte_range("5-5"), Some((5, 5)));
+        assert_eq!(parse_hex_byte_range("7-0"), None);
+        assert_eq!(parse_hex_byte_range("-7"), None);
+        assert_eq!(parse_hex_byte_range("a-7"), None);
+    }
+
+    /// Test 11: Removing a selected range (more than one log set) and undoing it
+    #[test]
+    fn test_hex_remove_byte_range_and_undo() {
+        let test_content: Vec<u8> = (0u8..64).collect();
+        let file_path = create_test_file("test_hex_remove_range_1.bin", &test_content);
+
+        let mut editor = create_test_editor_state(file_path.clone(), 5);
+        editor.hex_cursor.selection_anchor = Some(45);
+        assert_eq!(editor.hex_cursor.selection_range(), Some((5, 45)));
+        assert!(editor.hex_cursor.is_selected(20));
+        assert!(!editor.hex_cursor.is_selected(46));
+
+        let result = editor.remove_n_log_hex_byte_range(5, 45);
+        assert_eq!(result.ok(), Some(41), "41 bytes should be removed");
+
+        let mut expected: Vec<u8> = (0u8..5).collect();
+        expected.extend(46u8..64);
+        let removed = fs::read(&file_path).expect("Should read file after remove");
+        assert_eq!(removed, expected);
+
+        // 41 bytes = two log sets (27 + 14): two undos restore everything
+        let changelog_dir =
+            get_undo_changelog_directory_path(&file_path).expect("Should get changelog directory");
+        for _ in 0..2 {
+            button_undo_redo_next_inverse_changelog_pop_lifo(&file_path, &changelog_dir)
+                .expect("Undo should succeed");
+        }
+
+        let restored = fs::read(&file_path).expect("Should read file after undo");
+        assert_eq!(restored, test_content, "Undo should restore removed range");
+
+        cleanup_test_file(&file_path);
+        if let Ok(redo_dir) = get_redo_changelog_directory_path(&file_path) {
+            let _ = fs::remove_dir_all(&redo_dir);
+        }
+    }
+
+    /// Test 12: Replace CRLF with LF (shorter), including a match split
+    /// across the 256-byte chunk boundary, then undo every replacement
+    #[test]
+    fn test_hex_replace_byte_pattern_and_undo() {
+        let mut test_content: Vec<u8> = vec![b'a'; 300];
+        test_content[10] = 0x0D;
+        test_content[11] = 0x0A;
+        test_content[255] = 0x0D; // straddles the chunk boundary
+        test_content[256] = 0x0A;
+        test_content[298] = 0x0D;
+        test_content[299] = 0x0A;
+        let file_path = create_test_file("test_hex_replace_1.bin", &test_content);
+
+        let mut editor = create_test_editor_state(file_path.clone(), 0);
+        let result = editor.replace_n_log_hex_byte_pattern(&[0x0D, 0x0A], &[0x0A]);
+        assert_eq!(result.ok(), Some(3), "Three CRLF pairs should be replaced");
+
+        let expected: Vec<u8> = test_content
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| ![10usize, 255, 298].contains(i))
+            .map(|(_, b)| *b)
+            .collect();
+        let replaced = fs::read(&file_path).expect("Should read file after replace");
+        assert_eq!(replaced, expected);
+
+        // Each replacement is two undo sets: re-add old, remove new
+        let changelog_dir =
+            get_undo_changelog_directory_path(&file_path).expect("Should get changelog directory");
+        for _ in 0..6 {
+            button_undo_redo_next_inverse_changelog_pop_lifo(&file_path, &changelog_dir)
+                .expect("Undo should succeed");
+        }
+
+        let restored = fs::read(&file_path).expect("Should read file after undo");
+        assert_eq!(restored, test_content, "Undo should restore original bytes");
+
+        cleanup_test_file(&file_path);
+        if let Ok(redo_dir) = get_redo_changelog_directory_path(&file_path) {
+            let _ = fs::remove_dir_all(&redo_dir);
+        }
+    }
+
+    /// Test 13: Replace with a longer pattern; no match leaves file untouched
+    #[test]
+    fn test_hex_replace_byte_pattern_longer_and_no_match() {
+        let test_content: Vec<u8> = vec![0x00, 0xFF, 0x00, 0xFF, 0x11];
+        let file_path = create_test_file("test_hex_replace_2.bin", &test_content);
+
+        let mut editor = create_test_editor_state(file_path.clone(), 0);
+        let none_found = editor.replace_n_log_hex_byte_pattern(&[0xAB], &[0xCD]);
+        assert_eq!(none_found.ok(), Some(0));
+        assert_eq!(
+            fs::read(&file_path).expect("Should read file"),
+            test_content
+        );
+
+        let result = editor.replace_n_log_hex_byte_pattern(&[0xFF], &[0xDE, 0xAD]);
+        assert_eq!(result.ok(), Some(2));
+        let replaced = fs::read(&file_path).expect("Should read file after replace");
+        assert_eq!(replaced, vec![0x00, 0xDE, 0xAD, 0x00, 0xDE, 0xAD, 0x11]);
+
+        cleanup_test_file(&file_path);
+        if let Ok(redo_dir) = get_redo_changelog_directory_path(&file_path) {
+            let _ = fs::remove_dir_all(&redo_dir);
+        }
+    }
+
+    /// Test 14: Forward search, including across the chunk boundary
+    #[test]
+    fn test_find_next_byte_pattern() {
+        let mut test_content: Vec<u8> = vec![0u8; 400];
+        test_content[5] = 0xBE;
+        test_content[6] = 0xEF;
+        test_content[255] = 0xBE;
+        test_content[256] = 0xEF;
+        let file_path = create_test_file("test_hex_search_1.bin", &test_content);
+
+        // Search starts after the cursor byte
+        assert_eq!(
+            find_next_byte_pattern(&file_path, 0, &[0xBE, 0xEF]).ok(),
+            Some(Some(5))
+        );
+        assert_eq!(
+            find_next_byte_pattern(&file_path, 5, &[0xBE, 0xEF]).ok(),
+            Some(Some(255))
+        );
+        assert_eq!(
+            find_next_byte_pattern(&file_path, 255, &[0xBE, 0xEF]).ok(),
+            Some(None)
+        );
+
+        cleanup_test_file(&file_path);
+    }
+
+    /// Test 15: Parsing s/{old}/{new}/ commands
+    #[test]
+    fn test_parse_hex_replace_command() {
+        let mut old_buffer = [0u8; limits::HEX_INSERT_MAX_BYTES];
+        let mut new_buffer = [0u8; limits::HEX_INSERT_MAX_BYTES];
+
+        assert_eq!(
+            parse_hex_replace_command("s/0D 0A/0A/", &mut old_buffer, &mut new_buffer),
+            Some((2, 1))
+        );
+        assert_eq!(&old_buffer[..2], &[0x0D, 0x0A]);
+        assert_eq!(new_buffer[0], 0x0A);
+
+        assert_eq!(
+            parse_hex_replace_command("s/00//", &mut old_buffer, &mut new_buffer),
+            Some((1, 0))
+        );
+        assert_eq!(
+            parse_hex_replace_command("s//00/", &mut old_buffer, &mut new_buffer),
+            None
+        );
+        assert_eq!(
+            parse_hex_replace_command("s/0/00/", &mut old_buffer, &mut new_buffer),
+            None
+        );
+        assert_eq!(
+            parse_hex_replace_command("s/00/11", &mut old_buffer, &mut new_buffer),
+            None
+        );
+    }
+
+    /// Test 16: Data inspector interpretations, LE/BE, and short reads
+    #[test]
+    fn test_format_data_inspector_panel() {
+        // 0x3F800000 is 1.0f32 big-endian
+        let panel = format_data_inspector_panel(&[0x3F, 0x80, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert!(panel.contains("u8  63   i8  63   bits 00111111"));
+        assert!(panel.contains("u16 LE 32831  BE 16256"));
+        assert!(panel.contains("u32 LE 32831  BE 1065353216"));
+        assert!(panel.contains("f32 LE 4.600603e-41  BE 1"));
+        assert!(panel.contains("utf8 ?  U+003F  (1 byte)"));
+        assert_eq!(panel.lines().count(), 9);
+
+        // Near EOF: only two bytes available, wider types unavailable
+        let short_panel = format_data_inspector_panel(&[0xC3, 0xA9]);
+        assert!(short_panel.contains("i8  -61"));
+        assert!(short_panel.contains("u32 LE --  BE --"));
+        assert!(short_panel.contains("utf8 é  U+00E9  (2 bytes)"));
+
+        // Continuation byte is not a character start
+        let continuation_panel = format_data_inspector_panel(&[0xA9]);
+        assert!(continuation_panel.contains("utf8 invalid"));
+    }
+
+    /// Test 17: Binary diff ranges across chunks and a length mismatch
+    #[test]
+    fn test_bindiff_compare_files() {
+        let mut content_a = vec![0u8; 5000];
+        let mut content_b = content_a.clone();
+        // Range 1: single byte; range 2: straddles the 4096-byte chunk edge
+        content_b[10] = 0xFF;
+        content_b[4094..4099].copy_from_slice(&[1, 2, 3, 4, 5]);
+        // Range 3: differs up to the shorter EOF, merges with b's tail
+        content_a[4998..].copy_from_slice(&[7, 7]);
+        content_b.extend_from_slice(&[9, 9, 9]);
+
+        let path_a = create_test_file("bindiff_test_a.bin", &content_a);
+        let path_b = create_test_file("bindiff_test_b.bin", &content_b);
+
+        let report = bindiff_compare_files(&path_a, &path_b).expect("bindiff should succeed");
+        assert_eq!(report.file_a_size, 5000);
+        assert_eq!(report.file_b_size, 5003);
+        assert_eq!(
+            report.listed_ranges,
+            vec![(10, 10), (4094, 4098), (4998, 5002)]
+        );
+        assert_eq!(report.total_range_count, 3);
+        assert_eq!(report.differing_byte_count, 11);
+        assert_eq!(report.first_difference(), Some(10));
+
+        // --porcelain: tab-separated records, fields in a fixed order
+        let mut porcelain = Vec::new();
+        write_bindiff_porcelain(&mut porcelain, &path_a, &path_b, &report)
+            .expect("porcelain report");
+        let expected = format!(
+            "file\ta\t5000\t{}\nfile\tb\t5003\t{}\n\
+             range\t10\t10\t1\nrange\t4094\t4098\t5\nrange\t4998\t5002\t5\n\
+             total\t3\t11\n",
+            path_a.display(),
+            path_b.display()
+        );
+        assert_eq!(String::from_utf8_lossy(&porcelain), expected);
+
+        let same_report = bindiff_compare_files(&path_a, &path_a).expect("bindiff of itself");
+        assert!(same_report.listed_ranges.is_empty());
+        assert_eq!(same_report.first_difference(), None);
+
+        // Exit status, as cmp: 0 same, 1 different, 2 error
+        let same = lines_bindiff_command(&path_a, &path_a, false);
+        assert!(matches!(same, Ok(true)));
+        assert_eq!(bindiff_exit_status(&same), 0);
+        let mut records = Vec::new();
+        let different = bindiff_porcelain_to(&mut records, &path_a, &path_b);
+        assert!(matches!(different, Ok(false)));
+        assert_eq!(bindiff_exit_status(&different), 1);
+        assert_eq!(String::from_utf8_lossy(&records), expected);
+        let missing = path_a.with_file_name("bindiff_test_missing.bin");
+        assert_eq!(
+            bindiff_exit_status(&lines_bindiff_command(&path_a, &missing, false)),
+            2
+        );
+
+        cleanup_test_file(&path_a);
+        cleanup_test_file(&path_b);
+    }
+
+    /// Test 18: xxd-style dump lines and range export with absolute offsets
+    #[test]
+    fn test_export_xxd_hex_dump() {
+        let mut line_buffer = [0u8; XXD_LINE_LENGTH];
+        let line_length = format_xxd_line(0, b"hello\n", &mut line_buffer);
+        assert_eq!(
+            &line_buffer[..line_length],
+            b"00000000: 6865 6c6c 6f0a                           hello.\n"
+        );
+
+        let content: Vec<u8> = (0u8..40).collect();
+        let source_path = create_test_file("xxd_export_source.bin", &content);
+        let mut dump_path = source_path.clone();
+        dump_path.set_file_name("xxd_export_dump.txt");
+        let _ = fs::remove_file(&dump_path);
+
+        let byte_count =
+            export_xxd_hex_dump(&source_path, 20, Some(39), &dump_path).expect("dump range");
+        assert_eq!(byte_count, 20);
+        let dump_text = fs::read_to_string(&dump_path).expect("read dump");
+        assert_eq!(
+            dump_text,
+            "00000014: 1415 1617 1819 1a1b 1c1d 1e1f 2021 2223  ............ !\"#\n\
+             00000024: 2425 2627                                $%&'\n"
+        );
+
+        // Never overwrites; range must lie inside the file
+        assert!(export_xxd_hex_dump(&source_path, 0, None, &dump_path).is_err());
+        let _ = fs::remove_file(&dump_path);
+        assert!(export_xxd_hex_dump(&source_path, 0, Some(40), &dump_path).is_err());
+
+        let _ = fs::remove_file(&dump_path);
+        cleanup_test_file(&source_path);
+    }
+
+    /// Test 19: xxd dump line parsing (ASCII column ignored)
+    #[test]
+    fn test_parse_xxd_line() {
+        let mut byte_buffer = [0u8; XXD_BYTES_PER_LINE];
+        assert_eq!(
+            parse_xxd_line("00000010: 4142 43  ABC\n", &mut byte_buffer),
+            Some((16, 3))
+        );
+        assert_eq!(&byte_buffer[..3], &[0x41, 0x42, 0x43]);
+        // ASCII column that looks like hex is not read as bytes
+        assert_eq!(
+            parse_xxd_line("00000000: 6162 6364  abcd", &mut byte_buffer),
+            Some((0, 4))
+        );
+        assert_eq!(parse_xxd_line("10: 41", &mut byte_buffer), Some((16, 1)));
+        assert_eq!(parse_xxd_line("00000010 4142", &mut byte_buffer), None);
+        assert_eq!(parse_xxd_line("00000010: 414", &mut byte_buffer), None);
+        assert_eq!(parse_xxd_line("00000010: ", &mut byte_buffer), None);
+        assert_eq!(parse_xxd_line("zz: 41", &mut byte_buffer), None);
+    }
+
+    /// Test 20: Dump patch is validated first, written in place, and undo-able
+    #[test]
+    fn test_hex_patch_from_xxd_dump_and_undo() {
+        let test_content = vec![0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
+        let file_path = create_test_file("test_hex_patch_dump_1.bin", &test_content);
+        let mut editor = create_test_editor_state(file_path.clone(), 0);
+
+        // Second line reaches past EOF: whole dump rejected, nothing written
+        let bad_dump_path = create_test_file(
+            "test_hex_patch_dump_bad.txt",
+            b"00000000: ffff  ..\n00000006: aabb cc  ...\n",
+        );
+        let rejected = editor.patch_n_log_from_xxd_dump(&bad_dump_path);
+        assert!(matches!(rejected, Err(LinesError::InvalidInput(_))));
+        assert_eq!(
+            fs::read(&file_path).expect("Should read file"),
+            test_content
+        );
+
+        // 0x11 is unchanged, so three bytes change
+        let dump_path = create_test_file(
+            "test_hex_patch_dump_good.txt",
+            b"00000001: 11aa  ..\n\n00000006: bbcc  ..\n",
+        );
+        let result = editor.patch_n_log_from_xxd_dump(&dump_path);
+        assert_eq!(result.ok(), Some(3), "Three bytes should change");
+        assert_eq!(
+            fs::read(&file_path).expect("Should read file after patch"),
+            vec![0x00, 0x11, 0xAA, 0x33, 0x44, 0x55, 0xBB, 0xCC]
+        );
+
+        // One undo restores the whole patch
+        execute_command(&mut editor, Command::UndoButtonsCommand).expect("Undo should succeed");
+        assert_eq!(
+            fs::read(&file_path).expect("Should read file after undo"),
+            test_content
+        );
+
+        let _ = fs::remove_file(&bad_dump_path);
+        let _ = fs::remove_file(&dump_path);
+        cleanup_test_file(&file_path);
+    }
+
+    /// Test 21: Single-byte add and remove both undo AND redo
+    #[test]
+    fn test_hex_single_byte_add_remove_undo_redo() {
+        let test_content = vec![0x10, 0x20, 0x30];
+        let file_path = create_test_file("test_hex_byte_undo_redo.bin", &test_content);
+        let mut editor = create_test_editor_state(file_path.clone(), 0);
+        let undo_dir =
+            get_undo_changelog_directory_path(&file_path).expect("Should get undo directory");
+        let redo_dir =
+            get_redo_changelog_directory_path(&file_path).expect("Should get redo directory");
+        let read = || fs::read(&file_path).expect("Should read file");
+
+        // Add (NN-i path): undo removes it, redo puts the same byte back
+        editor
+            .insert_n_log_hex_byte_sequence(1, &[0xAB])
+            .expect("Insert should succeed");
+        assert_eq!(read(), vec![0x10, 0xAB, 0x20, 0x30]);
+        button_undo_redo_next_inverse_changelog_pop_lifo(&file_path, &undo_dir)
+            .expect("Undo should succeed");
+        assert_eq!(read(), test_content);
+        button_undo_redo_next_inverse_changelog_pop_lifo(&file_path, &redo_dir)
+            .expect("Redo should succeed");
+        assert_eq!(read(), vec![0x10, 0xAB, 0x20, 0x30]);
+
+        // Remove (d path): undo restores the byte, redo removes it again
+        editor
+            .remove_n_log_hex_byte_range(2, 2)
+            .expect("Remove should succeed");
+        assert_eq!(read(), vec![0x10, 0xAB, 0x30]);
+        button_undo_redo_next_inverse_changelog_pop_lifo(&file_path, &undo_dir)
+            .expect("Undo should succeed");
+        assert_eq!(read(), vec![0x10, 0xAB, 0x20, 0x30]);
+        button_undo_redo_next_inverse_changelog_pop_lifo(&file_path, &redo_dir)
+            .expect("Redo should succeed");
+        assert_eq!(read(), vec![0x10, 0xAB, 0x30]);
+
+        let _ = fs::remove_dir_all(&redo_dir);
+        cleanup_test_file(&file_path);
+    }
+
+    /// Test 22: Hex dual view maps the text cursor column to its bytes
+    #[test]
+    fn test_hex_dual_view_cursor_byte_span_and_window() {
+        // ASCII: one byte per column
+        assert_eq!(hex_dual_cursor_byte_span("abc", 0), Some((0, 1)));
+        assert_eq!(hex_dual_cursor_byte_span("abc", 2), Some((2, 1)));
+        assert_eq!(hex_dual_cursor_byte_span("abc", 3), None);
+
+        // "é" is 2 bytes, "世" is 3 bytes and 2 columns wide
+        let row = "é世x";
+        assert_eq!(hex_dual_cursor_byte_span(row, 0), Some((0, 2)));
+        assert_eq!(hex_dual_cursor_byte_span(row, 1), Some((2, 3)));
+        assert_eq!(hex_dual_cursor_byte_span(row, 2), Some((2, 3)));
+        assert_eq!(hex_dual_cursor_byte_span(row, 3), Some((5, 1)));
+
+        // Window starts at 0 while the cursor fits, then scrolls right
+        assert_eq!(hex_dual_row_byte_window(5, Some((0, 1)), 10), (0, 5));
+        assert_eq!(hex_dual_row_byte_window(30, Some((9, 1)), 10), (0, 10));
+        assert_eq!(hex_dual_row_byte_window(30, Some((12, 3)), 10), (5, 15));
+        assert_eq!(hex_dual_row_byte_window(30, None, 10), (0, 10));
+    }
+
+    /// Test 23: Leaving hex mode puts the text cursor on the hex cursor's byte
+    #[test]
+    fn test_hex_cursor_syncs_text_cursor_on_exit() {
+        // "ab\n" = 0..3, "cd" = 3..5, "é" = 5..7, "\n" = 7, "xyz" = 8..11
+        let test_content = "ab\ncdé\nxyz\n".as_bytes().to_vec();
+        let file_path = create_test_file("test_hex_sync_text_cursor.txt", &test_content);
+
+        assert_eq!(locate_byte_line_and_column(&file_path, 0).unwrap(), (0, 0));
+        assert_eq!(locate_byte_line_and_column(&file_path, 4).unwrap(), (1, 1));
+        assert_eq!(locate_byte_line_and_column(&file_path, 5).unwrap(), (1, 2));
+        // Inside the two-byte "é": same character
+        assert_eq!(locate_byte_line_and_column(&file_path, 6).unwrap(), (1, 2));
+        assert_eq!(locate_byte_line_and_column(&file_path, 7).unwrap(), (1, 3));
+        assert_eq!(locate_byte_line_and_column(&file_path, 9).unwrap(), (2, 1));
+
+        let mut editor = create_test_editor_state(file_path.clone(), 9);
+        execute_command(&mut editor, Command::EnterNormalMode).expect("Mode switch should succeed");
+        assert_eq!(editor.mode, EditorMode::Normal);
+
+        let text_position = editor
+            .get_row_col_file_position(editor.cursor.tui_row, editor.cursor.tui_visual_col)
+            .expect("Position lookup should succeed")
+            .expect("Cursor should be on a file position");
+        assert_eq!(text_position.byte_offset_linear_file_absolute_position, 9);
+
+        cleanup_test_file(&file_path);
+    }
 }
 
 // ============================================================================
@@ -2176,6 +3007,7 @@ mod hexedit_tests {
 #[cfg(test)]
 mod saveas_tests {
     use super::*;
+    use crate::fault_injection_module::*;
     use std::fs;
     use std::io::{ErrorKind, Write};
     use std::path::PathBuf;
@@ -2764,13 +3596,99 @@ mod saveas_tests {
 
         cleanup_test_dir(&test_dir);
     }
-}
 
-// ============================================================================
-// (end) SAVE-AS-COPY OPERATION: Test Suite
-// ============================================================================
+    // ========================================================================
+    // Fault Injection Tests (retry and handle-and-move-on paths)
+    // ========================================================================
 
-#[cfg(test)]
+    #[test]
+    fn test_copy_retries_transient_read_faults() {
+        // Test: Two interrupted reads are retried and the copy completes
+        let test_dir = create_test_dir();
+        let source_path = test_dir.join("source.txt");
+        let dest_path = test_dir.join("destination.txt");
+        create_test_file(&source_path, b"retry me").expect("Failed to create source");
+
+        inject_fault(FaultPlan {
+            operation: FaultOperation::Read,
+            nth: 1,
+            repeat: 2,
+            kind: ErrorKind::Interrupted,
+        });
+        let result = save_file_as_newfile_with_newname(&source_path, &dest_path);
+        let reads_seen = fault_calls_seen(FaultOperation::Read);
+        clear_faults();
+
+        assert_eq!(
+            result.expect("Copy should succeed").0,
+            FileOperationStatus::Copied
+        );
+        assert_eq!(read_test_file(&dest_path).expect("Read dest"), b"retry me");
+        // 2 failed + 1 data + 1 EOF
+        assert_eq!(reads_seen, 4);
+
+        cleanup_test_dir(&test_dir);
+    }
+
+    #[test]
+    fn test_copy_gives_up_after_exhausted_write_retries() {
+        // Test: A write that keeps timing out ends in an error, not a hang
+        let test_dir = create_test_dir();
+        let source_path = test_dir.join("source.txt");
+        let dest_path = test_dir.join("destination.txt");
+        create_test_file(&source_path, b"content").expect("Failed to create source");
+
+        inject_fault(FaultPlan {
+            operation: FaultOperation::Write,
+            nth: 1,
+            repeat: 3, // SAVE_AS_COPY_MAX_RETRY_ATTEMPTS
+            kind: ErrorKind::TimedOut,
+        });
+        let result = save_file_as_newfile_with_newname(&source_path, &dest_path);
+        let writes_seen = fault_calls_seen(FaultOperation::Write);
+        clear_faults();
+
+        match result {
+            Err(LinesError::Io(e)) => assert_eq!(e.kind(), ErrorKind::TimedOut),
+            other => panic!("Expected TimedOut I/O error, got {:?}", other),
+        }
+        assert_eq!(writes_seen, 3, "Should attempt max times");
+
+        cleanup_test_dir(&test_dir);
+    }
+
+    #[test]
+    fn test_copy_does_not_retry_permanent_write_fault() {
+        // Test: PermissionDenied fails on the first attempt
+        let test_dir = create_test_dir();
+        let source_path = test_dir.join("source.txt");
+        let dest_path = test_dir.join("destination.txt");
+        create_test_file(&source_path, b"content").expect("Failed to create source");
+
+        inject_fault(FaultPlan {
+            operation: FaultOperation::Write,
+            nth: 1,
+            repeat: 1,
+            kind: ErrorKind::PermissionDenied,
+        });
+        let result = save_file_as_newfile_with_newname(&source_path, &dest_path);
+        let writes_seen = fault_calls_seen(FaultOperation::Write);
+        clear_faults();
+
+        assert!(
+            matches!(result, Err(LinesError::Io(ref e)) if e.kind() == ErrorKind::PermissionDenied)
+        );
+        assert_eq!(writes_seen, 1, "Permanent errors must not be retried");
+
+        cleanup_test_dir(&test_dir);
+    }
+}
+
+// ============================================================================
+// (end) SAVE-AS-COPY OPERATION: Test Suite
+// ============================================================================
+
+#[cfg(test)]
 mod byte_utf8_newline_tests {
     // use super::*;
 
@@ -2820,6 +3738,38 @@ mod byte_utf8_newline_tests {
     }
 }
 
+#[cfg(test)]
+mod info_bar_text_tests {
+    use super::*;
+    use crate::buffy_format_write_module::buffy_write;
+
+    #[test]
+    fn test_info_bar_message_with_braces_is_written_as_text() {
+        let mut state = EditorState::new();
+        let message = b"Use: f{char} or t{char}";
+        state.info_bar_message_buffer[..message.len()].copy_from_slice(message);
+        let info_bar = format_info_bar_cafe_normal_visualselect(&state).expect("format");
+
+        // As a template the braces are a bad placeholder
+        let mut as_template = Vec::new();
+        assert!(buffy_write(&mut as_template, &info_bar, &[]).is_err());
+
+        let mut written = Vec::new();
+        write_info_bar_text(&mut written, &info_bar).expect("write");
+        assert_eq!(written, info_bar.as_bytes());
+        assert!(info_bar.contains("Use: f{char} or t{char}"));
+
+        // "{}" too: it would take an argument there is none of
+        let message = b"{} ok";
+        state.info_bar_message_buffer = [0; INFOBAR_MESSAGE_BUFFER_SIZE];
+        state.info_bar_message_buffer[..message.len()].copy_from_slice(message);
+        let info_bar = format_info_bar_cafe_normal_visualselect(&state).expect("format");
+        let mut written = Vec::new();
+        write_info_bar_text(&mut written, &info_bar).expect("write");
+        assert!(String::from_utf8_lossy(&written).contains("{} ok"));
+    }
+}
+
 #[cfg(test)]
 mod hex_format_tests {
     use super::*;
@@ -3201,3 +4151,4321 @@ mod tempname_tests {
         assert!(true);
     }
 }
+
+#[cfg(test)]
+mod error_log_location_tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn test_error_log_location_precedence() {
+        let state_home = Some(OsStr::new("/home/someone/.local/state"));
+
+        // Nothing set: default next to the executable
+        assert_eq!(
+            error_log_location_from(None, None, None),
+            ErrorLogLocation::ExecutableRelative
+        );
+
+        // XDG_STATE_HOME gets a lines_editor subdirectory
+        assert_eq!(
+            error_log_location_from(None, None, state_home),
+            ErrorLogLocation::Directory(PathBuf::from(
+                "/home/someone/.local/state/lines_editor/error_logs"
+            ))
+        );
+
+        // LINES_ERROR_LOG_DIR wins over XDG_STATE_HOME
+        assert_eq!(
+            error_log_location_from(None, Some(OsStr::new("/var/tmp/lines_logs")), state_home),
+            ErrorLogLocation::Directory(PathBuf::from("/var/tmp/lines_logs"))
+        );
+
+        // The off switch wins over everything, case-insensitively
+        assert_eq!(
+            error_log_location_from(
+                Some(OsStr::new("OFF")),
+                Some(OsStr::new("/var/tmp/lines_logs")),
+                state_home
+            ),
+            ErrorLogLocation::Disabled
+        );
+
+        // Any other switch value leaves logging on
+        assert_eq!(
+            error_log_location_from(Some(OsStr::new("on")), None, None),
+            ErrorLogLocation::ExecutableRelative
+        );
+    }
+
+    #[test]
+    fn test_error_log_location_ignores_relative_paths() {
+        assert_eq!(
+            error_log_location_from(None, Some(OsStr::new("logs")), Some(OsStr::new("state"))),
+            ErrorLogLocation::ExecutableRelative
+        );
+        assert_eq!(
+            error_log_location_from(None, Some(OsStr::new("")), None),
+            ErrorLogLocation::ExecutableRelative
+        );
+    }
+}
+
+#[cfg(test)]
+mod data_location_tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn test_data_location_precedence() {
+        let state_home = Some(OsStr::new("/home/someone/.local/state"));
+        let data_home = Some(OsStr::new("/home/someone/.local/share"));
+        let home = Some(OsStr::new("/home/someone"));
+
+        // LINES_DATA_DIR wins, even over a writable install
+        assert_eq!(
+            data_location_from(
+                Some(OsStr::new("/srv/lines")),
+                true,
+                state_home,
+                data_home,
+                home
+            ),
+            DataLocation::Directory(PathBuf::from("/srv/lines"))
+        );
+
+        // A writable executable directory keeps the portable default
+        assert_eq!(
+            data_location_from(None, true, state_home, data_home, home),
+            DataLocation::ExecutableRelative
+        );
+
+        // Read-only install: XDG_STATE_HOME, then XDG_DATA_HOME
+        assert_eq!(
+            data_location_from(None, false, state_home, data_home, home),
+            DataLocation::Directory(PathBuf::from("/home/someone/.local/state/lines_editor"))
+        );
+        assert_eq!(
+            data_location_from(None, false, None, data_home, home),
+            DataLocation::Directory(PathBuf::from("/home/someone/.local/share/lines_editor"))
+        );
+
+        // Nothing to go on: executable-relative, whose error says why
+        assert_eq!(
+            data_location_from(None, false, None, None, None),
+            DataLocation::ExecutableRelative
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_data_location_home_default() {
+        assert_eq!(
+            data_location_from(None, false, None, None, Some(OsStr::new("/home/someone"))),
+            DataLocation::Directory(PathBuf::from("/home/someone/.local/state/lines_editor"))
+        );
+    }
+
+    #[test]
+    fn test_data_location_ignores_relative_paths() {
+        assert_eq!(
+            data_location_from(
+                Some(OsStr::new("data")),
+                false,
+                Some(OsStr::new("")),
+                Some(OsStr::new("share")),
+                Some(OsStr::new("/home/someone"))
+            ),
+            data_location_from(None, false, None, None, Some(OsStr::new("/home/someone")))
+        );
+    }
+
+    #[test]
+    fn test_looks_like_session_path() {
+        assert!(looks_like_session_path(Path::new(
+            "/opt/lines/lines_data/sessions/2026_01_01_00_00_00"
+        )));
+        assert!(!looks_like_session_path(Path::new("/home/someone/notes")));
+    }
+}
+
+#[cfg(test)]
+mod large_file_guard_tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parse_byte_size_units() {
+        assert_eq!(parse_byte_size("1500"), Some(1500));
+        assert_eq!(parse_byte_size("4G"), Some(4 << 30));
+        assert_eq!(parse_byte_size(" 512m "), Some(512 << 20));
+        assert_eq!(parse_byte_size("2K"), Some(2048));
+        assert_eq!(parse_byte_size("1T"), Some(1 << 40));
+        assert_eq!(parse_byte_size(""), None);
+        assert_eq!(parse_byte_size("G"), None);
+        assert_eq!(parse_byte_size("4X"), None);
+        assert_eq!(parse_byte_size("99999999999T"), None); // overflow
+    }
+
+    #[test]
+    fn test_large_file_warn_threshold_from_environment_value() {
+        assert_eq!(
+            large_file_warn_threshold_from(None),
+            Some(DEFAULT_LARGE_FILE_WARN_BYTES)
+        );
+        assert_eq!(
+            large_file_warn_threshold_from(Some(OsStr::new("4G"))),
+            Some(4 << 30)
+        );
+        assert_eq!(
+            large_file_warn_threshold_from(Some(OsStr::new("OFF"))),
+            None
+        );
+        assert_eq!(large_file_warn_threshold_from(Some(OsStr::new("0"))), None);
+        assert_eq!(
+            large_file_warn_threshold_from(Some(OsStr::new("lots"))),
+            Some(DEFAULT_LARGE_FILE_WARN_BYTES)
+        );
+    }
+
+    #[test]
+    fn test_format_byte_size() {
+        assert_eq!(format_byte_size(0), "0 B");
+        assert_eq!(format_byte_size(1023), "1023 B");
+        assert_eq!(format_byte_size(1536), "1.5 KiB");
+        assert_eq!(format_byte_size(4 << 30), "4.0 GiB");
+        assert_eq!(format_byte_size(5000 << 40), "5000.0 TiB");
+    }
+
+    #[test]
+    fn test_data_quota_from_environment_value() {
+        assert_eq!(data_quota_from(None), None);
+        assert_eq!(data_quota_from(Some(OsStr::new("off"))), None);
+        assert_eq!(data_quota_from(Some(OsStr::new("lots"))), None);
+        assert_eq!(
+            data_quota_from(Some(OsStr::new("500M"))),
+            Some(DataQuota {
+                limit_bytes: 500 << 20,
+                action: QuotaAction::Warn,
+            })
+        );
+        assert_eq!(
+            data_quota_from(Some(OsStr::new(" 2G:Refuse "))),
+            Some(DataQuota {
+                limit_bytes: 2 << 30,
+                action: QuotaAction::Refuse,
+            })
+        );
+        assert_eq!(data_quota_from(Some(OsStr::new("2G:later"))), None);
+    }
+
+    #[test]
+    fn test_measure_data_usage_by_kind() {
+        let data_dir =
+            std::env::temp_dir().join(format!("lines_data_usage_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&data_dir);
+        let session_dir = data_dir
+            .join("sessions")
+            .join("2026_26_10_16_12_00_00_000001");
+        std::fs::create_dir_all(session_dir.join("clipboard")).expect("create session dir");
+        std::fs::create_dir_all(data_dir.join("error_logs")).expect("create log dir");
+        std::fs::create_dir_all(data_dir.join("screenshots")).expect("create screenshot dir");
+        std::fs::write(session_dir.join("read_copy.txt"), vec![b'r'; 300]).expect("write");
+        std::fs::write(session_dir.join("clipboard").join("item"), vec![b'c'; 40]).expect("write");
+        std::fs::write(data_dir.join("error_logs").join("a.log"), vec![b'e'; 20]).expect("write");
+        std::fs::write(data_dir.join("screenshots").join("s.txt"), vec![b's'; 5]).expect("write");
+
+        let usage = measure_data_usage(&data_dir);
+        assert_eq!(
+            usage,
+            DataUsage {
+                session_bytes: 300,
+                clipboard_bytes: 40,
+                error_log_bytes: 20,
+                other_bytes: 5,
+                complete: true,
+            }
+        );
+        assert_eq!(usage.total(), 365);
+
+        let quota = DataQuota {
+            limit_bytes: 100,
+            action: QuotaAction::Refuse,
+        };
+        let report = data_quota_report(&data_dir, &usage, &quota);
+        assert!(report.contains("365 B used, quota 100 B (LINES_DATA_QUOTA)"));
+        assert!(report.contains("  sessions/     300 B\n"));
+        assert!(report.contains("    clipboards  40 B\n"));
+
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn test_confirm_large_file_read_copy() {
+        let path =
+            std::env::temp_dir().join(format!("lines_large_file_guard_{}.txt", std::process::id()));
+        std::fs::write(&path, vec![b'a'; 100]).expect("write test file");
+
+        // Under the threshold, or warning off: no question asked
+        let mut no_input = Cursor::new(Vec::new());
+        assert_eq!(
+            confirm_large_file_read_copy(&path, Some(100), &mut no_input).unwrap(),
+            LargeFileChoice::Copy
+        );
+        assert_eq!(
+            confirm_large_file_read_copy(&path, None, &mut no_input).unwrap(),
+            LargeFileChoice::Copy
+        );
+
+        // Over the threshold: y copies, v views the original, else cancel
+        let mut yes = Cursor::new(b"Y\n".to_vec());
+        assert_eq!(
+            confirm_large_file_read_copy(&path, Some(99), &mut yes).unwrap(),
+            LargeFileChoice::Copy
+        );
+        let mut view = Cursor::new(b"v\n".to_vec());
+        assert_eq!(
+            confirm_large_file_read_copy(&path, Some(99), &mut view).unwrap(),
+            LargeFileChoice::ViewOriginal
+        );
+        let mut enter = Cursor::new(b"\n".to_vec());
+        assert_eq!(
+            confirm_large_file_read_copy(&path, Some(99), &mut enter).unwrap(),
+            LargeFileChoice::Cancel
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_edit_commands_are_not_read_only() {
+        assert!(Command::MoveDown(3).is_read_only());
+        assert!(Command::Copyank.is_read_only());
+        assert!(Command::Quit.is_read_only());
+        assert!(!Command::EnterInsertMode.is_read_only());
+        assert!(!Command::EnterPastyClipboardMode.is_read_only());
+        assert!(!Command::DeleteLine.is_read_only());
+        assert!(!Command::UndoButtonsCommand.is_read_only());
+        assert!(!Command::SaveFileStandard.is_read_only());
+    }
+
+    #[test]
+    fn test_materialize_deferred_read_copy() {
+        let session_dir =
+            std::env::temp_dir().join(format!("lines_deferred_copy_{}", std::process::id()));
+        std::fs::create_dir_all(&session_dir).expect("create session dir");
+        let original = session_dir.join("big_original.log");
+        std::fs::write(&original, b"line one\nline two\n").expect("write original");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(original.clone());
+        state.session_directory_path = Some(session_dir.clone());
+        state.read_copy_path = Some(original.clone());
+        state.read_copy_deferred = true;
+
+        state.materialize_deferred_read_copy().expect("materialize");
+        let read_copy = state.read_copy_path.clone().expect("read-copy path");
+        assert!(!state.read_copy_deferred);
+        assert_ne!(read_copy, original);
+        assert_eq!(
+            std::fs::read(&read_copy).expect("read copy"),
+            b"line one\nline two\n"
+        );
+
+        // Second call is a no-op
+        state.materialize_deferred_read_copy().expect("no-op");
+        assert_eq!(state.read_copy_path, Some(read_copy));
+
+        let _ = std::fs::remove_dir_all(&session_dir);
+    }
+}
+
+#[cfg(test)]
+mod security_mode_tests {
+    use super::*;
+
+    #[test]
+    fn test_scrub_sensitive_buffers_zeros_state() {
+        let mut state = EditorState::new();
+        state.utf8_txt_display_buffers[0][..6].copy_from_slice(b"secret");
+        state.display_utf8txt_buffer_lengths[0] = 6;
+        state.line_chunk_scratch[..6].copy_from_slice(b"secret");
+        state.info_bar_message_buffer[..6].copy_from_slice(b"secret");
+
+        state.scrub_sensitive_buffers();
+
+        assert!(state.utf8_txt_display_buffers[0].iter().all(|&b| b == 0));
+        assert_eq!(state.display_utf8txt_buffer_lengths[0], 0);
+        assert!(state.line_chunk_scratch.iter().all(|&b| b == 0));
+        assert!(state.info_bar_message_buffer.iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_overwrite_file_with_zeros_keeps_length() {
+        let test_dir = env::temp_dir().join(format!("lines_zero_test_{}", std::process::id()));
+        std::fs::create_dir_all(&test_dir).expect("Cannot create test dir");
+        let file_path = test_dir.join("secret.txt");
+        // Larger than one overwrite chunk, not a multiple of it
+        let content = vec![b'x'; 10_000];
+        std::fs::write(&file_path, &content).expect("Cannot write test file");
+
+        overwrite_file_with_zeros(&file_path).expect("Overwrite should succeed");
+        let after = std::fs::read(&file_path).expect("Cannot read test file");
+        assert_eq!(after.len(), content.len());
+        assert!(after.iter().all(|&b| b == 0));
+
+        remove_session_file(&file_path, true).expect("Remove should succeed");
+        assert!(!file_path.exists());
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_overwrite_session_directory_with_zeros() {
+        let session_dir = env::temp_dir()
+            .join(format!("lines_wipe_test_{}", std::process::id()))
+            .join("lines_data")
+            .join("sessions")
+            .join("s1");
+        let changelog_dir = session_dir.join("changelog_readcopytxt");
+        std::fs::create_dir_all(&changelog_dir).expect("Cannot create test dirs");
+        std::fs::write(session_dir.join("readcopy.txt"), b"secret").expect("write");
+        std::fs::write(changelog_dir.join("0"), b"secret log").expect("write");
+
+        assert_eq!(
+            overwrite_session_directory_with_zeros(&session_dir).ok(),
+            Some(0)
+        );
+        assert_eq!(
+            std::fs::read(session_dir.join("readcopy.txt")).expect("read"),
+            vec![0u8; 6]
+        );
+        assert_eq!(
+            std::fs::read(changelog_dir.join("0")).expect("read"),
+            vec![0u8; 10]
+        );
+
+        // Refuses anything that is not a session directory
+        assert!(overwrite_session_directory_with_zeros(&env::temp_dir()).is_err());
+
+        let _ = std::fs::remove_dir_all(
+            env::temp_dir().join(format!("lines_wipe_test_{}", std::process::id())),
+        );
+    }
+
+    #[test]
+    fn test_zero_buffer() {
+        let mut buffer = *b"password";
+        zero_buffer(&mut buffer);
+        assert_eq!(buffer, [0u8; 8]);
+    }
+}
+
+#[cfg(test)]
+mod log_level_tests {
+    use super::*;
+
+    #[test]
+    fn test_verbose_switch_gates_info_only() {
+        assert!(LogLevel::Info < LogLevel::Warn && LogLevel::Warn < LogLevel::Error);
+        assert_eq!(LogLevel::Warn.label(), "WARN");
+
+        set_verbose_logging(true);
+        assert!(log_level_enabled(LogLevel::Info));
+
+        // Back to the default: Warn and Error only
+        set_verbose_logging(false);
+        assert!(!log_level_enabled(LogLevel::Info));
+        assert!(log_level_enabled(LogLevel::Warn));
+        assert!(log_level_enabled(LogLevel::Error));
+    }
+}
+
+// ============================================================================
+// RANDOMIZED EDIT FUZZ TESTS
+// ============================================================================
+//
+// Bounded random sequences of inserts, deletes, undos, and saves run against
+// a temp file, and after every step the file is compared with an in-memory
+// reference model (a Vec<u8> plus a stack of pre-edit snapshots for undo).
+//
+// Std-only: a xorshift64 PRNG, no crates. Runs are deterministic per seed.
+// To reproduce or explore a seed:
+//     LINES_FUZZ_SEED=12345 cargo test edit_fuzz
+// A divergence panics with the seed, the step, and the operations so far.
+
+#[cfg(test)]
+mod edit_fuzz_tests {
+    use super::*;
+    use crate::buttons_reversible_edit_changelog_module::*;
+    use std::fs;
+
+    /// Seeds run by default (LINES_FUZZ_SEED replaces them with one seed)
+    const FUZZ_DEFAULT_SEEDS: [u64; 4] = [0x9E37_79B9_7F4A_7C15, 1, 0xDEAD_BEEF, 20_240_611];
+
+    /// Operations per seed
+    const FUZZ_STEPS_PER_SEED: usize = 60;
+
+    /// Largest file the model lets grow to (keeps each run fast)
+    const FUZZ_MAX_FILE_BYTES: usize = 512;
+
+    /// Largest insert/delete per operation: one undo log group
+    const FUZZ_MAX_EDIT_BYTES: usize = 27;
+
+    /// Minimal xorshift64 PRNG (Marsaglia), never seeded with 0
+    struct XorShift64 {
+        state: u64,
+    }
+
+    impl XorShift64 {
+        fn new(seed: u64) -> Self {
+            XorShift64 {
+                state: if seed == 0 {
+                    0x2545_F491_4F6C_DD1D
+                } else {
+                    seed
+                },
+            }
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let mut x = self.state;
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            self.state = x;
+            x
+        }
+
+        /// Uniform-enough value in 0..bound (bound > 0)
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next_u64() % bound as u64) as usize
+        }
+    }
+
+    /// One fuzz operation, recorded for the failure report
+    #[derive(Debug)]
+    enum FuzzOp {
+        Insert { position: usize, bytes: Vec<u8> },
+        Delete { start: usize, end: usize },
+        Undo,
+        Save,
+    }
+
+    /// Reference model: expected content and undo snapshots
+    struct FuzzModel {
+        content: Vec<u8>,
+        undo_snapshots: Vec<Vec<u8>>,
+    }
+
+    /// Picks the next operation the model allows
+    fn choose_op(rng: &mut XorShift64, model: &FuzzModel) -> FuzzOp {
+        loop {
+            match rng.below(10) {
+                0..=3 if model.content.len() < FUZZ_MAX_FILE_BYTES => {
+                    let position = rng.below(model.content.len() + 1);
+                    let length = 1 + rng.below(FUZZ_MAX_EDIT_BYTES);
+                    // Mix newlines and multi-byte UTF-8 lead/continuation bytes in
+                    let bytes = (0..length)
+                        .map(|_| match rng.below(8) {
+                            0 => b'\n',
+                            1 => 0xE4,
+                            2 => 0xB8,
+                            _ => b'a' + rng.below(26) as u8,
+                        })
+                        .collect();
+                    return FuzzOp::Insert { position, bytes };
+                }
+                4..=6 if !model.content.is_empty() => {
+                    let start = rng.below(model.content.len());
+                    let max_length = (model.content.len() - start).min(FUZZ_MAX_EDIT_BYTES);
+                    let end = start + rng.below(max_length);
+                    return FuzzOp::Delete { start, end };
+                }
+                7 | 8 if !model.undo_snapshots.is_empty() => return FuzzOp::Undo,
+                9 => return FuzzOp::Save,
+                _ => continue,
+            }
+        }
+    }
+
+    /// Runs one seed; on divergence, panics with a reproducible report
+    fn run_fuzz_seed(seed: u64) {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_fuzz_{}_{}", std::process::id(), seed));
+        let _ = fs::remove_dir_all(&test_dir);
+        fs::create_dir_all(&test_dir).expect("Cannot create fuzz directory");
+
+        let original_path = test_dir.join("fuzz_original.txt");
+        let read_copy_path = test_dir.join("fuzz_readcopy.txt");
+        let initial_content = b"fuzz\nstart\n".to_vec();
+        fs::write(&original_path, &initial_content).expect("Cannot write original");
+        fs::write(&read_copy_path, &initial_content).expect("Cannot write read-copy");
+
+        let undo_dir =
+            get_undo_changelog_directory_path(&read_copy_path).expect("Cannot get undo directory");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(original_path.clone());
+        state.read_copy_path = Some(read_copy_path.clone());
+
+        let mut model = FuzzModel {
+            content: initial_content,
+            undo_snapshots: Vec::new(),
+        };
+        let mut rng = XorShift64::new(seed);
+        let mut history: Vec<FuzzOp> = Vec::with_capacity(FUZZ_STEPS_PER_SEED);
+
+        for step in 0..FUZZ_STEPS_PER_SEED {
+            let op = choose_op(&mut rng, &model);
+
+            let outcome: std::result::Result<(), String> = match &op {
+                FuzzOp::Insert { position, bytes } => {
+                    model.undo_snapshots.push(model.content.clone());
+                    model
+                        .content
+                        .splice(*position..*position, bytes.iter().copied());
+                    state
+                        .insert_n_log_hex_byte_sequence(*position, bytes)
+                        .map_err(|e| e.to_string())
+                }
+                FuzzOp::Delete { start, end } => {
+                    model.undo_snapshots.push(model.content.clone());
+                    model.content.drain(*start..=*end);
+                    state
+                        .remove_n_log_hex_byte_range(*start, *end)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                }
+                FuzzOp::Undo => {
+                    if let Some(snapshot) = model.undo_snapshots.pop() {
+                        model.content = snapshot;
+                    }
+                    button_undo_redo_next_inverse_changelog_pop_lifo(&read_copy_path, &undo_dir)
+                        .map_err(|e| e.to_string())
+                }
+                FuzzOp::Save => save_file(&mut state).map(|_| ()).map_err(|e| e.to_string()),
+            };
+            history.push(op);
+
+            let failure = match outcome {
+                Err(message) => Some(format!("operation failed: {}", message)),
+                Ok(()) => {
+                    let actual = fs::read(&read_copy_path).expect("Cannot read read-copy");
+                    let saved_ok = !matches!(history.last(), Some(FuzzOp::Save))
+                        || fs::read(&original_path).ok().as_ref() == Some(&model.content);
+                    if actual != model.content {
+                        Some(format!(
+                            "content diverged\n  expected: {:?}\n  actual:   {:?}",
+                            String::from_utf8_lossy(&model.content),
+                            String::from_utf8_lossy(&actual)
+                        ))
+                    } else if !saved_ok {
+                        Some("saved original differs from model".to_string())
+                    } else {
+                        None
+                    }
+                }
+            };
+
+            if let Some(reason) = failure {
+                let _ = fs::remove_dir_all(&test_dir);
+                panic!(
+                    "Edit fuzz divergence (LINES_FUZZ_SEED={}) at step {}: {}\n  ops: {:?}",
+                    seed, step, reason, history
+                );
+            }
+        }
+
+        let _ = fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_xorshift_is_deterministic() {
+        let mut a = XorShift64::new(42);
+        let mut b = XorShift64::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        // Zero seed must not get stuck at zero
+        assert_ne!(XorShift64::new(0).next_u64(), 0);
+    }
+
+    #[test]
+    fn test_edit_fuzz_against_reference_model() {
+        match env::var("LINES_FUZZ_SEED")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+        {
+            Some(seed) => run_fuzz_seed(seed),
+            None => {
+                for seed in FUZZ_DEFAULT_SEEDS {
+                    run_fuzz_seed(seed);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod long_line_tests {
+    use super::*;
+
+    #[test]
+    fn test_build_windowmap_scrolled_deep_into_long_line() {
+        let test_dir = std::env::temp_dir().join(format!("lines_long_line_{}", std::process::id()));
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let path = test_dir.join("one_line.json");
+
+        // 200,000 chars / 400,000 bytes, then a short line
+        let long_line = "ab世界".repeat(50_000);
+        std::fs::write(&path, format!("{}\nshort\n", long_line)).expect("write file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(path.clone());
+        // Char 150,001 is a 'b' at byte 150,000 / 4 * 8 + 1
+        state.tui_window_horizontal_utf8txt_line_char_offset = 150_001;
+
+        build_windowmap_nowrap(&mut state, &path).expect("build window");
+
+        assert_eq!(
+            state.windowmap_line_byte_start_end_position_pairs[0],
+            Some((0, 399_999))
+        );
+        assert_eq!(
+            state.windowmap_line_byte_start_end_position_pairs[1],
+            Some((400_001, 400_005))
+        );
+        assert_eq!(state.windowmap_line_visible_start_bytes[0], Some(300_001));
+        // Shorter than the offset: nothing visible, start is the line end
+        assert_eq!(state.windowmap_line_visible_start_bytes[1], Some(400_006));
+
+        // First content cell of row 0 maps to the first visible byte
+        let first_position = (0..state.effective_cols)
+            .find_map(|col| state.get_row_col_file_position(0, col).ok().flatten())
+            .expect("row 0 has a mapped cell");
+        assert_eq!(
+            first_position.byte_offset_linear_file_absolute_position,
+            300_001
+        );
+        assert_eq!(first_position.byte_in_line, 300_001);
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_parse_goto_column() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("gc42", EditorMode::Normal),
+            Command::GotoColumn(42)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("gc0", EditorMode::Normal),
+            Command::None
+        );
+        assert!(Command::GotoColumn(1).is_read_only());
+    }
+}
+
+#[cfg(test)]
+mod final_newline_tests {
+    use super::*;
+
+    fn row_text(state: &EditorState, row: usize) -> String {
+        let len = state.display_utf8txt_buffer_lengths[row];
+        String::from_utf8_lossy(&state.utf8_txt_display_buffers[row][..len]).into_owned()
+    }
+
+    #[test]
+    fn test_missing_final_newline_is_marked() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_final_newline_mark_{}", std::process::id()));
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let no_eol = test_dir.join("no_eol.txt");
+        let with_eol = test_dir.join("with_eol.txt");
+        std::fs::write(&no_eol, b"first\nlast").expect("write no_eol");
+        std::fs::write(&with_eol, b"first\nlast\n").expect("write with_eol");
+
+        let mut state = EditorState::new();
+        build_windowmap_nowrap(&mut state, &no_eol).expect("build no_eol");
+        assert!(row_text(&state, 0).ends_with("first␤"));
+        assert!(row_text(&state, 1).ends_with("last␃"));
+
+        build_windowmap_nowrap(&mut state, &with_eol).expect("build with_eol");
+        assert!(row_text(&state, 1).ends_with("last␤"));
+        assert!(!row_text(&state, 1).contains('␃'));
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_save_appends_final_newline_only_when_enabled() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_final_newline_save_{}", std::process::id()));
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let original = test_dir.join("notes.txt");
+        let read_copy = test_dir.join("read_copy_notes.txt");
+        std::fs::write(&original, b"old").expect("write original");
+        std::fs::write(&read_copy, b"no newline").expect("write read-copy");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(original.clone());
+        state.read_copy_path = Some(read_copy.clone());
+
+        // Default: the file's ending is preserved
+        save_file(&mut state).expect("save as-is");
+        assert_eq!(std::fs::read(&original).expect("read"), b"no newline");
+
+        state.final_newline_on_save = true;
+        save_file(&mut state).expect("save with final newline");
+        assert_eq!(std::fs::read(&original).expect("read"), b"no newline\n");
+        assert_eq!(std::fs::read(&read_copy).expect("read"), b"no newline\n");
+
+        // Already terminated: no second newline
+        save_file(&mut state).expect("save again");
+        assert_eq!(std::fs::read(&original).expect("read"), b"no newline\n");
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+#[cfg(test)]
+mod nul_byte_tests {
+    use super::*;
+
+    #[test]
+    fn test_nul_bytes_shown_as_glyph_with_one_cursor_stop_each() {
+        let test_dir = std::env::temp_dir().join(format!("lines_nul_bytes_{}", std::process::id()));
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("nul.txt");
+        std::fs::write(&file_path, b"ab\0\0cd\n").expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+
+        let len = state.display_utf8txt_buffer_lengths[0];
+        let row = String::from_utf8_lossy(&state.utf8_txt_display_buffers[0][..len]).into_owned();
+        assert!(row.ends_with("ab␀␀cd␤"), "row was {:?}", row);
+        assert_eq!(state.window_nul_byte_count, 2);
+
+        // Each NUL is one cell mapping to its own file byte
+        let first_col = (0..state.effective_cols)
+            .find(|&col| matches!(state.get_row_col_file_position(0, col), Ok(Some(_))))
+            .expect("row 0 has a mapped cell");
+        for (offset, expected_byte) in [(2, 2), (3, 3), (4, 4)] {
+            let position = state
+                .get_row_col_file_position(0, first_col + offset)
+                .expect("lookup")
+                .expect("mapped cell");
+            assert_eq!(
+                position.byte_offset_linear_file_absolute_position,
+                expected_byte
+            );
+        }
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+#[cfg(test)]
+mod nfc_selection_tests {
+    use crate::buttons_reversible_edit_changelog_module::*;
+
+    use super::*;
+
+    #[test]
+    fn test_nfc_selection_rewrite_and_undo() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_nfc_selection_{}", std::process::id()));
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("names.txt");
+        let decomposed = "Cafe\u{301} ok\n";
+        std::fs::write(&file_path, decomposed).expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        // Select "Cafe" + U+0301 (the mark starts at byte 4)
+        state.file_position_of_vis_select_start = 0;
+        state.file_position_of_vis_select_end = 4;
+
+        assert!(state.normalize_selection_nfc_n_report().expect("nfc"));
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "Caf\u{E9} ok\n"
+        );
+
+        // Now composed: nothing to do
+        state.file_position_of_vis_select_end = 3;
+        assert!(!state.normalize_selection_nfc_n_report().expect("nfc again"));
+
+        // One "add old" set and one "remove new" set
+        let changelog_dir =
+            get_undo_changelog_directory_path(&file_path).expect("changelog directory");
+        for _ in 0..2 {
+            button_undo_redo_next_inverse_changelog_pop_lifo(&file_path, &changelog_dir)
+                .expect("undo");
+        }
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            decomposed
+        );
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_search_reports_other_normalization_form() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_nfc_search_{}", std::process::id()));
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("mixed.txt");
+        std::fs::write(&file_path, "x re\u{301}sume\u{301}\n").expect("write test file");
+
+        let composed = "r\u{E9}sum\u{E9}".as_bytes();
+        assert_eq!(
+            find_next_byte_pattern(&file_path, 0, composed).expect("search"),
+            None
+        );
+        let (form, position) = find_other_normalization_form(&file_path, 0, composed)
+            .expect("search")
+            .expect("decomposed form is in the file");
+        assert_eq!(form.name(), "nfd");
+        assert_eq!(position, 2);
+
+        assert_eq!(
+            find_other_normalization_form(&file_path, 0, b"resume").expect("search"),
+            None
+        );
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+#[cfg(test)]
+mod big_word_motion_tests {
+    use super::editor_test_helpers::cursor_byte;
+    use super::*;
+
+    #[test]
+    fn test_word_motions_cross_paths_as_one_unit() {
+        let test_dir = std::env::temp_dir().join(format!("lines_big_word_{}", std::process::id()));
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("shell.txt");
+        // "cd" 0..2, ' ' 2, "/usr/local/bin" 3..17, ' ' 17, "now" 18..21
+        std::fs::write(&file_path, "cd /usr/local/bin now\n").expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+        state.cursor.tui_visual_col = (0..state.effective_cols)
+            .find(|&col| matches!(state.get_row_col_file_position(0, col), Ok(Some(_))))
+            .expect("row 0 has a mapped cell");
+        assert_eq!(cursor_byte(&state), 0);
+
+        execute_command(&mut state, Command::MoveBigWordForward(2)).expect("W");
+        assert_eq!(cursor_byte(&state), 17);
+
+        execute_command(&mut state, Command::MoveBigWordBack(1)).expect("B");
+        assert_eq!(cursor_byte(&state), 3);
+
+        execute_command(&mut state, Command::MoveBigWordEnd(1)).expect("E");
+        assert_eq!(cursor_byte(&state), 16);
+
+        // w, by contrast, stops at the next '/'
+        execute_command(&mut state, Command::MoveBigWordBack(1)).expect("B");
+        execute_command(&mut state, Command::MoveWordForward(1)).expect("w");
+        assert_eq!(cursor_byte(&state), 7);
+
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("3W", EditorMode::Normal),
+            Command::MoveBigWordForward(3)
+        );
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+#[cfg(test)]
+mod find_char_in_line_tests {
+    use super::editor_test_helpers::cursor_byte;
+    use super::*;
+
+    #[test]
+    fn test_parse_find_and_till() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("f,\n", EditorMode::Normal),
+            Command::FindCharInLine(',', 1)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes(
+                "3t)\n",
+                EditorMode::VisualSelectMode
+            ),
+            Command::TillCharInLine(')', 3)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("f \n", EditorMode::Normal),
+            Command::FindCharInLine(' ', 1)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("f;\n", EditorMode::Normal),
+            Command::FindCharInLine(';', 1)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("2;\n", EditorMode::Normal),
+            Command::RepeatCharFind(2)
+        );
+        // Longer words starting with t still reach the command tables
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("tall+\n", EditorMode::Normal),
+            Command::TallPlus
+        );
+    }
+
+    #[test]
+    fn test_find_and_till_move_within_line() {
+        let test_dir = std::env::temp_dir().join(format!("lines_find_char_{}", std::process::id()));
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("call.rs");
+        // bytes: f0 (1 a2 ,3 ' '4 b5 ,6 ' '7 世8..11 ,11 )12
+        std::fs::write(&file_path, "f(a, b, 世,)\nx,\n").expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+        state.cursor.tui_visual_col = (0..state.effective_cols)
+            .find(|&col| matches!(state.get_row_col_file_position(0, col), Ok(Some(_))))
+            .expect("row 0 has a mapped cell");
+
+        execute_command(&mut state, Command::FindCharInLine(',', 1)).expect("f,");
+        assert_eq!(cursor_byte(&state), 3);
+
+        execute_command(&mut state, Command::RepeatCharFind(2)).expect(";");
+        assert_eq!(cursor_byte(&state), 11, "past the double-width character");
+
+        // Only this line: the ',' on the next line is not found
+        execute_command(&mut state, Command::RepeatCharFind(1)).expect(";");
+        assert_eq!(cursor_byte(&state), 11);
+
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+        execute_command(&mut state, Command::TillCharInLine(',', 1)).expect("t,");
+        assert_eq!(cursor_byte(&state), 2);
+        // Repeating t moves on instead of standing still
+        execute_command(&mut state, Command::RepeatCharFind(1)).expect(";");
+        assert_eq!(cursor_byte(&state), 5);
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+#[cfg(test)]
+mod insert_append_line_tests {
+    use super::editor_test_helpers::{cursor_byte, state_for};
+    use super::*;
+
+    #[test]
+    fn test_parse_insert_and_append() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("I\n", EditorMode::Normal),
+            Command::InsertAtLineStart
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("A\n", EditorMode::Normal),
+            Command::AppendAtLineEnd
+        );
+        assert!(!Command::InsertAtLineStart.is_read_only());
+        assert!(!Command::AppendAtLineEnd.is_read_only());
+    }
+
+    #[test]
+    fn test_insert_at_first_non_blank() {
+        let (mut state, test_dir, _) = state_for("insert_append_insert", " \t let x = 1;\n");
+        execute_command(&mut state, Command::MoveRight(8)).expect("l");
+
+        execute_command(&mut state, Command::InsertAtLineStart).expect("I");
+        assert_eq!(state.mode, EditorMode::Insert);
+        assert_eq!(cursor_byte(&state), 3, "past the space, tab and space");
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_insert_on_blank_line_goes_to_end() {
+        let (mut state, test_dir, _) = state_for("insert_append_blank", "   \nx\n");
+
+        execute_command(&mut state, Command::InsertAtLineStart).expect("I");
+        assert_eq!(cursor_byte(&state), 3);
+        assert!(state.is_current_cursor_on_newline().expect("newline check"));
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_append_at_line_end() {
+        let (mut state, test_dir, _) = state_for("insert_append_append", "abc\n\nlast");
+
+        execute_command(&mut state, Command::AppendAtLineEnd).expect("A");
+        assert_eq!(state.mode, EditorMode::Insert);
+        assert_eq!(cursor_byte(&state), 3, "on the newline, after 'c'");
+
+        // An empty line: already at its end
+        state.mode = EditorMode::Normal;
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(&mut state, Command::AppendAtLineEnd).expect("A");
+        assert_eq!(cursor_byte(&state), 4);
+
+        // Last line without a newline: the end-of-file cell
+        state.mode = EditorMode::Normal;
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(&mut state, Command::AppendAtLineEnd).expect("A");
+        assert_eq!(cursor_byte(&state), 9);
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+#[cfg(test)]
+mod open_line_tests {
+    use super::editor_test_helpers::{cursor_byte, state_for};
+    use super::*;
+    use crate::buttons_reversible_edit_changelog_module::*;
+
+    #[test]
+    fn test_parse_open_line() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("o\n", EditorMode::Normal),
+            Command::OpenLineBelow
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("O\n", EditorMode::Normal),
+            Command::OpenLineAbove
+        );
+        assert!(!Command::OpenLineBelow.is_read_only());
+    }
+
+    #[test]
+    fn test_open_line_below_keeps_indent_and_undoes_in_one_step() {
+        let (mut state, test_dir, file_path) =
+            state_for("open_line_below", "fn x() {\n\tlet a;\n}\n");
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(&mut state, Command::MoveRight(3)).expect("l");
+
+        execute_command(&mut state, Command::OpenLineBelow).expect("o");
+        assert_eq!(state.mode, EditorMode::Insert);
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "fn x() {\n\tlet a;\n\t\n}\n"
+        );
+        assert_eq!(state.cursor.tui_row, 2);
+        assert_eq!(cursor_byte(&state), 18, "after the copied tab");
+
+        let log_dir = get_undo_changelog_directory_path(&file_path).expect("log dir");
+        button_undo_redo_next_inverse_changelog_pop_lifo(&file_path, &log_dir).expect("undo");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "fn x() {\n\tlet a;\n}\n"
+        );
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_open_line_above_and_below_last_line() {
+        let (mut state, test_dir, file_path) = state_for("open_line_above", "  a\n  b");
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+
+        execute_command(&mut state, Command::OpenLineAbove).expect("O");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "  a\n  \n  b"
+        );
+        assert_eq!(state.cursor.tui_row, 1);
+        assert_eq!(cursor_byte(&state), 6);
+
+        // Below a last line with no newline
+        state.mode = EditorMode::Normal;
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(&mut state, Command::OpenLineBelow).expect("o");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "  a\n  \n  b\n  "
+        );
+        assert_eq!(state.cursor.tui_row, 3);
+        assert_eq!(cursor_byte(&state), 13);
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+#[cfg(test)]
+mod change_operator_tests {
+    use super::editor_test_helpers::{cursor_byte, state_for};
+    use super::*;
+
+    fn read(file_path: &Path) -> String {
+        std::fs::read_to_string(file_path).expect("read test file")
+    }
+
+    #[test]
+    fn test_parse_change_operators() {
+        let mut state = EditorState::new();
+        let mut parse = |input: &str| {
+            state.parse_commands_for_normal_visualselect_modes(input, EditorMode::Normal)
+        };
+        assert_eq!(
+            parse("cw\n"),
+            Command::ChangeToMotion(ChangeMotion::Word, 1)
+        );
+        assert_eq!(
+            parse("2cE\n"),
+            Command::ChangeToMotion(ChangeMotion::BigWordEnd, 2)
+        );
+        assert_eq!(
+            parse("c3b\n"),
+            Command::ChangeToMotion(ChangeMotion::WordBack, 3)
+        );
+        assert_eq!(
+            parse("c$\n"),
+            Command::ChangeToMotion(ChangeMotion::LineEnd, 1)
+        );
+        assert_eq!(
+            parse("cgh\n"),
+            Command::ChangeToMotion(ChangeMotion::LineStart, 1)
+        );
+        assert_eq!(
+            parse("ct)\n"),
+            Command::ChangeToMotion(ChangeMotion::TillChar(')'), 1)
+        );
+        assert_eq!(
+            parse("cf \n"),
+            Command::ChangeToMotion(ChangeMotion::FindChar(' '), 1)
+        );
+        assert_eq!(parse("cc\n"), Command::ChangeLine);
+        assert_eq!(parse("cq\n"), Command::None);
+        assert!(!Command::ChangeLine.is_read_only());
+    }
+
+    #[test]
+    fn test_change_word_then_typing_undoes_in_one_step() {
+        let (mut state, test_dir, file_path) = state_for("change_op_cw", "hello world\n");
+
+        execute_command(&mut state, Command::ChangeToMotion(ChangeMotion::Word, 1)).expect("cw");
+        assert_eq!(state.mode, EditorMode::Insert);
+        assert_eq!(read(&file_path), " world\n");
+        assert_eq!(cursor_byte(&state), 0);
+
+        insert_text_chunk_at_cursor_position(&mut state, &file_path, b"bye").expect("type");
+        execute_command(&mut state, Command::EnterNormalMode).expect("esc");
+        assert_eq!(read(&file_path), "bye world\n");
+
+        execute_command(&mut state, Command::UndoButtonsCommand).expect("u");
+        assert_eq!(read(&file_path), "hello world\n");
+
+        // And one redo puts the whole change back
+        execute_command(&mut state, Command::RedoButtonsCommand).expect("re");
+        assert_eq!(read(&file_path), "bye world\n");
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_change_word_on_last_char_and_change_back() {
+        let (mut state, test_dir, file_path) = state_for("change_op_cb", "ab cd.ef\n");
+
+        // On 'b', the last character of "ab": only it changes
+        execute_command(&mut state, Command::MoveRight(1)).expect("l");
+        execute_command(&mut state, Command::ChangeToMotion(ChangeMotion::Word, 1)).expect("cw");
+        assert_eq!(read(&file_path), "a cd.ef\n");
+        execute_command(&mut state, Command::EnterNormalMode).expect("esc");
+
+        // cb from 'e' removes "cd." but keeps the cursor's 'e'
+        execute_command(&mut state, Command::FindCharInLine('e', 1)).expect("fe");
+        execute_command(
+            &mut state,
+            Command::ChangeToMotion(ChangeMotion::WordBack, 1),
+        )
+        .expect("cb");
+        assert_eq!(read(&file_path), "a ef\n");
+        assert_eq!(cursor_byte(&state), 2);
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_change_to_line_end_and_till_char() {
+        let (mut state, test_dir, file_path) = state_for("change_op_cdollar", "call(a, b)\nnext\n");
+
+        execute_command(&mut state, Command::FindCharInLine('(', 1)).expect("f(");
+        execute_command(&mut state, Command::MoveRight(1)).expect("l");
+        execute_command(
+            &mut state,
+            Command::ChangeToMotion(ChangeMotion::TillChar(')'), 1),
+        )
+        .expect("ct)");
+        assert_eq!(read(&file_path), "call()\nnext\n");
+        assert_eq!(cursor_byte(&state), 5);
+        execute_command(&mut state, Command::EnterNormalMode).expect("esc");
+
+        // A missing target changes nothing and stays in Normal mode
+        execute_command(
+            &mut state,
+            Command::ChangeToMotion(ChangeMotion::FindChar('z'), 1),
+        )
+        .expect("cfz");
+        assert_eq!(state.mode, EditorMode::Normal);
+        assert_eq!(read(&file_path), "call()\nnext\n");
+
+        // c$ keeps the newline
+        execute_command(
+            &mut state,
+            Command::ChangeToMotion(ChangeMotion::LineEnd, 1),
+        )
+        .expect("c$");
+        assert_eq!(read(&file_path), "call(\nnext\n");
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_change_line_keeps_indent() {
+        let (mut state, test_dir, file_path) = state_for("change_op_cc", "x\n    old text\ny\n");
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+
+        execute_command(&mut state, Command::ChangeLine).expect("cc");
+        assert_eq!(state.mode, EditorMode::Insert);
+        assert_eq!(read(&file_path), "x\n    \ny\n");
+        assert_eq!(cursor_byte(&state), 6);
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+#[cfg(test)]
+mod normal_mode_yank_tests {
+    use super::editor_test_helpers::session_state_for;
+    use super::*;
+
+    /// Contents of the only clipboard item, then removes it
+    fn take_clipboard_item(test_dir: &Path) -> String {
+        let clipboard_dir = test_dir.join("clipboard");
+        let mut items: Vec<PathBuf> = std::fs::read_dir(&clipboard_dir)
+            .expect("clipboard dir")
+            .map(|entry| entry.expect("entry").path())
+            .collect();
+        assert_eq!(items.len(), 1, "one clipboard item");
+        let item = items.pop().expect("item");
+        let text = std::fs::read_to_string(&item).expect("read item");
+        std::fs::remove_file(&item).expect("remove item");
+        text
+    }
+
+    #[test]
+    fn test_parse_yank_commands() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("yw\n", EditorMode::Normal),
+            Command::YankWord(false)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("yW\n", EditorMode::Normal),
+            Command::YankWord(true)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("3yy\n", EditorMode::Normal),
+            Command::YankLines(3)
+        );
+        assert!(Command::YankLines(1).is_read_only());
+    }
+
+    #[test]
+    fn test_yank_word_under_cursor() {
+        let (mut state, test_dir, file_path) =
+            session_state_for("yank_word", "let path = a/b.txt;\n");
+
+        // Cursor inside "path"
+        execute_command(&mut state, Command::MoveRight(6)).expect("l");
+        execute_command(&mut state, Command::YankWord(false)).expect("yw");
+        assert_eq!(take_clipboard_item(&test_dir), "path");
+
+        // yW takes the whole whitespace-delimited WORD
+        execute_command(&mut state, Command::FindCharInLine('b', 1)).expect("fb");
+        execute_command(&mut state, Command::YankWord(true)).expect("yW");
+        assert_eq!(take_clipboard_item(&test_dir), "a/b.txt;");
+
+        // Nothing on a space
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+        execute_command(&mut state, Command::MoveRight(3)).expect("l");
+        execute_command(&mut state, Command::YankWord(false)).expect("yw");
+        assert_eq!(
+            std::fs::read_dir(test_dir.join("clipboard"))
+                .expect("clipboard dir")
+                .count(),
+            0
+        );
+
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "let path = a/b.txt;\n"
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_yank_lines() {
+        let (mut state, test_dir, _file_path) = session_state_for("yank_lines", "one\ntwo\nthree");
+
+        execute_command(&mut state, Command::MoveRight(2)).expect("l");
+        execute_command(&mut state, Command::YankLines(1)).expect("yy");
+        assert_eq!(take_clipboard_item(&test_dir), "one\n");
+
+        // A count past the end stops at the last line (no final newline)
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(&mut state, Command::YankLines(5)).expect("5yy");
+        assert_eq!(take_clipboard_item(&test_dir), "two\nthree");
+
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod put_clipboard_tests {
+    use super::editor_test_helpers::{cursor_byte, session_state_for};
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    /// Writes clipboard items, oldest first, a second apart
+    fn write_clipboard_items(test_dir: &Path, items: &[&str]) {
+        let start = SystemTime::now() - Duration::from_secs(100);
+        for (index, text) in items.iter().enumerate() {
+            let item_path = test_dir.join("clipboard").join(format!("item_{}", index));
+            std::fs::write(&item_path, text).expect("write item");
+            std::fs::File::options()
+                .write(true)
+                .open(&item_path)
+                .and_then(|file| file.set_modified(start + Duration::from_secs(index as u64)))
+                .expect("set item time");
+        }
+    }
+
+    #[test]
+    fn test_parse_put_commands() {
+        let mut state = EditorState::new();
+        let parse = |state: &mut EditorState, input: &str| {
+            state.parse_commands_for_normal_visualselect_modes(input, EditorMode::Normal)
+        };
+        assert_eq!(
+            parse(&mut state, "p\n"),
+            Command::PutClipboardItem(true, 1, 1)
+        );
+        assert_eq!(
+            parse(&mut state, "P\n"),
+            Command::PutClipboardItem(false, 1, 1)
+        );
+        assert_eq!(
+            parse(&mut state, "p3\n"),
+            Command::PutClipboardItem(true, 3, 1)
+        );
+        assert_eq!(
+            parse(&mut state, "2P4\n"),
+            Command::PutClipboardItem(false, 4, 2)
+        );
+        assert_eq!(
+            parse(&mut state, "pasty\n"),
+            Command::EnterPastyClipboardMode
+        );
+        assert_eq!(parse(&mut state, "p0\n"), Command::None);
+        assert!(!Command::PutClipboardItem(true, 1, 1).is_read_only());
+
+        // Visual mode keeps p for Pasty mode
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("p\n", EditorMode::VisualSelectMode),
+            Command::EnterPastyClipboardMode
+        );
+    }
+
+    #[test]
+    fn test_put_characters_after_and_before() {
+        let (mut state, test_dir, file_path) = session_state_for("put_chars", "abc\n");
+        write_clipboard_items(&test_dir, &["XY"]);
+
+        execute_command(&mut state, Command::PutClipboardItem(true, 1, 1)).expect("p");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "aXYbc\n"
+        );
+        assert_eq!(cursor_byte(&state), 1);
+
+        execute_command(&mut state, Command::PutClipboardItem(false, 1, 2)).expect("2P");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "aXYXYXYbc\n"
+        );
+
+        // One undo removes the whole put
+        execute_command(&mut state, Command::UndoButtonsCommand).expect("u");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "aXYbc\n"
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_put_lines_below_and_above() {
+        let (mut state, test_dir, file_path) = session_state_for("put_lines", "one\ntwo");
+        write_clipboard_items(&test_dir, &["old\n", "new\n"]);
+
+        // p3 is past the end of the clipboard
+        execute_command(&mut state, Command::PutClipboardItem(true, 3, 1)).expect("p3");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "one\ntwo"
+        );
+
+        // Below "one", from mid-line; cursor on the new line
+        execute_command(&mut state, Command::MoveRight(2)).expect("l");
+        execute_command(&mut state, Command::PutClipboardItem(true, 1, 1)).expect("p");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "one\nnew\ntwo"
+        );
+        assert_eq!(cursor_byte(&state), 4);
+
+        // Below the last line, which has no newline yet
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(&mut state, Command::PutClipboardItem(true, 2, 1)).expect("p2");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "one\nnew\ntwo\nold\n"
+        );
+        execute_command(&mut state, Command::UndoButtonsCommand).expect("u");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "one\nnew\ntwo"
+        );
+
+        // Above the first line
+        execute_command(&mut state, Command::GotoFileStart).expect("gg");
+        execute_command(&mut state, Command::PutClipboardItem(false, 2, 1)).expect("P2");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "old\none\nnew\ntwo"
+        );
+        assert_eq!(cursor_byte(&state), 0);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod insert_repeat_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_insert_with_count() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("80i\n", EditorMode::Normal),
+            Command::InsertRepeated(80)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("i\n", EditorMode::Normal),
+            Command::EnterInsertMode
+        );
+        assert!(!Command::InsertRepeated(2).is_read_only());
+    }
+
+    #[test]
+    fn test_insert_repeated_sets_bounded_count() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_insert_repeat_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("text.txt");
+        std::fs::write(&file_path, "abc\n").expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+
+        execute_command(&mut state, Command::InsertRepeated(5)).expect("5i");
+        assert_eq!(state.mode, EditorMode::Insert);
+        assert_eq!(state.insert_repeat_count, 5);
+
+        execute_command(&mut state, Command::InsertRepeated(usize::MAX)).expect("huge i");
+        assert_eq!(state.insert_repeat_count, limits::INSERT_REPEAT_MAX);
+
+        // Only entering Insert mode; nothing is typed yet
+        assert_eq!(std::fs::read_to_string(&file_path).expect("read"), "abc\n");
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod scroll_off_tests {
+    use super::editor_test_helpers::state_for;
+    use super::*;
+
+    fn cursor_line(state: &EditorState) -> usize {
+        state.line_count_at_top_of_window + state.cursor.tui_row
+    }
+
+    #[test]
+    fn test_parse_scroll_off() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("so3\n", EditorMode::Normal),
+            Command::SetScrollOff(3)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("so0\n", EditorMode::Normal),
+            Command::SetScrollOff(0)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("so\n", EditorMode::Normal),
+            Command::None
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("so1000\n", EditorMode::Normal),
+            Command::None
+        );
+        assert!(Command::SetScrollOff(3).is_read_only());
+    }
+
+    #[test]
+    fn test_j_and_k_keep_the_margin() {
+        let line_count = 200;
+        let content: String = (1..=line_count).map(|n| format!("line {}\n", n)).collect();
+        let (mut state, test_dir, _file_path) = state_for("scroll_off_jk", &content);
+        execute_command(&mut state, Command::SetScrollOff(3)).expect("so3");
+        let bottom_row = state.effective_rows - 1;
+
+        // Down: the cursor never gets closer than 3 rows to the bottom
+        for step in 1..=(bottom_row + 10) {
+            execute_command(&mut state, Command::MoveDown(1)).expect("j");
+            assert_eq!(cursor_line(&state), step);
+            assert!(state.cursor.tui_row <= bottom_row - 3, "step {}", step);
+        }
+        assert!(state.line_count_at_top_of_window > 0);
+
+        // Up: the window scrolls back before the cursor reaches row 0
+        for _ in 0..(bottom_row + 10) {
+            let line_before = cursor_line(&state);
+            execute_command(&mut state, Command::MoveUp(1)).expect("k");
+            assert_eq!(cursor_line(&state), line_before - 1);
+            if state.line_count_at_top_of_window > 0 {
+                assert!(state.cursor.tui_row >= 3);
+            }
+        }
+        assert_eq!(cursor_line(&state), 0);
+        assert_eq!(state.line_count_at_top_of_window, 0);
+
+        // At the end of the file the cursor may reach the bottom rows
+        execute_command(&mut state, Command::MoveDown(line_count + 10)).expect("j");
+        assert!(state.eof_fileline_tuirow_tuple.is_some());
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod column_ruler_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ruler() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("ruler\n", EditorMode::Normal),
+            Command::SetRuler(None)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("ruler100\n", EditorMode::Normal),
+            Command::SetRuler(Some(100))
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("ruler0\n", EditorMode::Normal),
+            Command::SetRuler(Some(0))
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("ruler+5\n", EditorMode::Normal),
+            Command::None
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("ruler10000\n", EditorMode::Normal),
+            Command::None
+        );
+        assert!(Command::SetRuler(None).is_read_only());
+    }
+
+    #[test]
+    fn test_ruler_marks() {
+        let marks: String = (1..=30).map(|column| ruler_mark(column) as char).collect();
+        assert_eq!(marks, "....+....1....+....2....+....3");
+        assert_eq!(ruler_mark(100), b'0');
+        assert_eq!(ruler_mark(120), b'2');
+    }
+
+    #[test]
+    fn test_ruler_takes_a_row_and_keeps_the_cursor_line() {
+        let test_dir = std::env::temp_dir().join(format!("lines_ruler_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("text.txt");
+        let content: String = (1..=100).map(|n| format!("line {}\n", n)).collect();
+        std::fs::write(&file_path, content).expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+        let rows = state.effective_rows;
+        state.cursor.tui_row = rows - 1;
+
+        // On: one row fewer, the cursor's line scrolled into view
+        execute_command(&mut state, Command::SetRuler(None)).expect("ruler");
+        assert!(state.ruler_shown);
+        assert_eq!(state.effective_rows, rows - 1);
+        assert_eq!(
+            state.line_count_at_top_of_window + state.cursor.tui_row,
+            rows - 1
+        );
+        assert!(state.cursor.tui_row < state.effective_rows);
+        assert_eq!(state.guide_column, Some(DEFAULT_GUIDE_COLUMN));
+
+        // A new guide column while shown takes no further row
+        execute_command(&mut state, Command::SetRuler(Some(100))).expect("ruler100");
+        assert_eq!(state.effective_rows, rows - 1);
+        assert_eq!(state.guide_column, Some(100));
+        execute_command(&mut state, Command::SetRuler(Some(0))).expect("ruler0");
+        assert_eq!(state.guide_column, None);
+
+        // Off: the row comes back
+        execute_command(&mut state, Command::SetRuler(None)).expect("ruler");
+        assert!(!state.ruler_shown);
+        assert_eq!(state.effective_rows, rows);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod forward_search_tests {
+    use super::editor_test_helpers::message;
+    use super::*;
+
+    fn cursor_position(state: &EditorState) -> (usize, usize) {
+        let position = state
+            .get_row_col_file_position(state.cursor.tui_row, state.cursor.tui_visual_col)
+            .expect("position")
+            .expect("cursor on text");
+        (position.line_number, position.byte_in_line)
+    }
+
+    #[test]
+    fn test_parse_search_forward() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("/fn main\n", EditorMode::Normal),
+            Command::SearchForward("fn main".to_string())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("//\n", EditorMode::Normal),
+            Command::SearchForward("/".to_string())
+        );
+        // The comment toggles are unchanged
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("/\n", EditorMode::Normal),
+            Command::ToggleCommentOneLine(0)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("///\n", EditorMode::Normal),
+            Command::ToggleDocstringOneLine(0)
+        );
+        assert!(Command::SearchForward("x".to_string()).is_read_only());
+    }
+
+    #[test]
+    fn test_parse_search_backward() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("?fn main\n", EditorMode::Normal),
+            Command::SearchBackward("fn main".to_string())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("??\n", EditorMode::Normal),
+            Command::SearchBackward("?".to_string())
+        );
+        assert_ne!(
+            state.parse_commands_for_normal_visualselect_modes("?\n", EditorMode::Normal),
+            Command::SearchBackward(String::new())
+        );
+        assert!(Command::SearchBackward("x".to_string()).is_read_only());
+    }
+
+    #[test]
+    fn test_n_and_shift_n_repeat_the_last_search() {
+        let read_copy =
+            std::env::temp_dir().join(format!("lines_search_repeat_{}.txt", std::process::id()));
+        std::fs::write(&read_copy, "a x\nb x\nc x\nd x\ne x\n").expect("write");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("3n\n", EditorMode::Normal),
+            Command::RepeatSearch(3)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("N\n", EditorMode::Normal),
+            Command::RepeatSearchReverse(1)
+        );
+        execute_command(&mut state, Command::RepeatSearch(1)).expect("n");
+        assert_eq!(message(&state), "no search to repeat");
+
+        execute_command(&mut state, Command::SearchForward("x".to_string())).expect("/x");
+        assert_eq!(cursor_position(&state), (0, 2));
+        execute_command(&mut state, Command::RepeatSearch(2)).expect("2n");
+        assert_eq!(cursor_position(&state), (2, 2));
+        execute_command(&mut state, Command::RepeatSearchReverse(1)).expect("N");
+        assert_eq!(cursor_position(&state), (1, 2));
+        // A count past the last match stops on it
+        execute_command(&mut state, Command::RepeatSearch(10)).expect("10n");
+        assert_eq!(cursor_position(&state), (4, 2));
+        assert_eq!(message(&state), "no match");
+
+        // After ?, n goes backward and N forward
+        execute_command(&mut state, Command::SearchBackward("x".to_string())).expect("?x");
+        assert_eq!(cursor_position(&state), (3, 2));
+        execute_command(&mut state, Command::RepeatSearch(1)).expect("n");
+        assert_eq!(cursor_position(&state), (2, 2));
+        execute_command(&mut state, Command::RepeatSearchReverse(1)).expect("N");
+        assert_eq!(cursor_position(&state), (3, 2));
+
+        // Text too long for the buffer is refused, the last search kept
+        let long_pattern = "x".repeat(limits::SEARCH_PATTERN_MAX_BYTES + 1);
+        execute_command(&mut state, Command::SearchForward(long_pattern)).expect("/long");
+        assert_eq!(message(&state), "search text too long");
+        assert_eq!(&state.last_search_pattern[..state.last_search_length], b"x");
+        let _ = std::fs::remove_file(&read_copy);
+    }
+
+    #[test]
+    fn test_search_history_ring_and_recall() {
+        let mut history = SearchHistory::new();
+        assert!(search_history_text(&history).contains("(none yet)"));
+        for number in 0..(SEARCH_HISTORY_SIZE + 2) {
+            history.record(format!("s{}", number).as_bytes(), number % 2 == 0);
+        }
+        // The same search again is kept once
+        history.record(format!("s{}", SEARCH_HISTORY_SIZE + 1).as_bytes(), false);
+        assert_eq!(history.newest_first().count(), SEARCH_HISTORY_SIZE);
+        assert_eq!(
+            history.get(1),
+            Some((format!("s{}", SEARCH_HISTORY_SIZE + 1).as_bytes(), false))
+        );
+        assert_eq!(history.get(SEARCH_HISTORY_SIZE), Some((&b"s2"[..], true)));
+        assert_eq!(history.get(SEARCH_HISTORY_SIZE + 1), None);
+        assert_eq!(history.get(0), None);
+        assert!(search_history_text(&history).contains("   2. /s16\n"));
+
+        let read_copy =
+            std::env::temp_dir().join(format!("lines_search_hist_{}.txt", std::process::id()));
+        std::fs::write(&read_copy, "a x\nb y\nc x\nd y\n").expect("write");
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("hist2\n", EditorMode::Normal),
+            Command::RecallSearch(2)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("hist\n", EditorMode::Normal),
+            Command::None
+        );
+        // "/hist", "/hist2" and "/history" are searches
+        for pattern in ["hist", "hist2", "history"] {
+            assert_eq!(
+                state.parse_commands_for_normal_visualselect_modes(
+                    &format!("/{}\n", pattern),
+                    EditorMode::Normal
+                ),
+                Command::SearchForward(pattern.to_string())
+            );
+        }
+
+        execute_command(&mut state, Command::RecallSearch(1)).expect("hist1");
+        assert_eq!(message(&state), "no such search in hist");
+
+        execute_command(&mut state, Command::SearchForward("x".to_string())).expect("/x");
+        execute_command(&mut state, Command::SearchForward("y".to_string())).expect("/y");
+        assert_eq!(cursor_position(&state), (1, 2));
+        // hist2 is "/x": forward from the cursor, and the newest again
+        execute_command(&mut state, Command::RecallSearch(2)).expect("hist2");
+        assert_eq!(cursor_position(&state), (2, 2));
+        assert_eq!(state.search_history.get(1), Some((&b"x"[..], true)));
+        assert_eq!(&state.last_search_pattern[..state.last_search_length], b"x");
+
+        state.scrub_sensitive_buffers();
+        assert_eq!(state.search_history.get(1), None);
+        let _ = std::fs::remove_file(&read_copy);
+    }
+
+    #[test]
+    fn test_search_in_visual_selection_marks_matches_inside_it() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes(
+                "/ab\n",
+                EditorMode::VisualSelectMode
+            ),
+            Command::SearchInSelection("ab".to_string())
+        );
+        // The range comment toggles are unchanged
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("/\n", EditorMode::VisualSelectMode),
+            Command::ToggleBasicCommentlinesRange
+        );
+        assert_eq!(
+            state
+                .parse_commands_for_normal_visualselect_modes("/b\n", EditorMode::VisualSelectMode),
+            Command::ToggleBlockcomments(0, 0)
+        );
+
+        let read_copy =
+            std::env::temp_dir().join(format!("lines_search_visual_{}.txt", std::process::id()));
+        // "ab" at bytes 0, 3, 6 and 12
+        std::fs::write(&read_copy, "ab ab\nab cd ab\n").expect("write");
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+        state.mode = EditorMode::VisualSelectMode;
+
+        // Selected backward, bytes 12..=3: the "ab" at 12 runs past the end
+        state.file_position_of_vis_select_start = 12;
+        state.file_position_of_vis_select_end = 3;
+        execute_command(&mut state, Command::SearchInSelection("ab".to_string())).expect("/ab");
+        assert_eq!(message(&state), "2 in selection");
+        assert_eq!(
+            state.selection_match_starts[..state.selection_match_count],
+            [3, 6]
+        );
+        assert_eq!(state.selection_match_length, 2);
+        // Kept for n / N
+        assert_eq!(
+            &state.last_search_pattern[..state.last_search_length],
+            b"ab"
+        );
+
+        state.file_position_of_vis_select_start = 13;
+        execute_command(&mut state, Command::SearchInSelection("ab".to_string())).expect("/ab");
+        assert_eq!(
+            state.selection_match_starts[..state.selection_match_count],
+            [3, 6, 12]
+        );
+
+        execute_command(
+            &mut state,
+            Command::SearchInSelection("cd ab\n".to_string()),
+        )
+        .expect("/cd");
+        assert_eq!(message(&state), "no match in selection");
+        assert_eq!(state.selection_match_count, 0);
+        let _ = std::fs::remove_file(&read_copy);
+    }
+
+    #[test]
+    fn test_search_moves_past_the_cursor_and_off_the_window() {
+        let read_copy =
+            std::env::temp_dir().join(format!("lines_search_fwd_{}.txt", std::process::id()));
+        // Matches on line 1 (at the cursor), line 3, and far below the window
+        let mut content = String::from("target one\nfiller\n  target two\n");
+        for n in 0..500 {
+            content.push_str(&format!("line {} with some padding text\n", n));
+        }
+        content.push_str("last target\n");
+        std::fs::write(&read_copy, content).expect("write");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+
+        execute_command(&mut state, Command::SearchForward("target".to_string())).expect("search");
+        assert_eq!(cursor_position(&state), (2, 2));
+        assert_eq!(message(&state), "found at line 3");
+
+        execute_command(&mut state, Command::SearchForward("target".to_string())).expect("search");
+        assert_eq!(cursor_position(&state), (503, 5));
+        assert!(state.line_count_at_top_of_window > 0);
+
+        // Nothing after the last match: the cursor stays
+        execute_command(&mut state, Command::SearchForward("target".to_string())).expect("search");
+        assert_eq!(cursor_position(&state), (503, 5));
+        assert_eq!(message(&state), "no match");
+        let _ = std::fs::remove_file(&read_copy);
+    }
+
+    #[test]
+    fn test_search_backward_from_below_the_window() {
+        let read_copy =
+            std::env::temp_dir().join(format!("lines_search_back_{}.txt", std::process::id()));
+        let mut content = String::from("target one\nfiller\n  target two\n");
+        for n in 0..500 {
+            content.push_str(&format!("line {} with some padding text\n", n));
+        }
+        content.push_str("last target\n");
+        std::fs::write(&read_copy, content).expect("write");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+        execute_command(&mut state, Command::SearchForward("last".to_string())).expect("search");
+        assert_eq!(cursor_position(&state), (503, 0));
+
+        // The match on the cursor's own line is after it: skipped
+        execute_command(&mut state, Command::SearchBackward("target".to_string())).expect("search");
+        assert_eq!(cursor_position(&state), (2, 2));
+        assert_eq!(message(&state), "found at line 3");
+        assert_eq!(state.line_count_at_top_of_window, 2);
+
+        execute_command(&mut state, Command::SearchBackward("target".to_string())).expect("search");
+        assert_eq!(cursor_position(&state), (0, 0));
+
+        // Nothing before the first match: the cursor stays
+        execute_command(&mut state, Command::SearchBackward("target".to_string())).expect("search");
+        assert_eq!(cursor_position(&state), (0, 0));
+        assert_eq!(message(&state), "no match");
+        let _ = std::fs::remove_file(&read_copy);
+    }
+}
+
+mod hunk_navigation_tests {
+    use super::editor_test_helpers::message;
+    use super::*;
+    use crate::diff_review_module::DiffLineKind;
+
+    fn cursor_line(state: &EditorState) -> usize {
+        state
+            .get_row_col_file_position(state.cursor.tui_row, state.cursor.tui_visual_col)
+            .expect("position")
+            .expect("cursor on text")
+            .line_number
+    }
+
+    #[test]
+    fn test_find_previous_byte_pattern() {
+        let path =
+            std::env::temp_dir().join(format!("lines_find_previous_{}.txt", std::process::id()));
+        // Matches at 0, 300 (across the first 256-byte chunk edge from the
+        // end) and 1000
+        let mut content = vec![b'.'; 1200];
+        for start in [0, 300, 1000] {
+            content[start..start + 4].copy_from_slice(b"\n@@ ");
+        }
+        std::fs::write(&path, &content).expect("write");
+
+        assert_eq!(
+            find_previous_byte_pattern(&path, 1200, b"\n@@ ").expect("search"),
+            Some(1000)
+        );
+        assert_eq!(
+            find_previous_byte_pattern(&path, 1000, b"\n@@ ").expect("search"),
+            Some(300)
+        );
+        // A match may run past the offset; only its start counts
+        assert_eq!(
+            find_previous_byte_pattern(&path, 302, b"\n@@ ").expect("search"),
+            Some(300)
+        );
+        assert_eq!(
+            find_previous_byte_pattern(&path, 300, b"\n@@ ").expect("search"),
+            Some(0)
+        );
+        assert_eq!(
+            find_previous_byte_pattern(&path, 0, b"\n@@ ").expect("search"),
+            None
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_hunks_and_line_kinds_of_a_patch() {
+        let test_dir = std::env::temp_dir().join(format!("lines_hunks_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let patch = test_dir.join("fix.patch");
+        let mut content = String::from("--- a/x.rs\n+++ b/x.rs\n@@ -1,3 +1,3 @@ fn a() {\n");
+        content.push_str(" keep\n-old\n+new\n");
+        for n in 0..60 {
+            content.push_str(&format!(" context {}\n", n));
+        }
+        content.push_str("@@ -90,2 +90,2 @@\n-b\n+c\n");
+        std::fs::write(&patch, content).expect("write");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(patch.clone());
+        state.read_copy_path = Some(patch.clone());
+        build_windowmap_nowrap(&mut state, &patch).expect("build window");
+        assert_eq!(
+            &state.diff_row_kinds[..6],
+            &[
+                DiffLineKind::FileHeader,
+                DiffLineKind::FileHeader,
+                DiffLineKind::HunkHeader,
+                DiffLineKind::Context,
+                DiffLineKind::Removed,
+                DiffLineKind::Added,
+            ]
+        );
+
+        execute_command(&mut state, Command::NextHunk(1)).expect("]h");
+        assert_eq!(cursor_line(&state), 2);
+        assert_eq!(message(&state), "@@ -1,3 +1,3 @@");
+
+        execute_command(&mut state, Command::NextHunk(1)).expect("]h");
+        assert_eq!(cursor_line(&state), 66);
+        assert_eq!(message(&state), "@@ -90,2 +90,2 @@");
+        execute_command(&mut state, Command::NextHunk(1)).expect("]h");
+        assert_eq!(cursor_line(&state), 66);
+        assert_eq!(message(&state), "no next hunk");
+
+        execute_command(&mut state, Command::PreviousHunk(1)).expect("[h");
+        assert_eq!(cursor_line(&state), 2);
+        execute_command(&mut state, Command::PreviousHunk(1)).expect("[h");
+        assert_eq!(message(&state), "no previous hunk");
+
+        // A count goes as far as it can
+        execute_command(&mut state, Command::NextHunk(5)).expect("5]h");
+        assert_eq!(cursor_line(&state), 66);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod start_column_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_start_column() {
+        assert_eq!(
+            parse_start_column("nonblank"),
+            Some(StartColumn::FirstNonBlank)
+        );
+        assert_eq!(parse_start_column("1"), Some(StartColumn::LineStart));
+        assert_eq!(parse_start_column("12"), Some(StartColumn::Column(12)));
+        assert_eq!(parse_start_column("0"), None);
+        assert_eq!(parse_start_column("+3"), None);
+        assert_eq!(parse_start_column(""), None);
+    }
+
+    #[test]
+    fn test_parse_file_location() {
+        let location = |path: &str, line, column| (PathBuf::from(path), line, column);
+        assert_eq!(
+            parse_file_location("src/main.rs:120:9"),
+            location("src/main.rs", Some(120), Some(9))
+        );
+        assert_eq!(
+            parse_file_location("notes.txt:42"),
+            location("notes.txt", Some(42), None)
+        );
+        assert_eq!(
+            parse_file_location("notes.txt"),
+            location("notes.txt", None, None)
+        );
+        assert_eq!(
+            parse_file_location("dir:x/notes.txt:7:3"),
+            location("dir:x/notes.txt", Some(7), Some(3))
+        );
+        // Not numbers above 0: part of the name
+        assert_eq!(
+            parse_file_location("my:file.txt"),
+            location("my:file.txt", None, None)
+        );
+        assert_eq!(
+            parse_file_location("notes.txt:12:0"),
+            location("notes.txt:12:0", None, None)
+        );
+        assert_eq!(
+            parse_file_location("notes.txt:0:5"),
+            location("notes.txt:0", Some(5), None)
+        );
+    }
+
+    #[test]
+    fn test_place_cursor_on_open() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_start_column_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("text.txt");
+        std::fs::write(&file_path, "    let x = 1;\nnext\n").expect("write test file");
+
+        let byte_at_cursor = |state: &EditorState| {
+            state
+                .get_row_col_file_position(state.cursor.tui_row, state.cursor.tui_visual_col)
+                .expect("position")
+                .expect("mapped cell")
+                .byte_offset_linear_file_absolute_position
+        };
+        let opened = || {
+            let mut state = EditorState::new();
+            state.read_copy_path = Some(file_path.clone());
+            build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+            state.cursor.tui_visual_col = (0..state.effective_cols)
+                .find(|&col| matches!(state.get_row_col_file_position(0, col), Ok(Some(_))))
+                .expect("row 0 has a mapped cell");
+            state
+        };
+
+        let mut state = opened();
+        place_cursor_on_open(&mut state, &file_path, StartColumn::LineStart).expect("start");
+        assert_eq!(byte_at_cursor(&state), 0);
+
+        let mut state = opened();
+        place_cursor_on_open(&mut state, &file_path, StartColumn::FirstNonBlank).expect("start");
+        assert_eq!(byte_at_cursor(&state), 4);
+
+        let mut state = opened();
+        place_cursor_on_open(&mut state, &file_path, StartColumn::Column(9)).expect("start");
+        assert_eq!(byte_at_cursor(&state), 8);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod special_key_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_special_keys_normal_and_visual() {
+        let mut state = EditorState::new();
+        let page_rows = state.effective_rows.saturating_sub(1).max(1);
+
+        for mode in [EditorMode::Normal, EditorMode::VisualSelectMode] {
+            assert_eq!(
+                state.parse_commands_for_normal_visualselect_modes("\x1b[A\n", mode),
+                Command::MoveUp(1)
+            );
+            assert_eq!(
+                state.parse_commands_for_normal_visualselect_modes("\x1bOB\x1bOB\n", mode),
+                Command::MoveDown(2)
+            );
+            assert_eq!(
+                state.parse_commands_for_normal_visualselect_modes("3\x1b[1;5C\n", mode),
+                Command::MoveRight(3)
+            );
+            assert_eq!(
+                state.parse_commands_for_normal_visualselect_modes("\x1b[1~\n", mode),
+                Command::GotoLineStart
+            );
+            assert_eq!(
+                state.parse_commands_for_normal_visualselect_modes("\x1b[F\n", mode),
+                Command::GotoLineEnd
+            );
+            assert_eq!(
+                state.parse_commands_for_normal_visualselect_modes("\x1b[6~\n", mode),
+                Command::MoveDown(page_rows)
+            );
+            assert_eq!(
+                state.parse_commands_for_normal_visualselect_modes("\x1b[3~\n", mode),
+                Command::DeleteBackspace
+            );
+            assert_eq!(
+                state.parse_commands_for_normal_visualselect_modes("\x1b[A\x1b[D\n", mode),
+                Command::None
+            );
+        }
+    }
+}
+
+mod key_sequence_tests {
+    use super::editor_test_helpers::message;
+    use super::*;
+    use crate::keymap_module::KeyWait;
+
+    #[test]
+    fn test_parse_key_sequences() {
+        let mut state = EditorState::new();
+        let mut parse = |input: &str, mode: EditorMode| {
+            state.parse_commands_for_normal_visualselect_modes(input, mode)
+        };
+
+        assert_eq!(parse("gg\n", EditorMode::Normal), Command::GotoFileStart);
+        assert_eq!(
+            parse("ge\n", EditorMode::VisualSelectMode),
+            Command::GotoFileLastLine
+        );
+        assert_eq!(parse("G\n", EditorMode::Normal), Command::GotoFileLastLine);
+        assert_eq!(
+            parse("G\n", EditorMode::VisualSelectMode),
+            Command::GotoFileLastLine
+        );
+        assert_eq!(parse("gl\n", EditorMode::Normal), Command::GotoLineEnd);
+        assert_eq!(parse("d\n", EditorMode::Normal), Command::DeleteLine);
+        assert_eq!(parse("dd\n", EditorMode::Normal), Command::DeleteLine);
+        assert_eq!(parse("3yy\n", EditorMode::Normal), Command::YankLines(3));
+        assert_eq!(parse("yW\n", EditorMode::Normal), Command::YankWord(true));
+        assert_eq!(
+            parse("d\n", EditorMode::VisualSelectMode),
+            Command::DeleteRange
+        );
+        assert_eq!(parse("g12\n", EditorMode::Normal), Command::GotoLine(12));
+        assert_eq!(
+            parse("g+5\n", EditorMode::Normal),
+            Command::GotoLineRelative(5)
+        );
+        assert_eq!(
+            parse("g-12\n", EditorMode::VisualSelectMode),
+            Command::GotoLineRelative(-12)
+        );
+        assert_eq!(parse("g+\n", EditorMode::Normal), Command::None);
+        assert_eq!(parse("gx\n", EditorMode::Normal), Command::None);
+        assert_eq!(
+            parse("gb1234\n", EditorMode::Normal),
+            Command::GotoByte(1234)
+        );
+        assert_eq!(
+            parse("gb0x4D2\n", EditorMode::VisualSelectMode),
+            Command::GotoByte(1234)
+        );
+        assert_eq!(parse("gb12ab\n", EditorMode::Normal), Command::None);
+        assert_eq!(parse("gbz\n", EditorMode::Normal), Command::None);
+
+        // Line start / end aliases; "0" is not a count
+        assert_eq!(parse("0\n", EditorMode::Normal), Command::GotoLineStart);
+        assert_eq!(
+            parse("gh\n", EditorMode::VisualSelectMode),
+            Command::GotoLineStart
+        );
+        assert_eq!(parse("$\n", EditorMode::Normal), Command::GotoLineEnd);
+        assert_eq!(
+            parse("$\n", EditorMode::VisualSelectMode),
+            Command::GotoLineEnd
+        );
+    }
+
+    #[test]
+    fn test_relative_goto_moves_from_the_cursor_line() {
+        let read_copy =
+            std::env::temp_dir().join(format!("lines_goto_relative_{}.txt", std::process::id()));
+        let text: String = (1..=40).map(|n| format!("line {}\n", n)).collect();
+        std::fs::write(&read_copy, text).expect("write");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+        let cursor_line = |state: &EditorState| {
+            state
+                .get_row_col_file_position(state.cursor.tui_row, state.cursor.tui_visual_col)
+                .expect("position")
+                .expect("on a line")
+                .line_number
+        };
+
+        execute_command(&mut state, Command::GotoLine(20)).expect("g20");
+        execute_command(&mut state, Command::GotoLineRelative(5)).expect("g+5");
+        assert_eq!(cursor_line(&state), 24);
+        execute_command(&mut state, Command::GotoLineRelative(-12)).expect("g-12");
+        assert_eq!(cursor_line(&state), 12);
+        // Above the first line: stops at line 1
+        execute_command(&mut state, Command::GotoLineRelative(-100)).expect("g-100");
+        assert_eq!(cursor_line(&state), 0);
+        // Past the last line: stops at it, and says so
+        execute_command(&mut state, Command::GotoLineRelative(100)).expect("g+100");
+        assert_eq!(cursor_line(&state), 39);
+        execute_command(&mut state, Command::GotoLine(1)).expect("g1");
+        execute_command(&mut state, Command::GotoLine(500)).expect("g500");
+        assert_eq!(cursor_line(&state), 39);
+        assert_eq!(message(&state), "No line 500; at last line");
+
+        let _ = std::fs::remove_file(&read_copy);
+    }
+
+    #[test]
+    fn test_goto_byte_lands_on_its_character() {
+        let read_copy =
+            std::env::temp_dir().join(format!("lines_goto_byte_{}.txt", std::process::id()));
+        // "é" is bytes 5-6 (line 2); line 3 is wider than the window
+        std::fs::write(&read_copy, format!("abc\nxé z\n{}Q\n", "w".repeat(300))).expect("write");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+        let cursor = |state: &EditorState| {
+            let position = state
+                .get_row_col_file_position(state.cursor.tui_row, state.cursor.tui_visual_col)
+                .expect("position")
+                .expect("on a line");
+            (
+                position.line_number,
+                position.byte_offset_linear_file_absolute_position,
+            )
+        };
+
+        execute_command(&mut state, Command::GotoByte(2)).expect("gb2");
+        assert_eq!(cursor(&state), (0, 2));
+        assert_eq!(message(&state), "byte 2: line 1");
+
+        // Inside "é": its first byte
+        execute_command(&mut state, Command::GotoByte(6)).expect("gb6");
+        assert_eq!(cursor(&state), (1, 5));
+        assert_eq!(message(&state), "byte 5: line 2");
+
+        // Far along a long line: scrolled there
+        execute_command(&mut state, Command::GotoByte(10 + 300)).expect("gb310");
+        assert_eq!(cursor(&state), (2, 310));
+        assert!(state.tui_window_horizontal_utf8txt_line_char_offset > 0);
+
+        // Past the end: the last byte
+        execute_command(&mut state, Command::GotoByte(9999)).expect("gb9999");
+        assert_eq!(cursor(&state), (2, 310));
+        assert_eq!(message(&state), "past end; at byte 310");
+
+        assert_eq!(parse_byte_offset("0X1f"), Some(31));
+        assert_eq!(parse_byte_offset("0x"), None);
+        assert_eq!(parse_byte_offset("99999999999999999999999"), None);
+
+        let _ = std::fs::remove_file(&read_copy);
+    }
+
+    #[test]
+    fn test_moving_to_a_short_line_scrolls_it_back_into_view() {
+        let read_copy =
+            std::env::temp_dir().join(format!("lines_hscroll_{}.txt", std::process::id()));
+        std::fs::write(&read_copy, format!("{}\nab\n", "x".repeat(300))).expect("write");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+        execute_command(&mut state, Command::GotoLineEnd).expect("gl");
+        assert!(state.tui_window_horizontal_utf8txt_line_char_offset > 0);
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        state.info_bar_message_buffer = [0u8; INFOBAR_MESSAGE_BUFFER_SIZE];
+        state.info_bar_message_buffer[..5].copy_from_slice(b"moved");
+
+        keep_cursor_on_line_text(&mut state, &read_copy).expect("keep in view");
+        assert_eq!(state.tui_window_horizontal_utf8txt_line_char_offset, 0);
+        let position = state
+            .get_row_col_file_position(state.cursor.tui_row, state.cursor.tui_visual_col)
+            .expect("position")
+            .expect("on the short line");
+        assert_eq!((position.line_number, position.byte_in_line), (1, 1));
+        assert_eq!(&state.info_bar_message_buffer[..5], b"moved");
+
+        // Already on text: nothing changes
+        let column = state.cursor.tui_visual_col;
+        keep_cursor_on_line_text(&mut state, &read_copy).expect("no-op");
+        assert_eq!(state.cursor.tui_visual_col, column);
+
+        let _ = std::fs::remove_file(&read_copy);
+    }
+
+    #[test]
+    fn test_gl_gh_on_a_line_wider_than_the_window() {
+        let read_copy =
+            std::env::temp_dir().join(format!("lines_gl_gh_{}.txt", std::process::id()));
+        let wide_line = "x".repeat(300);
+        std::fs::write(
+            &read_copy,
+            format!(
+                "short\n{}\n{}\tz\n{}é\n",
+                wide_line,
+                "y".repeat(200),
+                "x".repeat(298)
+            ),
+        )
+        .expect("write");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+
+        let byte_in_line = |state: &EditorState| {
+            state
+                .get_row_col_file_position(state.cursor.tui_row, state.cursor.tui_visual_col)
+                .expect("position")
+                .expect("on a line")
+                .byte_in_line
+        };
+
+        execute_command(&mut state, Command::GotoLineEnd).expect("gl");
+        assert!(state.tui_window_horizontal_utf8txt_line_char_offset > 0);
+        assert_eq!(byte_in_line(&state), 299);
+
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+        assert_eq!(state.tui_window_horizontal_utf8txt_line_char_offset, 0);
+        assert_eq!(byte_in_line(&state), 0);
+
+        // Off-screen ends after a tab, and on a two-byte character (its
+        // first byte)
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(&mut state, Command::GotoLineEnd).expect("gl");
+        assert_eq!(byte_in_line(&state), 201);
+        assert!(state.cursor.tui_visual_col < state.effective_cols);
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(&mut state, Command::GotoLineEnd).expect("gl");
+        assert_eq!(byte_in_line(&state), 298);
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+        assert_eq!(state.tui_window_horizontal_utf8txt_line_char_offset, 0);
+        assert_eq!(byte_in_line(&state), 0);
+
+        let _ = std::fs::remove_file(&read_copy);
+    }
+
+    #[test]
+    fn test_normal_visual_keys_wait() {
+        let normal = |keys: &str| normal_visual_keys_wait(EditorMode::Normal, keys.as_bytes());
+        let visual =
+            |keys: &str| normal_visual_keys_wait(EditorMode::VisualSelectMode, keys.as_bytes());
+
+        for waiting in [
+            "", "5", "g", "g45", "gc", "gc1", "gb", "gb0x1F", "y", "3f", "t", "c", "c3", "cf",
+        ] {
+            assert_eq!(normal(waiting), KeyWait::NeedMore, "{:?}", waiting);
+        }
+        for ready in ["j", "10j", "gg", "yw", "f,", "cw", "cf.", "c3w", "dd", "Y"] {
+            assert!(
+                matches!(normal(ready), KeyWait::Ready | KeyWait::NoMatch),
+                "{:?}",
+                ready
+            );
+        }
+        assert_eq!(normal("d"), KeyWait::ReadyAfterTimeout);
+        assert_eq!(normal("dj"), KeyWait::NoMatch);
+        assert_eq!(normal("0"), KeyWait::Ready);
+        assert_eq!(normal("10"), KeyWait::NeedMore);
+        assert_eq!(normal("g-"), KeyWait::NeedMore);
+        assert_eq!(visual("g+12"), KeyWait::NeedMore);
+        assert_eq!(visual("$"), KeyWait::Ready);
+        assert_eq!(
+            normal_visual_keys_wait(EditorMode::Normal, &"f\u{e9}".as_bytes()[..2]),
+            KeyWait::NeedMore
+        );
+
+        assert_eq!(visual("d"), KeyWait::NoMatch);
+        assert_eq!(visual("c"), KeyWait::NoMatch);
+        assert_eq!(visual("g"), KeyWait::NeedMore);
+    }
+}
+
+mod message_catalog_tests {
+    use crate::message_catalog_module::CATALOG_MESSAGES;
+
+    /// Every fixed info-bar message in the editor is in the catalog, so the
+    /// --messages-template file offers it for replacement
+    #[test]
+    fn test_catalog_lists_every_info_bar_message() {
+        let source = include_str!("lines_editor_module.rs");
+        let call = "set_info_bar_message(\"";
+        let mut missing = Vec::new();
+
+        for (start, _) in source.match_indices(call) {
+            let rest = &source[start + call.len()..];
+            let Some(end) = rest.find("\")") else {
+                continue;
+            };
+            let text = &rest[..end];
+            if !text.is_empty() && !text.contains('"') && !CATALOG_MESSAGES.contains(&text) {
+                missing.push(text);
+            }
+        }
+        assert!(missing.is_empty(), "not in CATALOG_MESSAGES: {:?}", missing);
+    }
+}
+
+mod screen_reader_tests {
+    use super::*;
+
+    #[test]
+    fn test_screen_reader_status_text() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_screen_reader_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("notes.txt");
+        std::fs::write(&file_path, "first\n\tcafé x\n").expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        state.original_file_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+        state.cursor.tui_row = 1;
+        state.cursor.tui_visual_col = (0..state.effective_cols)
+            .find(|&col| matches!(state.get_row_col_file_position(1, col), Ok(Some(_))))
+            .expect("row 1 has a mapped cell");
+
+        let status = screen_reader_status_text(&state);
+        assert_eq!(
+            status,
+            "NORMAL, line 2, column 1, notes.txt\n2: \tcafé x\non: tab\n"
+        );
+        assert!(!status.contains('\x1b'));
+
+        state.info_bar_message_buffer[..5].copy_from_slice(b"Saved");
+        assert!(screen_reader_status_text(&state).ends_with("note: Saved\n"));
+
+        state.mode = EditorMode::HexMode;
+        state.hex_cursor.byte_offset_linear_file_absolute_position = 1;
+        assert!(
+            screen_reader_status_text(&state)
+                .starts_with("HEX, byte 1 of 15, notes.txt\nbyte 1: 0x69, i\n")
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod screenshot_tests {
+    use super::*;
+
+    #[test]
+    fn test_screenshot_written_outside_session() {
+        let lines_data = std::env::temp_dir().join(format!(
+            "lines_screenshot_{}/lines_data",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&lines_data);
+        let session_dir = lines_data.join("sessions").join("now");
+        std::fs::create_dir_all(&session_dir).expect("create session dir");
+        let file_path = session_dir.join("shot.txt");
+        std::fs::write(&file_path, "screenshot line\n").expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        state.original_file_path = Some(file_path.clone());
+        state.session_directory_path = Some(session_dir.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+
+        execute_command(&mut state, Command::Screenshot(false)).expect("plain shot");
+        execute_command(&mut state, Command::Screenshot(true)).expect("colour shot");
+
+        let shots: Vec<_> = std::fs::read_dir(lines_data.join("screenshots"))
+            .expect("screenshot dir")
+            .map(|entry| entry.expect("entry").path())
+            .collect();
+        assert_eq!(shots.len(), 2);
+        let shot_with = |extension: &str| {
+            shots
+                .iter()
+                .find(|path| path.extension() == Some(std::ffi::OsStr::new(extension)))
+                .expect("screenshot with extension")
+        };
+        let colour = std::fs::read(shot_with("ansi")).expect("read .ansi");
+        let plain = std::fs::read_to_string(shot_with("txt")).expect("read .txt");
+        assert!(colour.contains(&0x1B));
+        assert!(!plain.contains('\x1b'));
+        assert!(plain.contains("screenshot line"));
+        assert!(plain.contains("NORMAL"));
+
+        state.security_mode = true;
+        execute_command(&mut state, Command::Screenshot(false)).expect("refused shot");
+        assert_eq!(
+            std::fs::read_dir(lines_data.join("screenshots"))
+                .expect("screenshot dir")
+                .count(),
+            2
+        );
+        let _ = std::fs::remove_dir_all(lines_data.parent().expect("test root"));
+    }
+}
+
+mod eof_marker_tests {
+    use super::*;
+    use crate::buffy_format_write_module::{
+        BUFFY_FRAME_DEFAULT_CAPACITY, BuffyFrame, buffy_strip_ansi,
+    };
+
+    fn screen_rows(state: &EditorState) -> Vec<String> {
+        let mut frame = BuffyFrame::with_capacity(BUFFY_FRAME_DEFAULT_CAPACITY);
+        write_tui_utf8txt_frame(state, &mut frame).expect("render");
+        String::from_utf8(buffy_strip_ansi(frame.as_bytes()))
+            .expect("utf-8 screen")
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_rows_past_eof_get_the_marker_and_empty_lines_do_not() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_eof_marker_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("text.txt");
+        std::fs::write(&file_path, "first\n\nthird\n").expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+        assert!(state.eof_fileline_tuirow_tuple.is_some());
+
+        // Legend, then one row per display row
+        let rows = screen_rows(&state);
+        assert!(rows[1].contains("first"));
+        assert!(!rows[2].contains('~'));
+        assert!(rows[3].contains("third"));
+        assert_eq!(rows[4], "~");
+        assert_eq!(rows[state.effective_rows], "~");
+
+        // A file longer than the window has no marker
+        let content: String = (1..=100).map(|n| format!("line {}\n", n)).collect();
+        std::fs::write(&file_path, content).expect("write test file");
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+        assert!(
+            screen_rows(&state)[1..=state.effective_rows]
+                .iter()
+                .all(|row| row != "~")
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod file_stats_tests {
+    use super::editor_test_helpers::{message, state_for};
+    use super::*;
+    use crate::buffy_format_write_module::{
+        BUFFY_FRAME_DEFAULT_CAPACITY, BuffyFrame, buffy_strip_ansi,
+    };
+
+    #[test]
+    fn test_count_file_stats() {
+        let (_, test_dir, file_path) = state_for("file_stats_count", "one\ntwo\nthree");
+        let stats = count_file_stats(&file_path).expect("count");
+        assert_eq!(stats.newline_count, 2);
+        assert_eq!(stats.byte_size, 13);
+        assert!(!stats.ends_with_newline);
+        assert_eq!(stats.line_count(), 3);
+
+        std::fs::write(&file_path, "one\n").expect("write");
+        assert_eq!(count_file_stats(&file_path).expect("count").line_count(), 1);
+        std::fs::write(&file_path, "").expect("write");
+        assert_eq!(count_file_stats(&file_path).expect("count").line_count(), 0);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_edits_adjust_the_cache_without_a_recount() {
+        let (mut state, test_dir, file_path) = state_for("file_stats_adjust", "alpha\nbeta\n");
+        state.refresh_file_stats(&file_path);
+        assert_eq!(state.file_stats.map(|stats| stats.line_count()), Some(2));
+
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+        insert_text_chunk_at_cursor_position(&mut state, &file_path, b"x\ny").expect("insert");
+        assert_eq!(
+            state.file_stats,
+            Some(count_file_stats(&file_path).expect("count"))
+        );
+        assert_eq!(state.file_stats.map(|stats| stats.line_count()), Some(3));
+
+        execute_command(&mut state, Command::InsertNewline('\n')).expect("newline");
+        assert_eq!(
+            state.file_stats,
+            Some(count_file_stats(&file_path).expect("count"))
+        );
+
+        // Joins line 2 to line 1: one newline fewer
+        let line_count = state.file_stats.map(|stats| stats.line_count());
+        execute_command(&mut state, Command::GotoLine(2)).expect("g2");
+        execute_command(&mut state, Command::DeleteBackspace).expect("backspace");
+        assert_eq!(
+            state.file_stats,
+            Some(count_file_stats(&file_path).expect("count"))
+        );
+        assert_eq!(
+            state.file_stats.map(|stats| stats.line_count()),
+            line_count.map(|count| count - 1)
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_stale_cache_is_counted_again() {
+        let (mut state, test_dir, file_path) = state_for("file_stats_stale", "a\nb\n");
+        state.refresh_file_stats(&file_path);
+        std::fs::write(&file_path, "a\nb\nc\nd").expect("write");
+        assert_eq!(state.file_stats(&file_path).expect("stats").line_count(), 4);
+
+        // An edit whose size does not match drops the cache
+        std::fs::write(&file_path, "a\n").expect("write");
+        state.adjust_file_stats(&file_path, 1, 0);
+        assert_eq!(state.file_stats, None);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_last_line_without_newline_and_info_bar_total() {
+        let (mut state, test_dir, _) = state_for("file_stats_last", "one\ntwo\nthree");
+        execute_command(&mut state, Command::GotoFileLastLine).expect("G");
+        assert_eq!(state.line_count_at_top_of_window + state.cursor.tui_row, 2);
+
+        execute_command(&mut state, Command::GotoLine(1)).expect("g1");
+        state.info_bar_message_buffer = [0; INFOBAR_MESSAGE_BUFFER_SIZE];
+        let mut frame = BuffyFrame::with_capacity(BUFFY_FRAME_DEFAULT_CAPACITY);
+        write_tui_utf8txt_frame(&state, &mut frame).expect("render");
+        let screen = String::from_utf8(buffy_strip_ansi(frame.as_bytes())).expect("utf-8");
+        let info_bar = screen.lines().last().expect("info bar");
+        assert!(info_bar.starts_with("NORMAL 1/3:0 "), "{}", info_bar);
+        assert!(info_bar.contains(" @0 33% "), "{}", info_bar);
+
+        // Not known: no total shown
+        state.file_stats = None;
+        write_tui_utf8txt_frame(&state, &mut frame).expect("render");
+        let screen = String::from_utf8(buffy_strip_ansi(frame.as_bytes())).expect("utf-8");
+        assert!(
+            screen
+                .lines()
+                .last()
+                .expect("info bar")
+                .starts_with("NORMAL 1:0 ")
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_file_end_fills_window_and_file_start_returns() {
+        let content: String = (1..=60).map(|n| format!("line {}\n", n)).collect();
+        let (mut state, test_dir, _) = state_for("file_stats_ends", content.trim_end());
+        let rows = state.effective_rows;
+        assert!(rows > 1 && rows < 60);
+
+        // G: line 60 on the bottom row, the lines before it above
+        execute_command(&mut state, Command::GotoFileLastLine).expect("G");
+        assert_eq!(state.line_count_at_top_of_window, 60 - rows);
+        assert_eq!(state.cursor.tui_row, rows - 1);
+        let position = state
+            .get_row_col_file_position(state.cursor.tui_row, state.cursor.tui_visual_col)
+            .expect("position")
+            .expect("cursor on text");
+        assert_eq!(position.line_number, 59);
+
+        // gg: line 1, first character, said as such
+        execute_command(&mut state, Command::GotoFileStart).expect("gg");
+        assert_eq!(state.line_count_at_top_of_window, 0);
+        assert_eq!(state.cursor.tui_row, 0);
+        let position = state
+            .get_row_col_file_position(0, state.cursor.tui_visual_col)
+            .expect("position")
+            .expect("cursor on text");
+        assert_eq!(position.byte_offset_linear_file_absolute_position, 0);
+        assert_eq!(message(&state), "Start of file");
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod print_range_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_print_range() {
+        assert_eq!(
+            parse_print_range("notes.txt:100-150"),
+            Some((PathBuf::from("notes.txt"), 100, 150))
+        );
+        assert_eq!(
+            parse_print_range("dir:x/notes.txt:7"),
+            Some((PathBuf::from("dir:x/notes.txt"), 7, 7))
+        );
+        assert_eq!(parse_print_range("notes.txt"), None);
+        assert_eq!(parse_print_range("notes.txt:0-3"), None);
+        assert_eq!(parse_print_range("notes.txt:9-3"), None);
+        assert_eq!(parse_print_range("notes.txt:3-"), None);
+        assert_eq!(parse_print_range(":3"), None);
+    }
+
+    #[test]
+    fn test_write_line_range() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_print_range_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("text.txt");
+        let long_line = "x".repeat(limits::LONG_LINE_SCAN_BYTES * 2 + 5);
+        std::fs::write(
+            &file_path,
+            format!(
+                "one\ntwo\n{}\nfour\nfive\nsix\nseven\neight\nnine\nten\neleven",
+                long_line
+            ),
+        )
+        .expect("write test file");
+
+        let mut out = Vec::new();
+        assert_eq!(
+            write_line_range(&mut out, &file_path, 2, 4, LineRangeFormat::Plain).expect("2-4"),
+            3
+        );
+        assert_eq!(out, format!("two\n{}\nfour\n", long_line).as_bytes());
+
+        let mut out = Vec::new();
+        write_line_range(&mut out, &file_path, 9, 11, LineRangeFormat::Numbered)
+            .expect("9-11 numbered");
+        assert_eq!(out, b" 9 nine\n10 ten\n11 eleven\n");
+
+        // --porcelain: number, tab, the line as it is
+        let mut out = Vec::new();
+        write_line_range(&mut out, &file_path, 9, 11, LineRangeFormat::Porcelain)
+            .expect("9-11 porcelain");
+        assert_eq!(out, b"9\tnine\n10\tten\n11\televen\n");
+
+        // Past the end: prints what there is
+        let mut out = Vec::new();
+        assert_eq!(
+            write_line_range(&mut out, &file_path, 11, 40, LineRangeFormat::Plain).expect("11-40"),
+            1
+        );
+        assert_eq!(out, b"eleven\n");
+
+        let mut out = Vec::new();
+        assert!(write_line_range(&mut out, &file_path, 12, 14, LineRangeFormat::Plain).is_err());
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod memo_wrap_tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_memo_display_line() {
+        assert_eq!(
+            wrap_memo_display_line("went to the market today", 10),
+            vec!["went to ", "the market", "today"]
+        );
+        assert_eq!(
+            wrap_memo_display_line("abcdefghijkl", 5),
+            vec!["abcde", "fghij", "kl"]
+        );
+        assert_eq!(wrap_memo_display_line("short", 40), vec!["short"]);
+        assert_eq!(wrap_memo_display_line("", 40), vec![""]);
+        // Double-width characters take two cells
+        assert_eq!(
+            wrap_memo_display_line("日本語の日記", 5),
+            vec!["日本", "語の", "日記"]
+        );
+        let line = "a long line of journal text that wraps on a phone";
+        for row in wrap_memo_display_line(line, 12) {
+            assert!(row.chars().count() <= 12);
+        }
+        assert_eq!(
+            wrap_memo_display_line(line, 12).concat().replace(' ', ""),
+            line.replace(' ', "")
+        );
+    }
+}
+
+mod save_lock_tests {
+    use super::*;
+
+    #[test]
+    fn test_save_waits_out_then_refuses_a_held_lock() {
+        let test_dir = std::env::temp_dir().join(format!("lines_save_lock_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let original = test_dir.join("locked.txt");
+        let read_copy = test_dir.join("read_copy.txt");
+        std::fs::write(&original, "old\n").expect("write original");
+        std::fs::write(&read_copy, "new\n").expect("write read-copy");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(original.clone());
+        state.read_copy_path = Some(read_copy.clone());
+
+        // Another "instance" holds the lock
+        let holder = File::open(&original).expect("open for lock");
+        holder.lock().expect("hold lock");
+        let error = save_file(&mut state).expect_err("locked save must fail");
+        assert_eq!(error.kind(), io::ErrorKind::WouldBlock);
+        assert_eq!(std::fs::read_to_string(&original).expect("read"), "old\n");
+
+        drop(holder);
+        save_file(&mut state).expect("unlocked save");
+        assert_eq!(std::fs::read_to_string(&original).expect("read"), "new\n");
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod reload_tests {
+    use super::editor_test_helpers::message;
+    use super::*;
+
+    fn touch_later(path: &Path, seconds: u64) {
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .expect("open");
+        file.set_modified(std::time::SystemTime::now() + std::time::Duration::from_secs(seconds))
+            .expect("set mtime");
+    }
+
+    #[test]
+    fn test_outside_change_offers_then_reloads() {
+        let test_dir = std::env::temp_dir().join(format!("lines_reload_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let original = test_dir.join("gen.txt");
+        let read_copy = test_dir.join("read_copy.txt");
+        std::fs::write(&original, "old one\nold two\n").expect("write original");
+        std::fs::copy(&original, &read_copy).expect("read-copy");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(original.clone());
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+
+        // First look only records the time
+        check_original_file_changed(&mut state);
+        assert_eq!(message(&state), "");
+
+        // Unsaved edits: warn, never reload
+        std::fs::write(&original, "generated\n").expect("rewrite original");
+        touch_later(&original, 10);
+        state.is_modified = true;
+        check_original_file_changed(&mut state);
+        assert_eq!(message(&state), "changed on disk; unsaved here");
+        execute_command(&mut state, Command::ReloadFromDisk).expect("refused rl");
+        assert_eq!(message(&state), "unsaved changes; not reloaded");
+        assert_eq!(
+            std::fs::read_to_string(&read_copy).expect("read"),
+            "old one\nold two\n"
+        );
+
+        // Clean buffer: offer `rl`, once per change
+        state.is_modified = false;
+        touch_later(&original, 20);
+        check_original_file_changed(&mut state);
+        assert_eq!(message(&state), "file changed: rl reloads");
+        state.info_bar_message_buffer = [0u8; INFOBAR_MESSAGE_BUFFER_SIZE];
+        check_original_file_changed(&mut state);
+        assert_eq!(message(&state), "");
+
+        execute_command(&mut state, Command::ReloadFromDisk).expect("rl");
+        assert_eq!(message(&state), "reloaded from disk");
+        assert_eq!(
+            std::fs::read_to_string(&read_copy).expect("read"),
+            "generated\n"
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod quit_protection_tests {
+    use super::editor_test_helpers::message;
+    use super::*;
+    use crate::keymap_module::KeyWait;
+
+    /// Editor state over a throw-away read-copy (not under sessions/, so
+    /// quitting leaves it alone)
+    fn state_with_read_copy(name: &str) -> (EditorState, PathBuf) {
+        let read_copy =
+            std::env::temp_dir().join(format!("lines_quit_{}_{}.txt", name, std::process::id()));
+        std::fs::write(&read_copy, "one\n").expect("write read-copy");
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(read_copy.clone());
+        (state, read_copy)
+    }
+
+    #[test]
+    fn test_q_refuses_unsaved_changes_and_q_bang_discards() {
+        let (mut state, read_copy) = state_with_read_copy("discard");
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("q!", EditorMode::Normal),
+            Command::QuitDiscardingChanges
+        );
+
+        state.is_modified = true;
+        assert!(execute_command(&mut state, Command::Quit).expect("q"));
+        assert_eq!(message(&state), "unsaved: wq saves, q! discards");
+        assert!(!unsaved_changes_discarded());
+
+        assert!(!execute_command(&mut state, Command::QuitDiscardingChanges).expect("q!"));
+        assert!(unsaved_changes_discarded());
+        let _ = std::fs::remove_file(&read_copy);
+    }
+
+    #[test]
+    fn test_q_quits_a_clean_buffer() {
+        let (mut state, read_copy) = state_with_read_copy("clean");
+        assert!(!execute_command(&mut state, Command::Quit).expect("q"));
+        let _ = std::fs::remove_file(&read_copy);
+        assert_eq!(
+            normal_visual_keys_wait(EditorMode::Normal, b"q"),
+            KeyWait::ReadyAfterTimeout
+        );
+        assert_eq!(
+            normal_visual_keys_wait(EditorMode::Normal, b"q!"),
+            KeyWait::Ready
+        );
+    }
+}
+
+mod save_summary_tests {
+    use super::*;
+
+    #[test]
+    fn test_save_reports_bytes_lines_and_archive() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_save_summary_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let original = test_dir.join("notes.txt");
+        let read_copy = test_dir.join("read_copy.txt");
+        std::fs::write(&original, "old\n").expect("write original");
+        std::fs::write(&read_copy, "one\ntwo\nthree").expect("write read-copy");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(original.clone());
+        state.read_copy_path = Some(read_copy.clone());
+
+        let summary = save_file(&mut state).expect("save");
+        assert_eq!(summary.bytes_written, 13);
+        assert_eq!(summary.line_count, 3);
+        let archive_name = summary.archive_name.clone().expect("backup made");
+        assert!(archive_name.ends_with("_notes.txt"));
+        assert!(test_dir.join("archive").join(&archive_name).exists());
+
+        let info = summary.info_bar_text();
+        assert!(info.starts_with("saved 13 B, 3L, "), "{}", info);
+        assert!(info.len() < INFOBAR_MESSAGE_BUFFER_SIZE);
+        assert!(summary.full_text().ends_with(&archive_name));
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_new_file_save_has_no_archive() {
+        let test_dir = std::env::temp_dir().join(format!("lines_save_new_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let read_copy = test_dir.join("read_copy.txt");
+        std::fs::write(&read_copy, "").expect("write read-copy");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(test_dir.join("fresh.txt"));
+        state.read_copy_path = Some(read_copy);
+
+        let summary = save_file(&mut state).expect("save");
+        assert_eq!((summary.bytes_written, summary.line_count), (0, 0));
+        assert_eq!(summary.archive_name, None);
+        assert!(summary.full_text().ends_with(", new file"));
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod signal_session_tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quoted_resume_paths() {
+        assert_eq!(shell_quoted("/tmp/notes.txt"), "/tmp/notes.txt");
+        assert_eq!(
+            shell_quoted("/data/sessions/2026_10_16_ 7_45"),
+            "'/data/sessions/2026_10_16_ 7_45'"
+        );
+        assert_eq!(shell_quoted("it's"), "'it'\\''s'");
+        assert_eq!(shell_quoted(""), "''");
+    }
+}
+
+mod bracketed_paste_tests {
+    use super::editor_test_helpers::state_for;
+    use super::*;
+
+    /// The first read in the buffer, the rest from `more`
+    fn paste(state: &mut EditorState, first_read: &[u8], mut more: &[u8]) {
+        let mut text_buffer = [0u8; 256];
+        text_buffer[..first_read.len()].copy_from_slice(first_read);
+        let read_copy = state.read_copy_path.clone().expect("read copy");
+        state
+            .insert_bracketed_paste(&mut more, &mut text_buffer, first_read.len(), &read_copy)
+            .expect("paste");
+    }
+
+    #[test]
+    fn test_pasted_lines_are_text_and_undo_together() {
+        let (mut state, test_dir, file_path) = state_for("paste_lines", "start\n");
+        state.mode = EditorMode::Insert;
+
+        // One read per pasted line, the end marker before the Enter
+        paste(&mut state, b"\x1b[200~one\n", b"\x1b\n5j\nthree\x1b[201~\n");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "one\n\x1b\n5j\nthreestart\n"
+        );
+        assert_eq!(state.mode, EditorMode::Insert);
+
+        execute_command(&mut state, Command::UndoButtonsCommand).expect("u");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "start\n"
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_end_marker_split_between_reads() {
+        let (mut state, test_dir, file_path) = state_for("paste_split", "start\n");
+        state.mode = EditorMode::Insert;
+
+        paste(&mut state, b"\x1b[200~ab\x1b[20", b"1~cd\n");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "abcdstart\n"
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod archive_preview_tests {
+    use super::editor_test_helpers::message;
+    use super::*;
+
+    #[test]
+    fn test_parse_preview_archive() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("arc\n", EditorMode::Normal),
+            Command::PreviewArchive(None)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("arc3\n", EditorMode::Normal),
+            Command::PreviewArchive(Some(3))
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("arc0\n", EditorMode::Normal),
+            Command::None
+        );
+        assert_eq!(message(&state), "Use: arc, arc{n} (1 = newest)");
+        assert!(Command::PreviewArchive(None).is_read_only());
+    }
+
+    #[test]
+    fn test_preview_is_read_only_and_q_goes_back() {
+        let test_dir = std::env::temp_dir().join(format!("lines_arc_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let archive_dir = test_dir.join("archive");
+        std::fs::create_dir_all(&archive_dir).expect("create archive dir");
+        let original = test_dir.join("todo.txt");
+        let read_copy = test_dir.join("read_copy.txt");
+        std::fs::write(&original, "now one\nnow two\nnow three\n").expect("write original");
+        std::fs::copy(&original, &read_copy).expect("read-copy");
+        let older = archive_dir.join("2026_26_10_14_09_12_33_104233_todo.txt");
+        let newer = archive_dir.join("2026_26_10_15_08_00_00_000001_todo.txt");
+        std::fs::write(&older, "oldest\n").expect("write archive");
+        std::fs::write(&newer, "newer\n").expect("write archive");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(original.clone());
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+        execute_command(&mut state, Command::MoveDown(2)).expect("j");
+        let file_cursor = (state.cursor.tui_row, state.cursor.tui_visual_col);
+
+        // arc: newest first, then the next older one
+        execute_command(&mut state, Command::PreviewArchive(None)).expect("arc");
+        assert_eq!(state.read_copy_path.as_deref(), Some(newer.as_path()));
+        assert_eq!(state.cursor.tui_row, 0);
+        assert_eq!(message(&state), "arc 1/2 26-10-15 08:00:00");
+        assert_eq!(state.displayed_file_path(), Some(newer.as_path()));
+        execute_command(&mut state, Command::PreviewArchive(None)).expect("arc");
+        assert_eq!(state.read_copy_path.as_deref(), Some(older.as_path()));
+        execute_command(&mut state, Command::PreviewArchive(Some(3))).expect("arc3");
+        assert_eq!(message(&state), "no archive 3");
+        assert_eq!(state.read_copy_path.as_deref(), Some(older.as_path()));
+
+        // Edits are refused; the archive is untouched
+        execute_command(&mut state, Command::DeleteLine).expect("refused dd");
+        assert_eq!(message(&state), "archive is read only; q back");
+        assert_eq!(std::fs::read_to_string(&older).expect("read"), "oldest\n");
+
+        // q: back to the file, where the cursor was
+        assert!(execute_command(&mut state, Command::Quit).expect("q"));
+        assert_eq!(message(&state), "back to file");
+        assert_eq!(state.archive_preview, None);
+        assert_eq!(state.read_copy_path.as_deref(), Some(read_copy.as_path()));
+        assert_eq!(
+            (state.cursor.tui_row, state.cursor.tui_visual_col),
+            file_cursor
+        );
+        assert_eq!(state.displayed_file_path(), Some(original.as_path()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_no_archives() {
+        let test_dir = std::env::temp_dir().join(format!("lines_arc_none_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let original = test_dir.join("new.txt");
+        std::fs::write(&original, "text\n").expect("write original");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(original.clone());
+        state.read_copy_path = Some(original.clone());
+        build_windowmap_nowrap(&mut state, &original).expect("build window");
+        execute_command(&mut state, Command::PreviewArchive(None)).expect("arc");
+        assert_eq!(message(&state), "no archives for this file");
+        assert_eq!(state.archive_preview, None);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod archive_restore_tests {
+    use super::editor_test_helpers::message;
+    use super::*;
+
+    #[test]
+    fn test_restore_archives_the_current_file_first() {
+        let test_dir = std::env::temp_dir().join(format!("lines_arcr_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        let archive_dir = test_dir.join("archive");
+        std::fs::create_dir_all(&archive_dir).expect("create archive dir");
+        let original = test_dir.join("todo.txt");
+        let read_copy = test_dir.join("read_copy.txt");
+        std::fs::write(&original, "current\n").expect("write original");
+        std::fs::copy(&original, &read_copy).expect("read-copy");
+        let archive = archive_dir.join("2026_26_10_14_09_12_33_104233_todo.txt");
+        std::fs::write(&archive, "earlier\n").expect("write archive");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(original.clone());
+        state.read_copy_path = Some(read_copy.clone());
+        build_windowmap_nowrap(&mut state, &read_copy).expect("build window");
+
+        // Only with an archive shown
+        execute_command(&mut state, Command::RestoreArchive).expect("arcr");
+        assert_eq!(message(&state), "arcr: show one with arc first");
+
+        // Never over unsaved changes
+        execute_command(&mut state, Command::PreviewArchive(None)).expect("arc");
+        state.is_modified = true;
+        execute_command(&mut state, Command::RestoreArchive).expect("arcr");
+        assert_eq!(message(&state), "unsaved changes; not restored");
+        assert_eq!(
+            std::fs::read_to_string(&original).expect("read"),
+            "current\n"
+        );
+
+        state.is_modified = false;
+        execute_command(&mut state, Command::RestoreArchive).expect("arcr");
+        assert_eq!(message(&state), "restored 26-10-14 09:12:33");
+        assert_eq!(state.archive_preview, None);
+        assert_eq!(
+            std::fs::read_to_string(&original).expect("read"),
+            "earlier\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&read_copy).expect("read"),
+            "earlier\n"
+        );
+
+        // The file as it was is now the newest archive
+        let archives = crate::archive_module::list_archives(&original).expect("list");
+        assert_eq!(archives.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(&archives[0]).expect("read"),
+            "current\n"
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("arcr\n", EditorMode::Normal),
+            Command::RestoreArchive
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod info_bar_history_tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_keeps_the_newest_messages() {
+        let mut history = InfoBarHistory::new();
+        assert!(history.is_empty());
+        assert!(info_bar_history_text(&history, 0).contains("(none yet)"));
+
+        for number in 0..(INFOBAR_HISTORY_SIZE + 3) {
+            history.record(format!("message {}", number).as_bytes(), number as u64);
+        }
+        let kept: Vec<(String, u64)> = history
+            .newest_first()
+            .map(|(message, time)| (String::from_utf8_lossy(message).into_owned(), time))
+            .collect();
+        assert_eq!(kept.len(), INFOBAR_HISTORY_SIZE);
+        assert_eq!(
+            kept[0],
+            (
+                format!("message {}", INFOBAR_HISTORY_SIZE + 2),
+                (INFOBAR_HISTORY_SIZE + 2) as u64
+            )
+        );
+        assert_eq!(kept[INFOBAR_HISTORY_SIZE - 1], ("message 3".to_string(), 3));
+    }
+
+    #[test]
+    fn test_info_bar_messages_are_recorded_with_times() {
+        let mut state = EditorState::new();
+        execute_command(&mut state, Command::RestoreArchive).expect("arcr");
+        state.parse_commands_for_normal_visualselect_modes("arc0\n", EditorMode::Normal);
+        state
+            .info_bar_history
+            .record(b"saved 3 B, 1L, 0ms", 86_400 + 3_723);
+
+        let text = info_bar_history_text(&state.info_bar_history, 0);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[1], "  01:02:03 UTC  saved 3 B, 1L, 0ms");
+        assert!(lines[2].ends_with("UTC  Use: arc, arc{n} (1 = newest)"));
+        assert!(lines[3].ends_with("UTC  arcr: show one with arc first"));
+        assert_eq!(lines.len(), 4);
+
+        // LINES_UTC_OFFSET=-05:30
+        let text = info_bar_history_text(&state.info_bar_history, -330);
+        assert_eq!(
+            text.lines().nth(1),
+            Some("  19:32:03 -05:30  saved 3 B, 1L, 0ms")
+        );
+
+        state.scrub_sensitive_buffers();
+        assert!(state.info_bar_history.is_empty());
+    }
+}
+
+mod annotation_command_tests {
+    use super::editor_test_helpers::message;
+    use super::*;
+    use crate::annotation_module::{ANNOTATIONS_DIRECTORY_NAME, load_annotations};
+
+    #[test]
+    fn test_parse_note() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes(
+                "note check\tthis loop\n",
+                EditorMode::Normal
+            ),
+            Command::AnnotateLine("check this loop".to_string())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("note\n", EditorMode::Normal),
+            Command::AnnotateLine(String::new())
+        );
+        assert!(Command::AnnotateLine(String::new()).is_read_only());
+    }
+
+    #[test]
+    fn test_note_on_the_cursor_line_then_remove_it() {
+        let test_dir = std::env::temp_dir().join(format!("lines_note_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let original = test_dir.join("review.txt");
+        std::fs::write(&original, "one\ntwo\nthree\n").expect("write file");
+
+        let mut state = EditorState::new();
+        state.original_file_path = Some(original.clone());
+        state.read_copy_path = Some(original.clone());
+        build_windowmap_nowrap(&mut state, &original).expect("build window");
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+
+        execute_command(&mut state, Command::AnnotateLine("why two?".to_string())).expect("note");
+        assert_eq!(message(&state), "note on line 2");
+        let directory =
+            lines_data_subdirectory(ANNOTATIONS_DIRECTORY_NAME).expect("annotations dir");
+        let annotations = load_annotations(&directory, &original).expect("load");
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(
+            (annotations[0].line, annotations[0].note.as_str()),
+            (2, "why two?")
+        );
+        // The file itself is untouched
+        assert_eq!(
+            std::fs::read_to_string(&original).expect("read"),
+            "one\ntwo\nthree\n"
+        );
+
+        execute_command(&mut state, Command::AnnotateLine(String::new())).expect("note");
+        assert_eq!(message(&state), "note removed");
+        execute_command(&mut state, Command::AnnotateLine(String::new())).expect("note");
+        assert_eq!(message(&state), "no note on this line");
+        assert!(
+            load_annotations(&directory, &original)
+                .expect("load")
+                .is_empty()
+        );
+
+        state.security_mode = true;
+        execute_command(&mut state, Command::AnnotateLine("x".to_string())).expect("note");
+        assert_eq!(message(&state), "no notes in secure mode");
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod insert_file_directive_tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_file_directive_path() {
+        assert_eq!(
+            insert_file_directive_path("-r snippets/header.txt\n"),
+            Some("snippets/header.txt")
+        );
+        assert_eq!(
+            insert_file_directive_path("-r  a b.txt \r\n"),
+            Some("a b.txt")
+        );
+        assert_eq!(insert_file_directive_path("-r \n"), None);
+        assert_eq!(insert_file_directive_path(" -r x\n"), None);
+        assert_eq!(insert_file_directive_path("-rx\n"), None);
+        // Pasted lines are text, and so is a chunk with no Enter yet
+        assert_eq!(insert_file_directive_path("-r x\nmore\n"), None);
+        assert_eq!(insert_file_directive_path("-r x"), None);
+    }
+
+    #[test]
+    fn test_file_goes_in_at_cursor_and_undoes_in_one() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_insert_file_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("lines.txt");
+        let snippet = test_dir.join("snippet.txt");
+        std::fs::write(&file_path, "start\nend\n").expect("write test file");
+        std::fs::write(&snippet, "// header\n// block\n").expect("write snippet");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+        state.mode = EditorMode::Insert;
+
+        state
+            .insert_file_directive(&snippet, &file_path)
+            .expect("insert file");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "start\n// header\n// block\nend\n"
+        );
+        // The cursor is after the block, at the start of "end"
+        let position = state
+            .get_row_col_file_position(state.cursor.tui_row, state.cursor.tui_visual_col)
+            .expect("position")
+            .expect("cursor on text");
+        assert_eq!((position.line_number, position.byte_in_line), (3, 0));
+        assert_eq!(state.mode, EditorMode::Insert);
+
+        execute_command(&mut state, Command::UndoButtonsCommand).expect("u");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "start\nend\n"
+        );
+
+        // Not the file being edited, and nothing for a missing file
+        state
+            .insert_file_directive(&file_path, &file_path)
+            .expect("refused");
+        state
+            .insert_file_directive(&test_dir.join("none.txt"), &file_path)
+            .expect("missing");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "start\nend\n"
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod replace_in_line_tests {
+    use super::editor_test_helpers::{message, state_for};
+    use super::*;
+
+    #[test]
+    fn test_parse_replace_in_line() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("s/teh/the/\n", EditorMode::Normal),
+            Command::ReplaceInLine("teh".to_string(), "the".to_string())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("s/a/b\n", EditorMode::Normal),
+            Command::ReplaceInLine("a".to_string(), "b".to_string())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("s/x//\n", EditorMode::Normal),
+            Command::ReplaceInLine("x".to_string(), String::new())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("s/a/b/c/\n", EditorMode::Normal),
+            Command::ReplaceInLine("a".to_string(), "b/c".to_string())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("s\n", EditorMode::Normal),
+            Command::SaveFileStandard
+        );
+        assert_ne!(
+            state.parse_commands_for_normal_visualselect_modes("s//x/\n", EditorMode::Normal),
+            Command::ReplaceInLine(String::new(), "x".to_string())
+        );
+        assert!(!Command::ReplaceInLine("a".to_string(), "b".to_string()).is_read_only());
+    }
+
+    #[test]
+    fn test_replaces_first_on_the_cursor_line_and_undoes_in_one() {
+        let (mut state, test_dir, file_path) =
+            state_for("replace_first", "teh cat\nteh dog, teh end\n");
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+
+        execute_command(
+            &mut state,
+            Command::ReplaceInLine(
+                "teh".to_string(),
+                "the quick brown fox that jumps".to_string(),
+            ),
+        )
+        .expect("s");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "teh cat\nthe quick brown fox that jumps dog, teh end\n"
+        );
+        assert_eq!(message(&state), "replaced on line 2");
+        assert!(state.is_modified);
+
+        execute_command(&mut state, Command::UndoButtonsCommand).expect("u");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "teh cat\nteh dog, teh end\n"
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_match_must_be_on_the_cursor_line() {
+        let (mut state, test_dir, file_path) = state_for("replace_line", "alpha\n\nbeta gamma\n");
+
+        // "gamma" is on line 3 only; line 1 has no match
+        execute_command(
+            &mut state,
+            Command::ReplaceInLine("gamma".to_string(), "x".to_string()),
+        )
+        .expect("s");
+        assert_eq!(message(&state), "no match");
+        // Nor may a match run on from the end of the line
+        execute_command(
+            &mut state,
+            Command::ReplaceInLine("alpha\n".to_string(), "x".to_string()),
+        )
+        .expect("s");
+        assert_eq!(message(&state), "no match");
+
+        // The empty line
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(
+            &mut state,
+            Command::ReplaceInLine("beta".to_string(), "x".to_string()),
+        )
+        .expect("s");
+        assert_eq!(message(&state), "no match");
+
+        // Deleting with an empty replacement
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(
+            &mut state,
+            Command::ReplaceInLine("beta ".to_string(), String::new()),
+        )
+        .expect("s");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "alpha\n\ngamma\n"
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_parse_replace_all() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("%s/teh/the/\n", EditorMode::Normal),
+            Command::ReplaceAll("teh".to_string(), "the".to_string())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("%s/x/\n", EditorMode::Normal),
+            Command::ReplaceAll("x".to_string(), String::new())
+        );
+        assert_ne!(
+            state.parse_commands_for_normal_visualselect_modes("%s//x/\n", EditorMode::Normal),
+            Command::ReplaceAll(String::new(), "x".to_string())
+        );
+        assert!(!Command::ReplaceAll("a".to_string(), "b".to_string()).is_read_only());
+    }
+
+    #[test]
+    fn test_replace_all_streams_the_file_and_archives_it_first() {
+        // Matches land across the 256-byte read chunks
+        let line = "teh quick fox, teh lazy dog\n";
+        let content = line.repeat(40);
+        let (mut state, test_dir, file_path) = state_for("replace_all", &content);
+        let original_path = test_dir.join("original.txt");
+        std::fs::write(&original_path, &content).expect("write original");
+        state.original_file_path = Some(original_path.clone());
+        execute_command(&mut state, Command::MoveDown(2)).expect("j");
+
+        execute_command(
+            &mut state,
+            Command::ReplaceAll("teh".to_string(), "the".to_string()),
+        )
+        .expect("%s");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            content.replace("teh", "the")
+        );
+        assert_eq!(message(&state), "replaced 80 (arc: before)");
+        assert!(state.is_modified);
+        assert_eq!(state.cursor.tui_row, 2);
+        // The original is not saved, only backed up as it was
+        assert_eq!(
+            std::fs::read_to_string(&original_path).expect("read original"),
+            content
+        );
+
+        let archives: Vec<_> = std::fs::read_dir(test_dir.join("archive"))
+            .expect("archive dir")
+            .flatten()
+            .collect();
+        assert_eq!(archives.len(), 1);
+        assert!(
+            archives[0]
+                .file_name()
+                .to_string_lossy()
+                .ends_with("_original.txt")
+        );
+        assert_eq!(
+            std::fs::read_to_string(archives[0].path()).expect("read archive"),
+            content
+        );
+        assert!(!file_path.with_extension("tmp_replace_all").exists());
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_replace_all_undoes_with_one_u_and_keeps_history() {
+        // Longer than one log set each way, and shrinking then growing
+        let old = "a".repeat(30);
+        let new = "B".repeat(40);
+        let content = format!("x{}y\n{}\nz{}", old, old, old);
+        let (mut state, test_dir, file_path) = state_for("replace_all_undo", &content);
+
+        insert_text_chunk_at_cursor_position(&mut state, &file_path, b"#").expect("type");
+        let typed = format!("#{}", content);
+        assert_eq!(std::fs::read_to_string(&file_path).expect("read"), typed);
+
+        execute_command(&mut state, Command::ReplaceAll(old.clone(), new.clone())).expect("%s");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            typed.replace(&old, &new)
+        );
+        assert_eq!(message(&state), "replaced 3 (arc: before)");
+        assert!(!file_path.with_extension("tmp_replace_all_before").exists());
+
+        execute_command(&mut state, Command::UndoButtonsCommand).expect("u");
+        assert_eq!(std::fs::read_to_string(&file_path).expect("read"), typed);
+        // The edit before it is still there to undo
+        execute_command(&mut state, Command::UndoButtonsCommand).expect("u");
+        assert_eq!(std::fs::read_to_string(&file_path).expect("read"), content);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_replace_all_without_a_match_changes_nothing() {
+        let (mut state, test_dir, file_path) = state_for("replace_all_none", "alpha\nbeta\n");
+        let original_path = test_dir.join("original.txt");
+        std::fs::write(&original_path, "alpha\nbeta\n").expect("write original");
+        state.original_file_path = Some(original_path);
+
+        execute_command(
+            &mut state,
+            Command::ReplaceAll("gamma".to_string(), "x".to_string()),
+        )
+        .expect("%s");
+        assert_eq!(message(&state), "no match");
+        assert!(!state.is_modified);
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "alpha\nbeta\n"
+        );
+        assert!(!test_dir.join("archive").exists());
+        assert!(!file_path.with_extension("tmp_replace_all").exists());
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_dot_repeats_the_last_edit_and_n_dot_at_the_next_match() {
+        let (mut state, test_dir, file_path) =
+            state_for("replace_dot", "teh a\nok\nteh b\nteh c\nteh d\n");
+        for (typed, command) in [
+            (".\n", Command::RepeatLastEdit),
+            ("n.\n", Command::RepeatSearchAndEdit(false)),
+            ("N.\n", Command::RepeatSearchAndEdit(true)),
+        ] {
+            assert_eq!(
+                state.parse_commands_for_normal_visualselect_modes(typed, EditorMode::Normal),
+                command
+            );
+            assert!(!command.is_read_only());
+        }
+
+        // Nothing to repeat yet: n. does not move either
+        execute_command(&mut state, Command::RepeatLastEdit).expect(".");
+        assert_eq!(message(&state), "no edit to repeat");
+        execute_command(&mut state, Command::SearchForward("teh".to_string())).expect("/teh");
+        execute_command(&mut state, Command::RepeatSearchAndEdit(false)).expect("n.");
+        assert_eq!(message(&state), "no edit to repeat");
+        assert_eq!(state.cursor.tui_row, 2);
+
+        // s/ on line 3, then . on line 4
+        execute_command(
+            &mut state,
+            Command::ReplaceInLine("teh".to_string(), "the".to_string()),
+        )
+        .expect("s");
+        execute_command(&mut state, Command::MoveDown(1)).expect("j");
+        execute_command(&mut state, Command::RepeatLastEdit).expect(".");
+        assert_eq!(message(&state), "replaced on line 4");
+
+        // From the top, n. passes the cursor's own match: line 5, then no
+        // further match and no edit; N. goes back for line 1
+        execute_command(&mut state, Command::GotoLine(1)).expect("g1");
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+        execute_command(&mut state, Command::RepeatSearchAndEdit(false)).expect("n.");
+        assert_eq!(message(&state), "replaced on line 5");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "teh a\nok\nthe b\nthe c\nthe d\n"
+        );
+        execute_command(&mut state, Command::RepeatSearchAndEdit(false)).expect("n.");
+        assert_eq!(message(&state), "no match");
+        execute_command(&mut state, Command::RepeatSearchAndEdit(true)).expect("N.");
+        assert_eq!(message(&state), "replaced on line 1");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "the a\nok\nthe b\nthe c\nthe d\n"
+        );
+
+        // Line commands are repeated on the cursor's row; motions are not kept
+        assert_eq!(
+            Command::IndentOneLine(0).at_cursor_row(4),
+            Command::IndentOneLine(4)
+        );
+        assert_eq!(Command::MoveDown(1).repeatable_edit(), None);
+        assert_eq!(
+            Command::ReplaceAll("a".to_string(), "b".to_string()).repeatable_edit(),
+            None
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod fuzzy_line_search_tests {
+    use super::editor_test_helpers::message;
+    use super::*;
+
+    fn test_file(name: &str, content: &str) -> (PathBuf, PathBuf) {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_fuzzy_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("code.rs");
+        std::fs::write(&file_path, content).expect("write test file");
+        (test_dir, file_path)
+    }
+
+    #[test]
+    fn test_parse_fuzzy_line_search() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("f hdlins\n", EditorMode::Normal),
+            Command::FuzzyLineSearch("hdlins".to_string())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("f fn main \n", EditorMode::Normal),
+            Command::FuzzyLineSearch("fn main".to_string())
+        );
+        // f{char} is unchanged, a space target included
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("fx\n", EditorMode::Normal),
+            Command::FindCharInLine('x', 1)
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("f \n", EditorMode::Normal),
+            Command::FindCharInLine(' ', 1)
+        );
+        assert!(Command::FuzzyLineSearch("x".to_string()).is_read_only());
+    }
+
+    #[test]
+    fn test_best_fuzzy_line_prefers_word_starts_and_runs() {
+        let (test_dir, file_path) = test_file(
+            "best",
+            "fn handle_normal() {}\nlet hidden_lines = 0;\nfn handle_insert() {}\nHANDLE_INSERT\n",
+        );
+        let best = best_fuzzy_line(&file_path, b"hdlins")
+            .expect("scan")
+            .expect("match");
+        // Line 2 also fits (h-d-l in "hidden_lines"), with a lower score
+        assert_eq!(best.line_index, 2);
+        assert_eq!(
+            best.first_match_byte,
+            "fn handle_normal() {}\nlet hidden_lines = 0;\nfn ".len() as u64
+        );
+        // Case only adds a point: the typed case wins
+        assert_eq!(
+            best_fuzzy_line(&file_path, b"HANDLE_INS")
+                .expect("scan")
+                .expect("match")
+                .line_index,
+            3
+        );
+        assert_eq!(best_fuzzy_line(&file_path, b"zq").expect("scan"), None);
+        assert_eq!(best_fuzzy_line(&file_path, b"").expect("scan"), None);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_fuzzy_match_across_chunks_and_without_final_newline() {
+        let long_line = format!("{}needle", "x".repeat(limits::LONG_LINE_SCAN_BYTES));
+        let (test_dir, file_path) = test_file("chunks", &format!("nope\n{}\nlast nedl", long_line));
+        let best = best_fuzzy_line(&file_path, b"needle")
+            .expect("scan")
+            .expect("match");
+        assert_eq!(best.line_index, 1);
+        assert_eq!(
+            best.first_match_byte,
+            5 + limits::LONG_LINE_SCAN_BYTES as u64
+        );
+        assert_eq!(
+            best_fuzzy_line(&file_path, b"lstndl")
+                .expect("scan")
+                .expect("match")
+                .line_index,
+            2
+        );
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_fuzzy_search_moves_to_the_line_and_reports_the_score() {
+        let (test_dir, file_path) = test_file("move", "// top\n\nfn main() {\n    run_all();\n}\n");
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+
+        execute_command(&mut state, Command::FuzzyLineSearch("rnal".to_string())).expect("f");
+        assert_eq!(state.line_count_at_top_of_window + state.cursor.tui_row, 3);
+        assert!(message(&state).starts_with("f: line 4 score "));
+        // On the 'r' of run_all, four columns in
+        let column = state.cursor.tui_visual_col;
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+        assert_eq!(column, state.cursor.tui_visual_col + 4);
+
+        execute_command(&mut state, Command::FuzzyLineSearch("zzz".to_string())).expect("f");
+        assert_eq!(message(&state), "no fuzzy match");
+        assert_eq!(state.line_count_at_top_of_window + state.cursor.tui_row, 3);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod regex_lite_tests {
+    use super::editor_test_helpers::{message, state_for};
+    use super::*;
+    use crate::regex_lite_module::{MAX_PATTERN_BYTES, Regex, RegexError};
+
+    fn find(pattern: &str, text: &str) -> Option<(usize, usize)> {
+        Regex::new(pattern.as_bytes())
+            .expect("pattern")
+            .find(text.as_bytes())
+            .expect("find")
+    }
+
+    #[test]
+    fn test_bad_patterns() {
+        assert_eq!(Regex::new(b""), Err(RegexError::Empty));
+        assert_eq!(
+            Regex::new(&[b'a'; MAX_PATTERN_BYTES + 1]),
+            Err(RegexError::TooLong)
+        );
+        assert!(Regex::new(&[b'a'; MAX_PATTERN_BYTES]).is_ok());
+        assert_eq!(Regex::new(b"[abc"), Err(RegexError::UnclosedClass));
+        assert_eq!(Regex::new(b"[]"), Err(RegexError::UnclosedClass));
+        assert_eq!(Regex::new(b"*a"), Err(RegexError::NothingToRepeat));
+        assert_eq!(Regex::new(b"^+"), Err(RegexError::NothingToRepeat));
+        assert_eq!(Regex::new(b"a**"), Err(RegexError::NothingToRepeat));
+        assert_eq!(Regex::new(b"ab\\"), Err(RegexError::TrailingBackslash));
+        for error in [
+            RegexError::Empty,
+            RegexError::TooLong,
+            RegexError::UnclosedClass,
+            RegexError::NothingToRepeat,
+            RegexError::TrailingBackslash,
+            RegexError::TooComplex,
+        ] {
+            assert!(error.message().len() <= 31, "{}", error);
+        }
+    }
+
+    #[test]
+    fn test_literal_dot_and_escapes() {
+        assert_eq!(find("cat", "concatenate"), Some((3, 6)));
+        assert_eq!(find("c.t", "a cut"), Some((2, 5)));
+        assert_eq!(find("x", "abc"), None);
+        assert_eq!(find("a\\.b", "axb a.b"), Some((4, 7)));
+        assert_eq!(find("\\*\\[", "a*[b"), Some((1, 3)));
+        assert_eq!(find("\\\\", "a\\b"), Some((1, 2)));
+    }
+
+    #[test]
+    fn test_repeats_are_greedy_and_backtrack() {
+        assert_eq!(find("ab*", "xabbbc"), Some((1, 5)));
+        assert_eq!(find("ab*c", "ac"), Some((0, 2)));
+        assert_eq!(find("ab+c", "ac abbc"), Some((3, 7)));
+        assert_eq!(find("colou?r", "color colour"), Some((0, 5)));
+        assert_eq!(find("colou?r", "colour"), Some((0, 6)));
+        // .* gives back what the rest needs
+        assert_eq!(find("a.*b", "a1b2b3"), Some((0, 5)));
+        assert_eq!(find("a.*b.*c", "a b c b"), Some((0, 5)));
+        // May match nothing, at the first place it can
+        assert_eq!(find("x*", "abc"), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_classes() {
+        assert_eq!(find("[0-9]+", "line 42;"), Some((5, 7)));
+        assert_eq!(find("[^ ]+$", "two words"), Some((4, 9)));
+        assert_eq!(find("[a-z_]+\\(", "Call do_it()"), Some((5, 11)));
+        // ']' first and '-' last are themselves
+        assert_eq!(find("[]x]", "a]"), Some((1, 2)));
+        assert_eq!(find("[a-]+", "b-a-c"), Some((1, 4)));
+        assert_eq!(find("[\\]\\\\]", "a\\"), Some((1, 2)));
+        assert_eq!(find("[^a-z]", "abcD"), Some((3, 4)));
+    }
+
+    #[test]
+    fn test_anchors() {
+        assert_eq!(find("^fn", "fn main"), Some((0, 2)));
+        assert_eq!(find("^fn", " fn main"), None);
+        assert_eq!(find("end$", "the end"), Some((4, 7)));
+        assert_eq!(find("end$", "end of"), None);
+        assert_eq!(find("^$", ""), Some((0, 0)));
+        assert_eq!(find("^.*$", "whole"), Some((0, 5)));
+        // Escaped or in the middle they are themselves
+        assert_eq!(find("\\^a", "b^a"), Some((1, 3)));
+        assert_eq!(find("5\\$", "$5$"), Some((1, 3)));
+        assert_eq!(find("a^b$c", "a^b$c"), Some((0, 5)));
+        assert_eq!(find("a\\\\$", "a\\"), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_whole_utf8_characters() {
+        assert_eq!(find(".", "é"), Some((0, 2)));
+        assert_eq!(find("a.b", "aéb"), Some((0, 4)));
+        assert_eq!(find("x.*", "xé€"), Some((0, 6)));
+        assert_eq!(find("é+", "aéé"), Some((1, 5)));
+        assert_eq!(find("[é€]+", "aé€b"), Some((1, 6)));
+        assert_eq!(find("[à-ÿ]", "zè"), Some((1, 3)));
+        assert_eq!(find("[^a-z]", "aéb"), Some((1, 3)));
+        assert_eq!(find("[^é]", "éx"), Some((2, 3)));
+        // Backtracking gives back whole characters
+        assert_eq!(find(".*€", "é€€x"), Some((0, 8)));
+        // A stray byte is one on its own
+        let regex = Regex::new(b"a.b").expect("pattern");
+        assert_eq!(regex.find(b"a\xffb"), Ok(Some((0, 3))));
+        // find_from inside a character starts at the next one
+        let any = Regex::new(b".").expect("pattern");
+        assert_eq!(any.find_from("éx".as_bytes(), 1), Ok(Some((2, 3))));
+    }
+
+    #[test]
+    fn test_find_from_and_giving_up() {
+        let regex = Regex::new(b"ab").expect("pattern");
+        assert_eq!(regex.find_from(b"ab ab", 1), Ok(Some((3, 5))));
+        let anchored = Regex::new(b"^ab").expect("pattern");
+        assert_eq!(anchored.find_from(b"ab ab", 1), Ok(None));
+        assert_eq!(regex.find_from(b"ab", 3), Ok(None));
+
+        let slow = Regex::new(b"a*a*a*a*a*a*a*a*a*a*b").expect("pattern");
+        assert_eq!(
+            slow.find("a".repeat(200).as_bytes()),
+            Err(RegexError::TooComplex)
+        );
+    }
+
+    #[test]
+    fn test_parse_regex_commands() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state
+                .parse_commands_for_normal_visualselect_modes("rx/fn [a-z]+\n", EditorMode::Normal),
+            Command::RegexSearch("fn [a-z]+".to_string())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes(
+                "rxs/[0-9]+/N/\n",
+                EditorMode::Normal
+            ),
+            Command::RegexReplaceInLine("[0-9]+".to_string(), "N".to_string())
+        );
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("rxs/a\\/b/c\n", EditorMode::Normal),
+            Command::RegexReplaceInLine("a\\/b".to_string(), "c".to_string())
+        );
+        assert_eq!(split_at_unescaped_slash("a\\\\/b"), Some(("a\\\\", "b")));
+        assert_eq!(split_at_unescaped_slash("a\\/b"), None);
+        assert!(Command::RegexSearch("x".to_string()).is_read_only());
+        assert!(!Command::RegexReplaceInLine("x".to_string(), String::new()).is_read_only());
+    }
+
+    #[test]
+    fn test_regex_search_and_replace_in_the_editor() {
+        let test_dir = std::env::temp_dir().join(format!("lines_regex_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("code.rs");
+        let long_line = "x".repeat(limits::REGEX_LINE_MAX_BYTES + 10);
+        std::fs::write(
+            &file_path,
+            format!(
+                "let a = 1;\n{}fn\n  fn first() {{}}\nfn second() {{}}\n",
+                long_line
+            ),
+        )
+        .expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+
+        // Past the long line (matched in its first part only), and "^fn"
+        // skips the indented one
+        execute_command(&mut state, Command::RegexSearch("^fn [a-z]+".to_string())).expect("rx");
+        assert_eq!(message(&state), "found at line 4");
+        assert_eq!(state.line_count_at_top_of_window + state.cursor.tui_row, 3);
+
+        execute_command(&mut state, Command::RegexSearch("[0-9]$".to_string())).expect("rx");
+        assert_eq!(message(&state), "no match");
+        execute_command(&mut state, Command::RegexSearch("[".to_string())).expect("rx");
+        assert_eq!(message(&state), "rx: [ without ]");
+
+        execute_command(
+            &mut state,
+            Command::RegexReplaceInLine("s[a-z]*".to_string(), "third".to_string()),
+        )
+        .expect("rxs");
+        assert_eq!(message(&state), "replaced on line 4");
+        let content = std::fs::read_to_string(&file_path).expect("read");
+        assert!(content.ends_with("  fn first() {}\nfn third() {}\n"));
+
+        // An empty match with nothing to put in
+        execute_command(
+            &mut state,
+            Command::RegexReplaceInLine("q*".to_string(), String::new()),
+        )
+        .expect("rxs");
+        assert_eq!(message(&state), "nothing to replace");
+
+        execute_command(&mut state, Command::UndoButtonsCommand).expect("u");
+        let content = std::fs::read_to_string(&file_path).expect("read");
+        assert!(content.ends_with("fn second() {}\n"));
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+
+    #[test]
+    fn test_regex_replace_takes_whole_characters() {
+        let (mut state, test_dir, file_path) = state_for("regex_utf8", "é\n");
+        execute_command(
+            &mut state,
+            Command::RegexReplaceInLine(".".to_string(), "x".to_string()),
+        )
+        .expect("rxs");
+        assert_eq!(std::fs::read_to_string(&file_path).expect("read"), "x\n");
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod insert_mode_undo_tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_mode_undo_redo() {
+        assert_eq!(
+            insert_mode_undo_redo("-u\n"),
+            Some(Command::UndoButtonsCommand)
+        );
+        assert_eq!(
+            insert_mode_undo_redo("-re\r\n"),
+            Some(Command::RedoButtonsCommand)
+        );
+        // Anything else is text to type in
+        assert_eq!(insert_mode_undo_redo(" -u\n"), None);
+        assert_eq!(insert_mode_undo_redo("-u x\n"), None);
+        assert_eq!(insert_mode_undo_redo("a\n-u\n"), None);
+        assert_eq!(insert_mode_undo_redo("-u"), None);
+    }
+
+    #[test]
+    fn test_undo_and_redo_stay_in_insert_mode() {
+        let test_dir =
+            std::env::temp_dir().join(format!("lines_insert_undo_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&test_dir);
+        std::fs::create_dir_all(&test_dir).expect("create test dir");
+        let file_path = test_dir.join("lines.txt");
+        std::fs::write(&file_path, "start\n").expect("write test file");
+
+        let mut state = EditorState::new();
+        state.read_copy_path = Some(file_path.clone());
+        build_windowmap_nowrap(&mut state, &file_path).expect("build window");
+        execute_command(&mut state, Command::GotoLineStart).expect("gh");
+        state.mode = EditorMode::Insert;
+
+        insert_text_chunk_at_cursor_position(&mut state, &file_path, b"x").expect("type");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "xstart\n"
+        );
+        let undo = insert_mode_undo_redo("-u\n").expect("undo");
+        execute_command(&mut state, undo).expect("-u");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "start\n"
+        );
+        assert_eq!(state.mode, EditorMode::Insert);
+
+        let redo = insert_mode_undo_redo("-re\n").expect("redo");
+        execute_command(&mut state, redo).expect("-re");
+        assert_eq!(
+            std::fs::read_to_string(&file_path).expect("read"),
+            "xstart\n"
+        );
+        assert_eq!(state.mode, EditorMode::Insert);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    }
+}
+
+mod repeat_count_cap_tests {
+    use super::editor_test_helpers::message;
+    use super::*;
+
+    #[test]
+    fn test_parser_caps_the_count() {
+        let mut state = EditorState::new();
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("1000000j\n", EditorMode::Normal),
+            Command::MoveDown(limits::CURSOR_MOVEMENT_STEPS)
+        );
+        assert_eq!(message(&state), "");
+
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes("5000000j\n", EditorMode::Normal),
+            Command::MoveDown(limits::CURSOR_MOVEMENT_STEPS)
+        );
+        assert_eq!(message(&state), "count capped at 1000000");
+
+        // The longest count the parser reads is capped the same way
+        assert_eq!(
+            state.parse_commands_for_normal_visualselect_modes(
+                "9999999999999999999w\n",
+                EditorMode::Normal
+            ),
+            Command::MoveWordForward(limits::CURSOR_MOVEMENT_STEPS)
+        );
+    }
+
+    #[test]
+    fn test_command_caps_say_so() {
+        let mut state = EditorState::new();
+        assert_eq!(state.capped_repeat_count(7, limits::PUT_REPEAT_MAX), 7);
+        assert_eq!(message(&state), "");
+        assert_eq!(
+            state.capped_repeat_count(500, limits::PUT_REPEAT_MAX),
+            limits::PUT_REPEAT_MAX
+        );
+        assert_eq!(message(&state), "count capped at 100");
+    }
+}
+
+mod selfcheck_tests {
+    use super::*;
+
+    #[test]
+    fn test_run_selfcheck_passes_and_cleans_up() {
+        let mut out = Vec::new();
+        let failures = run_selfcheck(&mut out, false).expect("selfcheck runs");
+        let report = String::from_utf8(out).expect("utf-8 report");
+        assert_eq!(failures, 0, "{}", report);
+
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.last(), Some(&"selfcheck: pass"));
+        for name in [
+            "data directory",
+            "timestamps",
+            "session files",
+            "render frame",
+            "session cleanup",
+        ] {
+            assert!(
+                lines
+                    .iter()
+                    .any(|line| line.starts_with("ok") && line.contains(name)),
+                "{}",
+                report
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_selfcheck_porcelain_records() {
+        let mut out = Vec::new();
+        let failures = run_selfcheck(&mut out, true).expect("selfcheck runs");
+        let report = String::from_utf8(out).expect("utf-8 report");
+        assert_eq!(failures, 0, "{}", report);
+
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.last(), Some(&"selfcheck\tpass"));
+        let names: Vec<&str> = lines[..lines.len() - 1]
+            .iter()
+            .map(|line| {
+                let fields: Vec<&str> = line.splitn(4, '\t').collect();
+                assert_eq!(fields.len(), 4, "{}", line);
+                assert_eq!(&fields[..2], ["check", "ok"]);
+                fields[2]
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "data_directory",
+                "timestamps",
+                "session_files",
+                "render_frame",
+                "session_cleanup"
+            ]
+        );
+    }
+}
+
+mod source_files_tests {
+    /// `lines --source` must write a tree that builds: every `mod x;` in
+    /// main.rs has its src/x.rs embedded
+    #[test]
+    fn test_every_module_is_embedded() {
+        for line in include_str!("main.rs").lines() {
+            let Some(name) = line
+                .strip_prefix("mod ")
+                .and_then(|rest| rest.strip_suffix(';'))
+            else {
+                continue;
+            };
+            let path = format!("src/{}.rs", name);
+            assert!(
+                crate::SOURCE_FILES.iter().any(|file| file.path == path),
+                "{} is not in SOURCE_FILES",
+                path
+            );
+        }
+    }
+}